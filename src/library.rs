@@ -1,11 +1,29 @@
+mod cache;
+mod client;
 mod collection;
+mod fixtures;
 mod library;
 mod media;
+mod omdb;
+mod provider;
 mod scrape;
+mod sidecar;
+mod tmdb;
+mod tvdb;
+mod tvmaze;
 mod util;
 
+pub use cache::*;
+pub use client::*;
 pub use collection::*;
+pub use fixtures::*;
 pub use library::*;
 pub use media::*;
+pub use omdb::*;
+pub use provider::*;
 pub use scrape::*;
+pub use sidecar::*;
+pub use tmdb::DEFAULT_API_BASE;
+pub use tvdb::*;
+pub use tvmaze::*;
 pub use util::*;
@@ -0,0 +1,76 @@
+use futures::StreamExt;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::path::{Path, PathBuf};
+
+/// How long a cached response is used as is, before it has to be revalidated.
+const TTL: chrono::TimeDelta = chrono::TimeDelta::days(1);
+
+/// Responses from metadata providers, kept under the storage directory so repeated scans don't
+/// repeat the same requests and previously seen titles can be scraped offline.
+#[derive(Debug, Clone)]
+pub struct ResponseCache {
+    dir: PathBuf,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct CachedResponse {
+    pub url: String,
+    pub fetched: chrono::DateTime<chrono::Local>,
+    pub etag: Option<String>,
+    pub last_modified: Option<String>,
+    pub body: String,
+}
+
+impl CachedResponse {
+    pub fn is_fresh(&self) -> bool {
+        chrono::Local::now() - self.fetched < TTL
+    }
+}
+
+impl ResponseCache {
+    pub fn new(storage: &Path) -> Self {
+        ResponseCache {
+            dir: cache_dir(storage),
+        }
+    }
+
+    /// `key` should identify the request, including its language, but not any credentials.
+    fn path(&self, key: &str) -> PathBuf {
+        self.dir.join(format!("{:x}.json", Sha256::digest(key)))
+    }
+
+    pub async fn get(&self, key: &str) -> Option<CachedResponse> {
+        let data = async_std::fs::read(self.path(key)).await.ok()?;
+        let response: CachedResponse = serde_json::from_slice(&data).ok()?;
+        // guards against the unlikely hash collision
+        (response.url == key).then_some(response)
+    }
+
+    pub async fn insert(&self, response: &CachedResponse) -> anyhow::Result<()> {
+        async_std::fs::create_dir_all(&self.dir).await?;
+        async_std::fs::write(self.path(&response.url), serde_json::to_vec(response)?).await?;
+        Ok(())
+    }
+}
+
+fn cache_dir(storage: &Path) -> PathBuf {
+    storage.join("cache").join("metadata")
+}
+
+/// Deletes every cached response, returning how many bytes were freed.
+pub async fn clear_metadata_cache(storage: &Path) -> anyhow::Result<u64> {
+    let mut entries = match async_std::fs::read_dir(cache_dir(storage)).await {
+        Ok(entries) => entries,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(0),
+        Err(err) => return Err(err.into()),
+    };
+
+    let mut freed = 0;
+    while let Some(entry) = entries.next().await {
+        let entry = entry?;
+        freed += entry.metadata().await?.len();
+        async_std::fs::remove_file(entry.path()).await?;
+    }
+    Ok(freed)
+}
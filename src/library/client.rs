@@ -0,0 +1,319 @@
+use super::{CachedResponse, Fixtures, Provider, ResponseCache};
+use serde::de::DeserializeOwned;
+use std::{
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+/// Attempts per request when a provider is rate limiting, failing or unreachable.
+const MAX_ATTEMPTS: u32 = 5;
+const INITIAL_BACKOFF: Duration = Duration::from_millis(500);
+const MAX_BACKOFF: Duration = Duration::from_secs(30);
+
+/// Token bucket shared by every client of one provider, as providers limit by address rather
+/// than by key.
+pub struct RateLimiter {
+    requests_per_second: f64,
+    burst: f64,
+    /// Tokens available, and when they were last topped up.
+    bucket: Mutex<(f64, Instant)>,
+}
+
+impl RateLimiter {
+    pub fn new(requests_per_second: f64, burst: f64) -> Self {
+        RateLimiter {
+            requests_per_second,
+            burst,
+            bucket: Mutex::new((burst, Instant::now())),
+        }
+    }
+
+    async fn acquire(&self) {
+        loop {
+            let wait = {
+                let mut bucket = self.bucket.lock().unwrap();
+                let (tokens, refilled) = &mut *bucket;
+                let now = Instant::now();
+                *tokens = (*tokens + (now - *refilled).as_secs_f64() * self.requests_per_second)
+                    .min(self.burst);
+                *refilled = now;
+
+                if *tokens >= 1.0 {
+                    *tokens -= 1.0;
+                    return;
+                }
+                Duration::from_secs_f64((1.0 - *tokens) / self.requests_per_second)
+            };
+            async_std::task::sleep(wait).await;
+        }
+    }
+}
+
+/// A provider turned down the credentials a request was sent with, so every other request to it
+/// will fail as well.
+#[derive(Debug, Clone, Copy)]
+pub struct Unauthorized(pub Provider);
+
+impl std::fmt::Display for Unauthorized {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} rejected the API key", self.0)
+    }
+}
+
+impl std::error::Error for Unauthorized {}
+
+/// How requests prove who they are from.
+#[derive(Debug, Clone)]
+pub enum Auth {
+    None,
+    Bearer(String),
+    /// A query parameter, such as `api_key`.
+    Query(&'static str, String),
+}
+
+/// Fetches JSON from a metadata provider's API, rate limited, retried, cached and optionally
+/// recorded.
+#[derive(Clone)]
+pub struct Client {
+    provider: Provider,
+    http: reqwest::Client,
+    auth: Auth,
+    api_base: String,
+    limiter: &'static RateLimiter,
+    cache: Option<ResponseCache>,
+    fixtures: Option<Fixtures>,
+}
+
+enum Response {
+    Fresh {
+        body: String,
+        etag: Option<String>,
+        last_modified: Option<String>,
+    },
+    NotModified,
+}
+
+impl Client {
+    pub fn new(
+        provider: Provider,
+        api_base: &str,
+        auth: Auth,
+        limiter: &'static RateLimiter,
+    ) -> Self {
+        Client {
+            provider,
+            http: reqwest::Client::builder()
+                .timeout(Duration::from_secs(30))
+                .build()
+                .unwrap_or_default(),
+            auth,
+            api_base: api_base.trim_end_matches('/').to_string(),
+            limiter,
+            cache: None,
+            fixtures: None,
+        }
+    }
+
+    /// Sends requests to a mirror or stand-in server instead of the provider itself.
+    pub fn api_base(mut self, api_base: &str) -> Self {
+        self.api_base = api_base.trim_end_matches('/').to_string();
+        self
+    }
+
+    pub fn auth(mut self, auth: Auth) -> Self {
+        self.auth = auth;
+        self
+    }
+
+    /// Reads responses through `cache`, revalidating them once they go stale.
+    pub fn cache(mut self, cache: ResponseCache) -> Self {
+        self.cache = Some(cache);
+        self
+    }
+
+    /// Records every response into `fixtures`, or answers only from them when replaying.
+    pub fn fixtures(mut self, fixtures: Fixtures) -> Self {
+        self.fixtures = Some(fixtures);
+        self
+    }
+
+    fn request(&self, path: &str, query: &[(&str, String)]) -> reqwest::RequestBuilder {
+        let request = self
+            .http
+            .get(format!("{}{}", self.api_base, path))
+            .query(query);
+
+        match &self.auth {
+            Auth::None => request,
+            Auth::Bearer(token) => request.bearer_auth(token),
+            Auth::Query(name, secret) => request.query(&[(name, secret)]),
+        }
+    }
+
+    fn check_auth(&self, response: reqwest::Response) -> Result<reqwest::Response, Unauthorized> {
+        if response.status() == reqwest::StatusCode::UNAUTHORIZED {
+            return Err(Unauthorized(self.provider));
+        }
+        Ok(response)
+    }
+
+    pub async fn get<T: DeserializeOwned>(
+        &self,
+        path: &str,
+        query: &[(&str, String)],
+    ) -> anyhow::Result<T> {
+        // recordings of different providers share a directory
+        let recorded = format!("/{}{}", self.provider.slug(), path);
+        let body = match &self.fixtures {
+            Some(fixtures) if fixtures.is_replaying() => {
+                fixtures.response(&recorded, query).await?
+            }
+            _ => self.fetch(path, query).await?,
+        };
+        if let Some(fixtures) = &self.fixtures {
+            fixtures.record_response(&recorded, query, &body).await;
+        }
+        Ok(serde_json::from_str(&body)?)
+    }
+
+    /// Sends `body` as JSON, for requests such as logging in that are never cached or recorded.
+    pub async fn post<T: DeserializeOwned>(
+        &self,
+        path: &str,
+        body: &serde_json::Value,
+    ) -> anyhow::Result<T> {
+        self.limiter.acquire().await;
+        let response = self
+            .http
+            .post(format!("{}{}", self.api_base, path))
+            .header(reqwest::header::CONTENT_TYPE, "application/json")
+            .body(body.to_string())
+            .send()
+            .await?;
+        let response = self.check_auth(response)?.error_for_status()?;
+        Ok(serde_json::from_str(&response.text().await?)?)
+    }
+
+    /// The response body, read through the cache if there is one.
+    async fn fetch(&self, path: &str, query: &[(&str, String)]) -> anyhow::Result<String> {
+        let Some(cache) = &self.cache else {
+            return match self.send(path, query, None).await? {
+                Response::Fresh { body, .. } => Ok(body),
+                Response::NotModified => unreachable!("not a conditional request"),
+            };
+        };
+
+        // the language is part of the query, and credentials never are
+        let key = reqwest::Url::parse_with_params(&format!("{}{}", self.api_base, path), query)?
+            .to_string();
+        let cached = cache.get(&key).await;
+        if let Some(cached) = cached.as_ref().filter(|cached| cached.is_fresh()) {
+            return Ok(cached.body.clone());
+        }
+
+        let response = match (self.send(path, query, cached.as_ref()).await, cached) {
+            (
+                Ok(Response::Fresh {
+                    body,
+                    etag,
+                    last_modified,
+                }),
+                _,
+            ) => CachedResponse {
+                url: key,
+                fetched: chrono::Local::now(),
+                etag,
+                last_modified,
+                body,
+            },
+            (Ok(Response::NotModified), Some(cached)) => CachedResponse {
+                fetched: chrono::Local::now(),
+                ..cached
+            },
+            (Ok(Response::NotModified), None) => {
+                return Err(anyhow::anyhow!("unexpected 304 for {}", path));
+            }
+            // offline, so a stale response is better than none
+            (Err(err), Some(cached)) if is_network_error(&err) => {
+                log::warn!("using stale cached response for {}: {:#}", path, err);
+                return Ok(cached.body);
+            }
+            (Err(err), _) => return Err(err),
+        };
+
+        if let Err(err) = cache.insert(&response).await {
+            log::warn!("failed to cache response for {}: {:#}", path, err);
+        }
+        Ok(response.body)
+    }
+
+    /// Rate limited, and retried with exponential backoff on 429s, server errors and timeouts.
+    /// Revalidates `cached` if given.
+    async fn send(
+        &self,
+        path: &str,
+        query: &[(&str, String)],
+        cached: Option<&CachedResponse>,
+    ) -> anyhow::Result<Response> {
+        let mut attempt = 0;
+        loop {
+            self.limiter.acquire().await;
+            let mut request = self.request(path, query);
+            if let Some(etag) = cached.and_then(|cached| cached.etag.as_ref()) {
+                request = request.header(reqwest::header::IF_NONE_MATCH, etag);
+            }
+            if let Some(modified) = cached.and_then(|cached| cached.last_modified.as_ref()) {
+                request = request.header(reqwest::header::IF_MODIFIED_SINCE, modified);
+            }
+            let result = request.send().await;
+            attempt += 1;
+
+            let retryable = match &result {
+                Ok(response) => {
+                    response.status() == reqwest::StatusCode::TOO_MANY_REQUESTS
+                        || response.status().is_server_error()
+                }
+                Err(err) => err.is_timeout() || err.is_connect(),
+            };
+            if !retryable || attempt >= MAX_ATTEMPTS {
+                let response = self.check_auth(result?)?.error_for_status()?;
+                if response.status() == reqwest::StatusCode::NOT_MODIFIED {
+                    return Ok(Response::NotModified);
+                }
+
+                let header = |name| {
+                    response
+                        .headers()
+                        .get(name)
+                        .and_then(|value: &reqwest::header::HeaderValue| value.to_str().ok())
+                        .map(String::from)
+                };
+                let etag = header(reqwest::header::ETAG);
+                let last_modified = header(reqwest::header::LAST_MODIFIED);
+                return Ok(Response::Fresh {
+                    body: response.text().await?,
+                    etag,
+                    last_modified,
+                });
+            }
+
+            let retry_after = result.ok().and_then(|response| {
+                let seconds = response
+                    .headers()
+                    .get(reqwest::header::RETRY_AFTER)?
+                    .to_str()
+                    .ok()?
+                    .parse()
+                    .ok()?;
+                Some(Duration::from_secs(seconds))
+            });
+            let backoff = INITIAL_BACKOFF * 2u32.pow(attempt - 1);
+            async_std::task::sleep(retry_after.unwrap_or(backoff).min(MAX_BACKOFF)).await;
+        }
+    }
+}
+
+fn is_network_error(err: &anyhow::Error) -> bool {
+    err.downcast_ref::<reqwest::Error>()
+        .is_some_and(|err| err.is_connect() || err.is_timeout())
+}
@@ -0,0 +1,372 @@
+use super::{
+    EpisodeMetadata, EpisodeOrder, Match, MediaKind, MovieMetadata, Provider, ProviderId,
+    ScrapeFailure, ScrapeFuture, Scraper, SearchResult, SeasonMetadata, SeriesMetadata,
+    TmdbScraper,
+};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FixtureMode {
+    /// Requests go out as usual, and every response is saved.
+    Record,
+    /// Requests are answered only from what was saved, and never reach the network.
+    Replay,
+}
+
+/// A directory of recorded API responses and images, for scraping offline with predictable
+/// results.
+///
+/// Responses are stored under `api/` by provider and the SHA-256 of the request, and images under
+/// `images/` by their size and TMDB file name, or by host and path for other providers. Neither
+/// includes the API base or credentials, so a recording can be shared and replayed against any
+/// endpoint.
+#[derive(Debug, Clone)]
+pub struct Fixtures {
+    dir: PathBuf,
+    mode: FixtureMode,
+}
+
+#[derive(Serialize, Deserialize)]
+struct RecordedResponse {
+    request: String,
+    body: String,
+}
+
+impl Fixtures {
+    pub fn new(dir: &Path, mode: FixtureMode) -> Self {
+        Fixtures {
+            dir: dir.to_path_buf(),
+            mode,
+        }
+    }
+
+    pub fn is_replaying(&self) -> bool {
+        self.mode == FixtureMode::Replay
+    }
+
+    /// The request path and query, which includes the language.
+    fn request(path: &str, query: &[(&str, String)]) -> String {
+        let query = url::form_urlencoded::Serializer::new(String::new())
+            .extend_pairs(query)
+            .finish();
+        format!("{}?{}", path, query)
+    }
+
+    fn response_path(&self, request: &str) -> PathBuf {
+        self.dir
+            .join("api")
+            .join(format!("{:x}.json", Sha256::digest(request)))
+    }
+
+    pub async fn response(&self, path: &str, query: &[(&str, String)]) -> anyhow::Result<String> {
+        let request = Self::request(path, query);
+        let missing = || anyhow::anyhow!("no response recorded for {}", request);
+
+        let data = async_std::fs::read(self.response_path(&request))
+            .await
+            .map_err(|_| missing())?;
+        let response: RecordedResponse = serde_json::from_slice(&data)?;
+        // guards against the unlikely hash collision
+        if response.request != request {
+            return Err(missing());
+        }
+        Ok(response.body)
+    }
+
+    /// Saves a response, unless replaying. Failures are only logged, as they shouldn't fail the
+    /// scrape itself.
+    pub async fn record_response(&self, path: &str, query: &[(&str, String)], body: &str) {
+        if self.is_replaying() {
+            return;
+        }
+
+        let request = Self::request(path, query);
+        let dest = self.response_path(&request);
+        let response = RecordedResponse {
+            request,
+            body: body.to_string(),
+        };
+        let result = async {
+            async_std::fs::create_dir_all(self.dir.join("api")).await?;
+            async_std::fs::write(&dest, serde_json::to_vec_pretty(&response)?).await?;
+            anyhow::Ok(())
+        };
+        if let Err(err) = result.await {
+            log::warn!("failed to record response for {}: {:#}", path, err);
+        }
+    }
+
+    /// `image` is the size and file name, e.g. `w185/abc.jpg`, or a URL without its scheme.
+    fn image_path(&self, image: &str) -> PathBuf {
+        self.dir.join("images").join(image.trim_start_matches('/'))
+    }
+
+    pub async fn image(&self, image: &str) -> anyhow::Result<Vec<u8>> {
+        async_std::fs::read(self.image_path(image))
+            .await
+            .map_err(|_| anyhow::anyhow!("no image recorded for {}", image))
+    }
+
+    /// Saves an image, unless replaying. Failures are only logged.
+    pub async fn record_image(&self, image: &str, data: &[u8]) {
+        if self.is_replaying() {
+            return;
+        }
+
+        let dest = self.image_path(image);
+        let result = async {
+            if let Some(dir) = dest.parent() {
+                async_std::fs::create_dir_all(dir).await?;
+            }
+            async_std::fs::write(&dest, data).await?;
+            anyhow::Ok(())
+        };
+        if let Err(err) = result.await {
+            log::warn!("failed to record image {}: {:#}", image, err);
+        }
+    }
+}
+
+/// Scrapes through TMDB while recording into, or replaying from, a fixture directory.
+///
+/// The language, region and scale factor are part of what is requested, so a recording should be
+/// replayed with a scraper set up the same way it was recorded with. Replaying doesn't need an API
+/// key.
+#[derive(Clone)]
+pub struct FixtureScraper {
+    scraper: TmdbScraper,
+}
+
+impl FixtureScraper {
+    pub fn record(scraper: TmdbScraper, dir: &Path) -> Self {
+        FixtureScraper {
+            scraper: scraper.fixtures(Fixtures::new(dir, FixtureMode::Record)),
+        }
+    }
+
+    pub fn replay(scraper: TmdbScraper, dir: &Path) -> Self {
+        FixtureScraper {
+            scraper: scraper.fixtures(Fixtures::new(dir, FixtureMode::Replay)),
+        }
+    }
+}
+
+impl Scraper for FixtureScraper {
+    fn provides(&self, provider: Provider) -> bool {
+        self.scraper.provides(provider)
+    }
+
+    fn scrape_movie_metadata<'a>(
+        &'a self,
+        storage: &'a Path,
+        title: &'a str,
+        year: u16,
+    ) -> ScrapeFuture<'a, Option<Match<MovieMetadata>>> {
+        Scraper::scrape_movie_metadata(&self.scraper, storage, title, year)
+    }
+
+    fn scrape_series_metadata<'a>(
+        &'a self,
+        storage: &'a Path,
+        title: &'a str,
+        year: Option<u16>,
+    ) -> ScrapeFuture<'a, Option<Match<SeriesMetadata>>> {
+        Scraper::scrape_series_metadata(&self.scraper, storage, title, year)
+    }
+
+    fn scrape_season_metadata<'a>(
+        &'a self,
+        storage: &'a Path,
+        series: ProviderId,
+        season: u16,
+        order: &'a EpisodeOrder,
+    ) -> ScrapeFuture<'a, Option<(SeasonMetadata, Vec<EpisodeMetadata>)>> {
+        Scraper::scrape_season_metadata(&self.scraper, storage, series, season, order)
+    }
+
+    fn episode_orders<'a>(&'a self, series: ProviderId) -> ScrapeFuture<'a, Vec<EpisodeOrder>> {
+        Scraper::episode_orders(&self.scraper, series)
+    }
+
+    fn episode_counts<'a>(
+        &'a self,
+        series: ProviderId,
+        order: &'a EpisodeOrder,
+    ) -> ScrapeFuture<'a, Vec<(u16, u16)>> {
+        Scraper::episode_counts(&self.scraper, series, order)
+    }
+
+    fn search<'a>(
+        &'a self,
+        kind: MediaKind,
+        title: &'a str,
+        year: Option<u16>,
+    ) -> ScrapeFuture<'a, Vec<SearchResult>> {
+        Scraper::search(&self.scraper, kind, title, year)
+    }
+
+    fn movie_metadata<'a>(
+        &'a self,
+        storage: &'a Path,
+        id: ProviderId,
+    ) -> ScrapeFuture<'a, MovieMetadata> {
+        Scraper::movie_metadata(&self.scraper, storage, id)
+    }
+
+    fn series_metadata<'a>(
+        &'a self,
+        storage: &'a Path,
+        id: ProviderId,
+    ) -> ScrapeFuture<'a, SeriesMetadata> {
+        Scraper::series_metadata(&self.scraper, storage, id)
+    }
+
+    fn take_failures(&self) -> Vec<ScrapeFailure> {
+        self.scraper.take_failures()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::library::{MATCH_THRESHOLD, OmdbScraper, TvdbScraper, TvmazeScraper};
+
+    #[test]
+    fn replays_tmdb_movie() {
+        let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("fixtures/tmdb");
+        let storage = std::env::temp_dir().join("jangal-tests/tmdb");
+        let scraper = FixtureScraper::replay(TmdbScraper::new(""), &dir);
+
+        let matched =
+            async_std::task::block_on(scraper.scrape_movie_metadata(&storage, "The Matrix", 1999))
+                .unwrap()
+                .unwrap();
+        assert!(matched.confidence >= MATCH_THRESHOLD);
+        let movie = matched.metadata;
+        assert_eq!(movie.source(), ProviderId::new(Provider::Tmdb, 603));
+        assert_eq!(movie.title, "The Matrix");
+        assert_eq!(movie.year, 1999);
+        assert_eq!(movie.released, chrono::NaiveDate::from_ymd_opt(1999, 3, 31));
+        // the region's certification, not the first one listed
+        assert_eq!(movie.details.certification.as_deref(), Some("R"));
+        assert_eq!(
+            movie.details.tagline.as_deref(),
+            Some("Welcome to the Real World.")
+        );
+        assert_eq!(movie.details.cast[0].role, "Thomas A. Anderson / Neo");
+        // only the key crew jobs are kept
+        let jobs: Vec<_> = movie
+            .details
+            .crew
+            .iter()
+            .map(|credit| credit.role.as_str())
+            .collect();
+        assert_eq!(jobs, ["Director", "Director", "Director of Photography"]);
+        assert!(scraper.take_failures().is_empty());
+    }
+
+    struct Case {
+        scraper: Box<dyn Scraper>,
+        kind: MediaKind,
+        title: &'static str,
+        year: u16,
+        /// What should match, if anything.
+        source: Option<ProviderId>,
+        /// The titles of the first season's episodes, for series.
+        episodes: &'static [&'static str],
+        /// Failures reported along the way, such as for artwork that wasn't recorded.
+        failures: usize,
+    }
+
+    #[test]
+    fn replays_other_providers() {
+        let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("fixtures");
+        let replay = |provider: &str| Fixtures::new(&dir.join(provider), FixtureMode::Replay);
+        // replaying never logs in, so needs no keys
+        let cases = [
+            Case {
+                scraper: Box::new(TvmazeScraper::new().fixtures(replay("tvmaze"))),
+                kind: MediaKind::Series,
+                title: "Under the Dome",
+                year: 2013,
+                source: Some(ProviderId::new(Provider::Tvmaze, 1)),
+                episodes: &["Pilot", "The Fire"],
+                failures: 1,
+            },
+            Case {
+                scraper: Box::new(TvdbScraper::new("").fixtures(replay("tvdb"))),
+                kind: MediaKind::Series,
+                title: "Breaking Bad",
+                year: 2008,
+                source: Some(ProviderId::new(Provider::Tvdb, 81189)),
+                episodes: &["Pilot", "Cat's in the Bag..."],
+                failures: 0,
+            },
+            Case {
+                scraper: Box::new(OmdbScraper::new("").fixtures(replay("omdb"))),
+                kind: MediaKind::Movie,
+                title: "The Matrix",
+                year: 1999,
+                source: Some(ProviderId::new(Provider::Omdb, 133093)),
+                episodes: &[],
+                failures: 0,
+            },
+            Case {
+                scraper: Box::new(OmdbScraper::new("").fixtures(replay("omdb"))),
+                kind: MediaKind::Movie,
+                title: "Home Video",
+                year: 2010,
+                source: None,
+                episodes: &[],
+                failures: 0,
+            },
+        ];
+
+        let storage = std::env::temp_dir().join("jangal-tests/providers");
+        for case in cases {
+            let scraper = case.scraper.as_ref();
+            let (confidence, source) = match case.kind {
+                MediaKind::Movie => async_std::task::block_on(
+                    scraper.scrape_movie_metadata(&storage, case.title, case.year),
+                )
+                .unwrap()
+                .map(|matched| (matched.confidence, matched.metadata.source())),
+                MediaKind::Series => async_std::task::block_on(scraper.scrape_series_metadata(
+                    &storage,
+                    case.title,
+                    Some(case.year),
+                ))
+                .unwrap()
+                .map(|matched| (matched.confidence, matched.metadata.source())),
+            }
+            .unzip();
+            assert_eq!(source, case.source, "{}", case.title);
+            assert!(confidence.is_none_or(|confidence| confidence >= MATCH_THRESHOLD));
+
+            if let (MediaKind::Series, Some(source)) = (case.kind, source) {
+                let (season, episodes) = async_std::task::block_on(scraper.scrape_season_metadata(
+                    &storage,
+                    source,
+                    1,
+                    &EpisodeOrder::Aired,
+                ))
+                .unwrap()
+                .unwrap();
+                assert_eq!(season.series_source(), source);
+                let titles: Vec<_> = episodes
+                    .iter()
+                    .map(|episode| episode.title.as_str())
+                    .collect();
+                assert_eq!(titles, case.episodes, "{}", case.title);
+            }
+            assert_eq!(
+                scraper.take_failures().len(),
+                case.failures,
+                "{}",
+                case.title
+            );
+        }
+    }
+}
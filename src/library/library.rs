@@ -1,5 +1,5 @@
 use super::*;
-use rustc_hash::FxHashMap;
+use rustc_hash::{FxHashMap, FxHashSet};
 use serde::{Deserialize, Serialize};
 use std::path::Path;
 
@@ -26,6 +26,9 @@ pub struct Library {
     collections: FxHashMap<CollectionId, Collection>,
     #[serde(default)]
     next_collection_id: CollectionId,
+
+    #[serde(default)]
+    last_refreshed: Option<chrono::DateTime<chrono::Local>>,
 }
 
 impl Library {
@@ -36,6 +39,8 @@ impl Library {
 
             collections: FxHashMap::default(),
             next_collection_id: CollectionId(1),
+
+            last_refreshed: None,
         }
     }
 
@@ -60,6 +65,15 @@ impl Library {
         Ok(())
     }
 
+    /// When the whole library last had its metadata refreshed.
+    pub fn last_refreshed(&self) -> Option<chrono::DateTime<chrono::Local>> {
+        self.last_refreshed
+    }
+
+    pub fn set_last_refreshed(&mut self, time: chrono::DateTime<chrono::Local>) {
+        self.last_refreshed = Some(time);
+    }
+
     fn generate_id(&mut self) -> MediaId {
         let id = self.next_id;
         self.next_id = MediaId(self.next_id.0 + 1);
@@ -104,6 +118,241 @@ impl Library {
         self.media.get_mut(&id)
     }
 
+    /// Turns a matched movie or episode back into uncategorised media, keeping its video and
+    /// collection membership. Returns `false` if `id` is not a movie or episode.
+    pub fn unmatch(&mut self, id: MediaId) -> bool {
+        let Some(media) = self.media.get_mut(&id) else {
+            return false;
+        };
+        if !matches!(media, Media::Movie(_) | Media::Episode(_)) {
+            return false;
+        }
+
+        let video = media.video().cloned().unwrap();
+        *media = Media::Uncategorised(Uncategorised {
+            video,
+            // otherwise the next scan would just match it again
+            dont_scrape: true,
+            needs_review: false,
+            scrape_failed: false,
+        });
+
+        self.remove_empty();
+        true
+    }
+
+    /// Re-files media with a video as an episode of `series`, creating the season if the series
+    /// does not have it yet. The video, and with it watch state and date added, is preserved.
+    /// The episode takes the metadata the new season lists for its number, while the seasons'
+    /// lists themselves stay as the provider has them.
+    pub fn move_episode(
+        &mut self,
+        id: MediaId,
+        series: MediaId,
+        season: u16,
+        episode: u16,
+    ) -> anyhow::Result<()> {
+        let Some(Media::Series(target)) = self.media.get(&series) else {
+            return Err(anyhow::anyhow!("not a series"));
+        };
+        let (provider, series_provider_id) =
+            (target.metadata.provider, target.metadata.provider_id);
+
+        let (video, mut metadata) = match self.media.get(&id) {
+            Some(Media::Episode(existing)) => (
+                existing.video.clone(),
+                EpisodeMetadata {
+                    provider,
+                    series_provider_id,
+                    season,
+                    episode,
+                    ..existing.metadata.clone()
+                },
+            ),
+            Some(media) => match media.video() {
+                Some(video) => (
+                    video.clone(),
+                    EpisodeMetadata {
+                        provider,
+                        series_provider_id,
+                        title: format!("Episode {}", episode),
+                        season,
+                        episode,
+                        aired: None,
+                        overview: None,
+                        runtime: None,
+                        still: None,
+                        locked: Default::default(),
+                    },
+                ),
+                None => return Err(anyhow::anyhow!("media has no video")),
+            },
+            None => return Err(anyhow::anyhow!("media not found")),
+        };
+
+        let season_id = self.media.iter().find_map(|(id, media)| match media {
+            Media::Season(other) if other.series == series && other.metadata.season == season => {
+                Some(*id)
+            }
+            _ => None,
+        });
+        let season_id = season_id.unwrap_or_else(|| {
+            self.insert(Media::Season(Season {
+                metadata: SeasonMetadata {
+                    provider,
+                    series_provider_id,
+                    title: format!("Season {}", season),
+                    season,
+                    poster: None,
+                    aired: None,
+                    overview: None,
+                    episodes: vec![],
+                    locked: Default::default(),
+                },
+                series,
+            }))
+        });
+
+        if let Some(Media::Season(target)) = self.media.get(&season_id) {
+            let cached = target
+                .metadata
+                .episodes
+                .iter()
+                .find(|cached| cached.episode == episode);
+            if let Some(cached) = cached {
+                // the number is where the video goes, whatever the provider has
+                let mut cached = cached.clone();
+                cached.season = season;
+                metadata.merge(cached);
+            }
+        }
+
+        self.media.insert(
+            id,
+            Media::Episode(Episode {
+                video,
+                series,
+                season: season_id,
+                metadata,
+            }),
+        );
+
+        self.remove_empty();
+        Ok(())
+    }
+
+    /// The order the series with `source` is scraped in.
+    pub fn episode_order(&self, source: ProviderId) -> EpisodeOrder {
+        self.media
+            .values()
+            .find_map(|media| match media {
+                Media::Series(series) if series.metadata.source() == source => {
+                    Some(series.episode_order.clone())
+                }
+                _ => None,
+            })
+            .unwrap_or_default()
+    }
+
+    /// Changes the order a series is scraped in, returning whether it changed. The seasons' episode
+    /// lists are dropped, as they are numbered by the old order; refreshing the series fetches
+    /// them again and renumbers its episodes' metadata in place, keeping their videos.
+    pub fn set_episode_order(&mut self, id: MediaId, order: EpisodeOrder) -> bool {
+        match self.media.get_mut(&id) {
+            Some(Media::Series(series)) if series.episode_order != order => {
+                series.episode_order = order;
+            }
+            _ => return false,
+        }
+
+        for media in self.media.values_mut() {
+            match media {
+                Media::Season(season) if season.series == id => season.metadata.episodes.clear(),
+                _ => {}
+            }
+        }
+        true
+    }
+
+    /// Replaces the metadata of `id` with a manually edited copy. Renumbering an episode moves it
+    /// into the matching season, and renumbering a season renumbers its episodes.
+    pub fn set_metadata(&mut self, id: MediaId, metadata: Metadata) -> anyhow::Result<()> {
+        match (self.media.get_mut(&id), metadata) {
+            (Some(Media::Movie(movie)), Metadata::Movie(metadata)) => movie.metadata = metadata,
+            (Some(Media::Series(series)), Metadata::Series(metadata)) => series.metadata = metadata,
+            (Some(Media::Season(season)), Metadata::Season(metadata)) => {
+                let series = season.series;
+                let number = metadata.season;
+                if number != season.metadata.season
+                    && self.media.values().any(|media| {
+                        matches!(media, Media::Season(other)
+                            if other.series == series && other.metadata.season == number)
+                    })
+                {
+                    return Err(anyhow::anyhow!("season {} already exists", number));
+                }
+
+                for media in self.media.values_mut() {
+                    match media {
+                        Media::Episode(episode) if episode.season == id => {
+                            episode.metadata.season = number;
+                        }
+                        _ => {}
+                    }
+                }
+                if let Some(Media::Season(season)) = self.media.get_mut(&id) {
+                    season.metadata = metadata;
+                }
+            }
+            (Some(Media::Episode(episode)), Metadata::Episode(metadata)) => {
+                let series = episode.series;
+                if metadata.season != episode.metadata.season
+                    || metadata.episode != episode.metadata.episode
+                {
+                    self.move_episode(id, series, metadata.season, metadata.episode)?;
+                }
+                if let Some(Media::Episode(episode)) = self.media.get_mut(&id) {
+                    episode.metadata = metadata;
+                }
+            }
+            _ => return Err(anyhow::anyhow!("metadata does not match media")),
+        }
+
+        Ok(())
+    }
+
+    /// Removes seasons without episodes and series without seasons, along with their
+    /// collection memberships.
+    pub fn remove_empty(&mut self) {
+        let seasons: FxHashSet<MediaId> = self
+            .media
+            .values()
+            .filter_map(|media| match media {
+                Media::Episode(episode) => Some(episode.season),
+                _ => None,
+            })
+            .collect();
+        self.media.retain(|id, media| match media {
+            Media::Season(_) => seasons.contains(id),
+            _ => true,
+        });
+
+        let series: FxHashSet<MediaId> = self
+            .media
+            .values()
+            .filter_map(|media| match media {
+                Media::Season(season) => Some(season.series),
+                _ => None,
+            })
+            .collect();
+        self.media.retain(|id, media| match media {
+            Media::Series(_) => series.contains(id),
+            _ => true,
+        });
+
+        self.purge_collections();
+    }
+
     fn generate_collection_id(&mut self) -> CollectionId {
         let id = self.next_collection_id;
         self.next_collection_id = CollectionId(self.next_collection_id.0 + 1);
@@ -1,5 +1,6 @@
 use super::*;
 use chrono::{Datelike, NaiveDate};
+use rustc_hash::FxHashSet;
 use serde::{Deserialize, Serialize};
 use std::path::{Path, PathBuf};
 
@@ -36,7 +37,7 @@ impl Media {
             Media::Movie(movie) => movie.metadata.released,
             Media::Series(series) => series.metadata.aired,
             Media::Season(season) => season.metadata.aired,
-            Media::Episode(episode) => Some(episode.metadata.aired),
+            Media::Episode(episode) => episode.metadata.aired,
         }
     }
 
@@ -65,6 +66,16 @@ impl Media {
         }
     }
 
+    pub fn metadata(&self) -> Option<Metadata> {
+        match self {
+            Media::Uncategorised(_) => None,
+            Media::Movie(movie) => Some(Metadata::Movie(movie.metadata.clone())),
+            Media::Series(series) => Some(Metadata::Series(series.metadata.clone())),
+            Media::Season(season) => Some(Metadata::Season(season.metadata.clone())),
+            Media::Episode(episode) => Some(Metadata::Episode(episode.metadata.clone())),
+        }
+    }
+
     pub fn poster(&self) -> Option<&Path> {
         match self {
             Media::Movie(movie) => Some(&movie.metadata.poster.as_ref()?),
@@ -104,6 +115,12 @@ pub struct Video {
 pub struct Uncategorised {
     pub video: Video,
     pub dont_scrape: bool,
+    /// Set when the best scraped match was not confident enough to apply automatically.
+    #[serde(default)]
+    pub needs_review: bool,
+    /// The last scrape ran into an error, so the next scan tries again.
+    #[serde(default)]
+    pub scrape_failed: bool,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -115,6 +132,28 @@ pub struct Movie {
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct Series {
     pub metadata: SeriesMetadata,
+    /// The numbering the series' files follow, which its seasons are scraped in.
+    #[serde(default)]
+    pub episode_order: EpisodeOrder,
+}
+
+/// How a series' episodes are split into seasons and numbered.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Hash, Default)]
+pub enum EpisodeOrder {
+    /// As originally aired, which is what every provider numbers by.
+    #[default]
+    Aired,
+    /// A TMDB episode group, such as the DVD order or story arcs. `name` is only for display.
+    Group { id: String, name: String },
+}
+
+impl std::fmt::Display for EpisodeOrder {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            EpisodeOrder::Aired => f.write_str("Aired order"),
+            EpisodeOrder::Group { name, .. } => f.write_str(name),
+        }
+    }
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -139,38 +178,305 @@ pub enum Metadata {
     Episode(EpisodeMetadata),
 }
 
+/// A user-editable metadata field, which can be locked against being overwritten by scraping.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Field {
+    Title,
+    Year,
+    Released,
+    Season,
+    Episode,
+    Poster,
+}
+
+/// A person credited on a movie or series, with their character (cast) or job (crew).
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Credit {
+    pub name: String,
+    pub role: String,
+}
+
+/// Descriptive metadata shared by movies and series.
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct Details {
+    #[serde(default)]
+    pub overview: Option<String>,
+    #[serde(default)]
+    pub tagline: Option<String>,
+    #[serde(default)]
+    pub genres: Vec<String>,
+    /// In minutes; typical episode length for series.
+    #[serde(default)]
+    pub runtime: Option<u32>,
+    #[serde(default)]
+    pub certification: Option<String>,
+    /// Average user rating at the provider, out of 10.
+    #[serde(default)]
+    pub rating: Option<f32>,
+    #[serde(default)]
+    pub original_title: Option<String>,
+    #[serde(default)]
+    pub original_language: Option<String>,
+    /// Production companies for movies, networks for series.
+    #[serde(default)]
+    pub studios: Vec<String>,
+    /// Top-billed cast, in billing order.
+    #[serde(default)]
+    pub cast: Vec<Credit>,
+    #[serde(default)]
+    pub crew: Vec<Credit>,
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct MovieMetadata {
-    pub tmdb_id: u64,
+    /// Where the metadata was scraped from. Everything from before providers were recorded is
+    /// TMDB's.
+    #[serde(default)]
+    pub provider: Provider,
+    /// The movie's id at `provider`, zero if it has none.
+    #[serde(alias = "tmdb_id")]
+    pub provider_id: u64,
     pub title: String,
     pub year: u16,
     pub poster: Option<PathBuf>,
+    #[serde(default)]
+    pub backdrop: Option<PathBuf>,
     pub released: Option<NaiveDate>,
+    #[serde(default)]
+    pub details: Details,
+    #[serde(default)]
+    pub locked: FxHashSet<Field>,
+}
+
+impl MovieMetadata {
+    pub fn source(&self) -> ProviderId {
+        ProviderId::new(self.provider, self.provider_id)
+    }
+
+    /// Takes freshly scraped metadata, keeping locked fields.
+    pub fn merge(&mut self, new: MovieMetadata) {
+        self.provider = new.provider;
+        self.provider_id = new.provider_id;
+        self.backdrop = new.backdrop;
+        self.details = new.details;
+        if !self.locked.contains(&Field::Title) {
+            self.title = new.title;
+        }
+        if !self.locked.contains(&Field::Year) {
+            self.year = new.year;
+        }
+        if !self.locked.contains(&Field::Poster) {
+            self.poster = new.poster;
+        }
+        if !self.locked.contains(&Field::Released) {
+            self.released = new.released;
+        }
+    }
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct SeriesMetadata {
-    pub tmdb_id: u64,
+    #[serde(default)]
+    pub provider: Provider,
+    /// The series' id at `provider`.
+    #[serde(alias = "tmdb_id")]
+    pub provider_id: u64,
     pub title: String,
     pub poster: Option<PathBuf>,
+    #[serde(default)]
+    pub backdrop: Option<PathBuf>,
+    /// Title logo with a transparent background.
+    #[serde(default)]
+    pub logo: Option<PathBuf>,
     pub aired: Option<NaiveDate>,
+    #[serde(default)]
+    pub details: Details,
+    /// The next episode to air, which may be in a season that isn't in the library yet.
+    #[serde(default)]
+    pub next_episode: Option<EpisodeMetadata>,
+    #[serde(default)]
+    pub locked: FxHashSet<Field>,
+}
+
+impl SeriesMetadata {
+    pub fn source(&self) -> ProviderId {
+        ProviderId::new(self.provider, self.provider_id)
+    }
+
+    /// Takes freshly scraped metadata, keeping locked fields.
+    pub fn merge(&mut self, new: SeriesMetadata) {
+        self.provider = new.provider;
+        self.provider_id = new.provider_id;
+        self.backdrop = new.backdrop;
+        self.logo = new.logo;
+        self.details = new.details;
+        self.next_episode = new.next_episode;
+        if !self.locked.contains(&Field::Title) {
+            self.title = new.title;
+        }
+        if !self.locked.contains(&Field::Poster) {
+            self.poster = new.poster;
+        }
+        if !self.locked.contains(&Field::Released) {
+            self.aired = new.aired;
+        }
+    }
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct SeasonMetadata {
-    pub series_tmdb_id: u64,
+    /// Seasons are always scraped from the same provider as their series.
+    #[serde(default)]
+    pub provider: Provider,
+    #[serde(alias = "series_tmdb_id")]
+    pub series_provider_id: u64,
     pub title: String,
     pub season: u16,
     pub poster: Option<PathBuf>,
     pub aired: Option<NaiveDate>,
     pub overview: Option<String>,
+    /// Every episode the provider lists for the season, including ones that aren't in the library.
+    #[serde(default)]
+    pub episodes: Vec<EpisodeMetadata>,
+    #[serde(default)]
+    pub locked: FxHashSet<Field>,
+}
+
+impl SeasonMetadata {
+    pub fn series_source(&self) -> ProviderId {
+        ProviderId::new(self.provider, self.series_provider_id)
+    }
+
+    /// Takes freshly scraped metadata, keeping locked fields.
+    pub fn merge(&mut self, new: SeasonMetadata) {
+        self.provider = new.provider;
+        self.series_provider_id = new.series_provider_id;
+        self.overview = new.overview;
+        self.episodes = new.episodes;
+        if !self.locked.contains(&Field::Title) {
+            self.title = new.title;
+        }
+        if !self.locked.contains(&Field::Season) {
+            self.season = new.season;
+        }
+        if !self.locked.contains(&Field::Poster) {
+            self.poster = new.poster;
+        }
+        if !self.locked.contains(&Field::Released) {
+            self.aired = new.aired;
+        }
+    }
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct EpisodeMetadata {
-    pub series_tmdb_id: u64,
+    #[serde(default)]
+    pub provider: Provider,
+    #[serde(alias = "series_tmdb_id")]
+    pub series_provider_id: u64,
     pub title: String,
     pub season: u16,
     pub episode: u16,
-    pub aired: NaiveDate,
+    pub aired: Option<NaiveDate>,
+    #[serde(default)]
+    pub overview: Option<String>,
+    /// In minutes.
+    #[serde(default)]
+    pub runtime: Option<u32>,
+    #[serde(default)]
+    pub still: Option<PathBuf>,
+    #[serde(default)]
+    pub locked: FxHashSet<Field>,
+}
+
+impl EpisodeMetadata {
+    pub fn series_source(&self) -> ProviderId {
+        ProviderId::new(self.provider, self.series_provider_id)
+    }
+
+    /// Takes freshly scraped metadata, keeping locked fields.
+    pub fn merge(&mut self, new: EpisodeMetadata) {
+        self.provider = new.provider;
+        self.series_provider_id = new.series_provider_id;
+        self.overview = new.overview;
+        self.runtime = new.runtime;
+        self.still = new.still;
+        if !self.locked.contains(&Field::Title) {
+            self.title = new.title;
+        }
+        if !self.locked.contains(&Field::Season) {
+            self.season = new.season;
+        }
+        if !self.locked.contains(&Field::Episode) {
+            self.episode = new.episode;
+        }
+        if !self.locked.contains(&Field::Released) {
+            self.aired = new.aired;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn movie(title: &str, year: u16) -> MovieMetadata {
+        MovieMetadata {
+            provider: Provider::Tmdb,
+            provider_id: 603,
+            title: title.into(),
+            year,
+            poster: Some(PathBuf::from(format!("{}.jpg", title))),
+            backdrop: None,
+            released: NaiveDate::from_ymd_opt(year as i32, 3, 31),
+            details: Details::default(),
+            locked: FxHashSet::default(),
+        }
+    }
+
+    #[test]
+    fn merge_keeps_locked_fields() {
+        let mut edited = movie("My Matrix", 1998);
+        edited.locked.extend([Field::Title, Field::Poster]);
+        edited.merge(MovieMetadata {
+            backdrop: Some(PathBuf::from("backdrop.jpg")),
+            ..movie("The Matrix", 1999)
+        });
+
+        assert_eq!(edited.title, "My Matrix");
+        assert_eq!(edited.poster, Some(PathBuf::from("My Matrix.jpg")));
+        assert_eq!(edited.year, 1999);
+        assert_eq!(edited.released, NaiveDate::from_ymd_opt(1999, 3, 31));
+        assert_eq!(edited.backdrop, Some(PathBuf::from("backdrop.jpg")));
+        assert_eq!(
+            edited.locked,
+            FxHashSet::from_iter([Field::Title, Field::Poster])
+        );
+    }
+
+    #[test]
+    fn merge_keeps_locked_episode_numbers() {
+        let episode = |season, episode| EpisodeMetadata {
+            provider: Provider::Tvmaze,
+            series_provider_id: 1,
+            title: format!("Episode {}", episode),
+            season,
+            episode,
+            aired: None,
+            overview: None,
+            runtime: None,
+            still: None,
+            locked: FxHashSet::default(),
+        };
+        let mut edited = episode(1, 3);
+        edited.locked.insert(Field::Season);
+        edited.merge(EpisodeMetadata {
+            overview: Some(String::from("Overview")),
+            ..episode(2, 5)
+        });
+
+        assert_eq!((edited.season, edited.episode), (1, 5));
+        assert_eq!(edited.title, "Episode 5");
+        assert_eq!(edited.overview.as_deref(), Some("Overview"));
+    }
 }
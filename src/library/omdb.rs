@@ -0,0 +1,532 @@
+use super::{
+    Auth, Candidate, Client, Credit, Details, EpisodeMetadata, EpisodeOrder, Failures, Fixtures,
+    MATCH_THRESHOLD, Match, MediaKind, MovieMetadata, Provider, ProviderId, RateLimiter,
+    ResponseCache, ScrapeFailure, ScrapeFuture, Scraper, SearchResult, SeasonMetadata,
+    SeriesMetadata, fetch_artwork, rank_candidates,
+};
+use anyhow::Context;
+use chrono::NaiveDate;
+use serde::{Deserialize, de::DeserializeOwned};
+use std::{path::Path, sync::LazyLock};
+
+const API_BASE: &str = "https://www.omdbapi.com";
+
+/// OMDb only limits requests per day, but there's no reason to send them all at once.
+static RATE_LIMITER: LazyLock<RateLimiter> = LazyLock::new(|| RateLimiter::new(5.0, 5.0));
+
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "PascalCase")]
+struct SearchPage {
+    #[serde(default)]
+    search: Vec<SearchHit>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+struct SearchHit {
+    #[serde(rename = "Title")]
+    title: String,
+    /// A range such as `2005–2013` for series.
+    #[serde(rename = "Year", default)]
+    year: String,
+    #[serde(rename = "imdbID")]
+    imdb_id: String,
+    #[serde(rename = "Poster", default)]
+    poster: String,
+}
+
+/// `/?i=<imdb id>`. Missing values are `N/A` rather than absent.
+#[derive(Deserialize, Debug, Clone)]
+struct Title {
+    #[serde(rename = "Title")]
+    title: String,
+    #[serde(rename = "Year", default)]
+    year: String,
+    #[serde(rename = "Rated", default)]
+    rated: String,
+    #[serde(rename = "Released", default)]
+    released: String,
+    #[serde(rename = "Runtime", default)]
+    runtime: String,
+    #[serde(rename = "Genre", default)]
+    genre: String,
+    #[serde(rename = "Director", default)]
+    director: String,
+    #[serde(rename = "Writer", default)]
+    writer: String,
+    #[serde(rename = "Actors", default)]
+    actors: String,
+    #[serde(rename = "Plot", default)]
+    plot: String,
+    #[serde(rename = "Poster", default)]
+    poster: String,
+    #[serde(rename = "Production", default)]
+    production: String,
+    #[serde(rename = "imdbRating", default)]
+    imdb_rating: String,
+    #[serde(rename = "imdbID")]
+    imdb_id: String,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "PascalCase")]
+struct Season {
+    #[serde(default)]
+    episodes: Vec<Episode>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "PascalCase")]
+struct Episode {
+    title: String,
+    #[serde(default)]
+    released: String,
+    episode: String,
+}
+
+fn available(value: String) -> Option<String> {
+    let value = value.trim();
+    (!value.is_empty() && value != "N/A").then(|| value.to_string())
+}
+
+fn list(value: String) -> Vec<String> {
+    available(value)
+        .map(|value| value.split(", ").map(String::from).collect())
+        .unwrap_or_default()
+}
+
+/// The first year of `1999` or `2005–2013`.
+fn year(value: &str) -> Option<u16> {
+    value.get(..4)?.parse().ok()
+}
+
+/// IMDb ids are stored by their number, and zero-padded to at least seven digits.
+pub fn parse_imdb_id(id: &str) -> anyhow::Result<u64> {
+    id.strip_prefix("tt")
+        .and_then(|number| number.parse().ok())
+        .ok_or_else(|| anyhow::anyhow!("invalid IMDb id {}", id))
+}
+
+fn imdb_id(id: u64) -> String {
+    format!("tt{:07}", id)
+}
+
+/// Writers come with what they wrote, as in `Lana Wachowski (screenplay)`.
+fn writers(value: String) -> Vec<Credit> {
+    list(value)
+        .into_iter()
+        .map(|writer| match writer.split_once(" (") {
+            Some((name, role)) => Credit {
+                name: name.to_string(),
+                role: role.trim_end_matches(')').to_string(),
+            },
+            None => Credit {
+                name: writer,
+                role: String::from("Writer"),
+            },
+        })
+        .collect()
+}
+
+/// Scrapes through OMDb, which has IMDb's data in English. Ids are IMDb's.
+#[derive(Clone)]
+pub struct OmdbScraper {
+    client: Client,
+    fixtures: Option<Fixtures>,
+    failures: Failures,
+}
+
+impl OmdbScraper {
+    pub fn new(key: &str) -> Self {
+        OmdbScraper {
+            client: Client::new(
+                Provider::Omdb,
+                API_BASE,
+                Auth::Query("apikey", key.trim().to_string()),
+                &RATE_LIMITER,
+            ),
+            fixtures: None,
+            failures: Failures::default(),
+        }
+    }
+
+    pub fn cache(mut self, cache: ResponseCache) -> Self {
+        self.client = self.client.cache(cache);
+        self
+    }
+
+    pub fn fixtures(mut self, fixtures: Fixtures) -> Self {
+        self.client = self.client.fixtures(fixtures.clone());
+        self.fixtures = Some(fixtures);
+        self
+    }
+
+    /// OMDb answers errors with a 200 and `"Response": "False"`. Nothing being found is `None`,
+    /// anything else, such as an invalid key, fails.
+    async fn get<T: DeserializeOwned>(
+        &self,
+        query: &[(&str, String)],
+    ) -> anyhow::Result<Option<T>> {
+        let reply: serde_json::Value = self.client.get("/", query).await?;
+        if reply["Response"] == "False" {
+            let error = reply["Error"].as_str().unwrap_or("unknown error");
+            return if error.ends_with("not found!") {
+                Ok(None)
+            } else {
+                Err(anyhow::anyhow!("{}", error))
+            };
+        }
+        Ok(Some(serde_json::from_value(reply)?))
+    }
+
+    async fn search_titles(
+        &self,
+        kind: MediaKind,
+        title: &str,
+        year: Option<u16>,
+    ) -> anyhow::Result<Vec<SearchHit>> {
+        let kind = match kind {
+            MediaKind::Movie => "movie",
+            MediaKind::Series => "series",
+        };
+        let mut query = vec![("s", title.to_string()), ("type", kind.to_string())];
+        if let Some(year) = year {
+            query.push(("y", year.to_string()));
+        }
+
+        let page: Option<SearchPage> = self
+            .get(&query)
+            .await
+            .with_context(|| format!("omdb {} search failed", kind))?;
+        Ok(page.map(|page| page.search).unwrap_or_default())
+    }
+
+    async fn title(&self, id: u64) -> anyhow::Result<Title> {
+        self.get(&[("i", imdb_id(id)), ("plot", String::from("full"))])
+            .await
+            .context("omdb details failed")?
+            .ok_or_else(|| anyhow::anyhow!("{} not found on OMDb", imdb_id(id)))
+    }
+
+    /// The best ranked search hit for `title`.
+    async fn best_match(
+        &self,
+        kind: MediaKind,
+        title: &str,
+        year: Option<u16>,
+    ) -> anyhow::Result<Option<(f32, SearchHit)>> {
+        let candidates = self
+            .search_titles(kind, title, year)
+            .await?
+            .into_iter()
+            .map(|hit| Candidate {
+                titles: vec![hit.title.clone()],
+                year: self::year(&hit.year),
+                popularity: 0.0,
+                vote_count: 0,
+                value: hit,
+            })
+            .collect();
+
+        Ok(rank_candidates(title, year, candidates)
+            .into_iter()
+            .next()
+            .map(|(confidence, candidate)| (confidence, candidate.value)))
+    }
+
+    fn details(title: &mut Title) -> Details {
+        let mut crew: Vec<_> = list(std::mem::take(&mut title.director))
+            .into_iter()
+            .map(|name| Credit {
+                name,
+                role: String::from("Director"),
+            })
+            .collect();
+        crew.extend(writers(std::mem::take(&mut title.writer)));
+
+        Details {
+            overview: available(std::mem::take(&mut title.plot)),
+            genres: list(std::mem::take(&mut title.genre)),
+            runtime: title
+                .runtime
+                .split_whitespace()
+                .next()
+                .and_then(|minutes| minutes.parse().ok()),
+            certification: available(std::mem::take(&mut title.rated)),
+            rating: title.imdb_rating.parse().ok(),
+            studios: list(std::mem::take(&mut title.production)),
+            cast: list(std::mem::take(&mut title.actors))
+                .into_iter()
+                .map(|name| Credit {
+                    name,
+                    role: String::new(),
+                })
+                .collect(),
+            crew,
+            ..Default::default()
+        }
+    }
+
+    async fn scrape_movie_metadata(
+        &self,
+        storage: &Path,
+        title: &str,
+        year: u16,
+    ) -> anyhow::Result<Option<Match<MovieMetadata>>> {
+        let Some((confidence, hit)) = self.best_match(MediaKind::Movie, title, Some(year)).await?
+        else {
+            return Ok(None);
+        };
+
+        let id = parse_imdb_id(&hit.imdb_id)?;
+        let metadata = if confidence < MATCH_THRESHOLD {
+            MovieMetadata {
+                provider: Provider::Omdb,
+                provider_id: id,
+                title: hit.title,
+                year: self::year(&hit.year).unwrap_or(0),
+                poster: None,
+                backdrop: None,
+                released: None,
+                details: Details::default(),
+                locked: Default::default(),
+            }
+        } else {
+            self.movie_metadata(storage, id).await?
+        };
+
+        Ok(Some(Match {
+            metadata,
+            confidence,
+        }))
+    }
+
+    async fn scrape_series_metadata(
+        &self,
+        storage: &Path,
+        title: &str,
+        year: Option<u16>,
+    ) -> anyhow::Result<Option<Match<SeriesMetadata>>> {
+        let Some((confidence, hit)) = self.best_match(MediaKind::Series, title, year).await? else {
+            return Ok(None);
+        };
+
+        let id = parse_imdb_id(&hit.imdb_id)?;
+        let metadata = if confidence < MATCH_THRESHOLD {
+            SeriesMetadata {
+                provider: Provider::Omdb,
+                provider_id: id,
+                title: hit.title,
+                poster: None,
+                backdrop: None,
+                logo: None,
+                aired: None,
+                details: Details::default(),
+                next_episode: None,
+                locked: Default::default(),
+            }
+        } else {
+            self.series_metadata(storage, id).await?
+        };
+
+        Ok(Some(Match {
+            metadata,
+            confidence,
+        }))
+    }
+
+    async fn scrape_season_metadata(
+        &self,
+        series_id: u64,
+        season: u16,
+    ) -> anyhow::Result<Option<(SeasonMetadata, Vec<EpisodeMetadata>)>> {
+        let Some(details): Option<Season> = self
+            .get(&[("i", imdb_id(series_id)), ("Season", season.to_string())])
+            .await
+            .context("omdb season failed")?
+        else {
+            return Ok(None);
+        };
+
+        // episodes only come with their title and air date
+        let episodes: Vec<_> = details
+            .episodes
+            .into_iter()
+            .filter_map(|episode| {
+                Some(EpisodeMetadata {
+                    provider: Provider::Omdb,
+                    series_provider_id: series_id,
+                    title: episode.title,
+                    season,
+                    episode: episode.episode.parse().ok()?,
+                    aired: NaiveDate::parse_from_str(&episode.released, "%Y-%m-%d").ok(),
+                    overview: None,
+                    runtime: None,
+                    still: None,
+                    locked: Default::default(),
+                })
+            })
+            .collect();
+
+        Ok(Some((
+            SeasonMetadata {
+                provider: Provider::Omdb,
+                series_provider_id: series_id,
+                title: format!("Season {}", season),
+                season,
+                poster: None,
+                aired: episodes.iter().filter_map(|episode| episode.aired).min(),
+                overview: None,
+                episodes: episodes.clone(),
+                locked: Default::default(),
+            },
+            episodes,
+        )))
+    }
+
+    async fn search(
+        &self,
+        kind: MediaKind,
+        title: &str,
+        year: Option<u16>,
+    ) -> anyhow::Result<Vec<SearchResult>> {
+        Ok(self
+            .search_titles(kind, title, year)
+            .await?
+            .into_iter()
+            .filter_map(|hit| {
+                Some(SearchResult {
+                    id: ProviderId::new(Provider::Omdb, parse_imdb_id(&hit.imdb_id).ok()?),
+                    year: self::year(&hit.year),
+                    title: hit.title,
+                    overview: String::new(),
+                    poster: available(hit.poster),
+                })
+            })
+            .collect())
+    }
+
+    async fn movie_metadata(&self, storage: &Path, id: u64) -> anyhow::Result<MovieMetadata> {
+        let mut title = self.title(id).await?;
+        let poster = fetch_artwork(
+            storage,
+            &format!("omdb/{}", title.imdb_id),
+            "poster",
+            available(std::mem::take(&mut title.poster)),
+            self.fixtures.as_ref(),
+            &self.failures,
+        )
+        .await;
+
+        Ok(MovieMetadata {
+            provider: Provider::Omdb,
+            provider_id: id,
+            year: year(&title.year).unwrap_or(0),
+            released: NaiveDate::parse_from_str(&title.released, "%d %b %Y").ok(),
+            poster,
+            backdrop: None,
+            details: Self::details(&mut title),
+            title: title.title,
+            locked: Default::default(),
+        })
+    }
+
+    async fn series_metadata(&self, storage: &Path, id: u64) -> anyhow::Result<SeriesMetadata> {
+        let mut title = self.title(id).await?;
+        let poster = fetch_artwork(
+            storage,
+            &format!("omdb/{}", title.imdb_id),
+            "poster",
+            available(std::mem::take(&mut title.poster)),
+            self.fixtures.as_ref(),
+            &self.failures,
+        )
+        .await;
+
+        Ok(SeriesMetadata {
+            provider: Provider::Omdb,
+            provider_id: id,
+            aired: NaiveDate::parse_from_str(&title.released, "%d %b %Y").ok(),
+            poster,
+            backdrop: None,
+            logo: None,
+            details: Self::details(&mut title),
+            title: title.title,
+            next_episode: None,
+            locked: Default::default(),
+        })
+    }
+}
+
+impl Scraper for OmdbScraper {
+    fn provides(&self, provider: Provider) -> bool {
+        provider == Provider::Omdb
+    }
+
+    fn scrape_movie_metadata<'a>(
+        &'a self,
+        storage: &'a Path,
+        title: &'a str,
+        year: u16,
+    ) -> ScrapeFuture<'a, Option<Match<MovieMetadata>>> {
+        Box::pin(OmdbScraper::scrape_movie_metadata(
+            self, storage, title, year,
+        ))
+    }
+
+    fn scrape_series_metadata<'a>(
+        &'a self,
+        storage: &'a Path,
+        title: &'a str,
+        year: Option<u16>,
+    ) -> ScrapeFuture<'a, Option<Match<SeriesMetadata>>> {
+        Box::pin(OmdbScraper::scrape_series_metadata(
+            self, storage, title, year,
+        ))
+    }
+
+    fn scrape_season_metadata<'a>(
+        &'a self,
+        _storage: &'a Path,
+        series: ProviderId,
+        season: u16,
+        _order: &'a EpisodeOrder,
+    ) -> ScrapeFuture<'a, Option<(SeasonMetadata, Vec<EpisodeMetadata>)>> {
+        Box::pin(async move {
+            let series = series.at(Provider::Omdb)?;
+            OmdbScraper::scrape_season_metadata(self, series, season).await
+        })
+    }
+
+    fn search<'a>(
+        &'a self,
+        kind: MediaKind,
+        title: &'a str,
+        year: Option<u16>,
+    ) -> ScrapeFuture<'a, Vec<SearchResult>> {
+        Box::pin(OmdbScraper::search(self, kind, title, year))
+    }
+
+    fn movie_metadata<'a>(
+        &'a self,
+        storage: &'a Path,
+        id: ProviderId,
+    ) -> ScrapeFuture<'a, MovieMetadata> {
+        Box::pin(
+            async move { OmdbScraper::movie_metadata(self, storage, id.at(Provider::Omdb)?).await },
+        )
+    }
+
+    fn series_metadata<'a>(
+        &'a self,
+        storage: &'a Path,
+        id: ProviderId,
+    ) -> ScrapeFuture<'a, SeriesMetadata> {
+        Box::pin(async move {
+            OmdbScraper::series_metadata(self, storage, id.at(Provider::Omdb)?).await
+        })
+    }
+
+    fn take_failures(&self) -> Vec<ScrapeFailure> {
+        self.failures.take()
+    }
+}
@@ -0,0 +1,336 @@
+use super::{
+    EpisodeMetadata, EpisodeOrder, FailureKind, Failures, MATCH_THRESHOLD, Match, MediaKind,
+    MovieMetadata, ScrapeFailure, ScrapeFuture, Scraper, SearchResult, SeasonMetadata,
+    SeriesMetadata,
+};
+use serde::{Deserialize, Serialize};
+use std::{path::Path, sync::Arc};
+
+/// A source of metadata.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum Provider {
+    #[default]
+    Tmdb,
+    Tvmaze,
+    Omdb,
+    Tvdb,
+    /// Metadata read from a `.nfo` file that doesn't name an id of any other provider. It is never
+    /// looked up by id, so it is not part of [`Provider::ALL`].
+    Local,
+}
+
+impl Provider {
+    pub const ALL: [Provider; 4] = [
+        Provider::Tmdb,
+        Provider::Tvmaze,
+        Provider::Omdb,
+        Provider::Tvdb,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Provider::Tmdb => "TMDB",
+            Provider::Tvmaze => "TVmaze",
+            Provider::Omdb => "OMDb",
+            Provider::Tvdb => "TheTVDB",
+            Provider::Local => "NFO file",
+        }
+    }
+
+    /// Identifies the provider in recorded fixtures.
+    pub fn slug(self) -> &'static str {
+        match self {
+            Provider::Tmdb => "tmdb",
+            Provider::Tvmaze => "tvmaze",
+            Provider::Omdb => "omdb",
+            Provider::Tvdb => "tvdb",
+            Provider::Local => "local",
+        }
+    }
+
+    /// Whether the provider has metadata for `kind` at all.
+    pub fn supports(self, kind: MediaKind) -> bool {
+        !(self == Provider::Tvmaze && kind == MediaKind::Movie)
+    }
+
+    pub fn needs_key(self) -> bool {
+        !matches!(self, Provider::Tvmaze | Provider::Local)
+    }
+}
+
+impl std::fmt::Display for Provider {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// A movie or series as a provider knows it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ProviderId {
+    pub provider: Provider,
+    pub id: u64,
+}
+
+impl ProviderId {
+    pub fn new(provider: Provider, id: u64) -> Self {
+        ProviderId { provider, id }
+    }
+
+    /// The id, as long as it is one of `provider`'s.
+    pub fn at(self, provider: Provider) -> anyhow::Result<u64> {
+        if self.provider == provider {
+            Ok(self.id)
+        } else {
+            Err(anyhow::anyhow!("{} is not a {} id", self, provider))
+        }
+    }
+}
+
+impl std::fmt::Display for ProviderId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} {}", self.provider, self.id)
+    }
+}
+
+/// Asks each provider in turn until one has a confident match, falling back to the best
+/// unconfident one.
+///
+/// Movies and series each have their own order, and a provider missing from one is never asked
+/// about that kind of media. Lookups by id go to whichever provider the id belongs to.
+#[derive(Clone, Default)]
+pub struct ScraperChain {
+    movies: Vec<Arc<dyn Scraper>>,
+    series: Vec<Arc<dyn Scraper>>,
+    /// Rejected keys that were passed over for the next provider.
+    failures: Failures,
+}
+
+impl ScraperChain {
+    pub fn new(movies: Vec<Arc<dyn Scraper>>, series: Vec<Arc<dyn Scraper>>) -> Self {
+        ScraperChain {
+            movies,
+            series,
+            failures: Failures::default(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.movies.is_empty() && self.series.is_empty()
+    }
+
+    /// Every provider's search results, in order, along with the errors of the ones that failed.
+    pub async fn search_all(
+        &self,
+        kind: MediaKind,
+        title: &str,
+        year: Option<u16>,
+    ) -> (Vec<SearchResult>, Vec<anyhow::Error>) {
+        let mut results = vec![];
+        let mut errors = vec![];
+        for scraper in self.scrapers(kind) {
+            match scraper.search(kind, title, year).await {
+                Ok(found) => results.extend(found),
+                Err(err) => errors.push(err),
+            }
+        }
+        (results, errors)
+    }
+
+    fn scrapers(&self, kind: MediaKind) -> &[Arc<dyn Scraper>] {
+        match kind {
+            MediaKind::Movie => &self.movies,
+            MediaKind::Series => &self.series,
+        }
+    }
+
+    /// The scraper for ids of `provider`, preferring one set up for `kind`.
+    fn by_provider(&self, kind: MediaKind, provider: Provider) -> anyhow::Result<&dyn Scraper> {
+        let other = match kind {
+            MediaKind::Movie => &self.series,
+            MediaKind::Series => &self.movies,
+        };
+        self.scrapers(kind)
+            .iter()
+            .chain(other)
+            .find(|scraper| scraper.provides(provider))
+            .map(|scraper| scraper.as_ref())
+            .ok_or_else(|| anyhow::anyhow!("{} is not set up", provider))
+    }
+}
+
+/// The first confident match, or the best unconfident one.
+///
+/// Only a rejected key moves on to the next provider, as that provider can't answer until the key
+/// is fixed. Any other error ends the chain, so media a provider can't reach for the moment is
+/// retried later rather than matched by the next provider for good.
+async fn first_match<'a, T>(
+    title: &str,
+    scrapers: &'a [Arc<dyn Scraper>],
+    failures: &Failures,
+    scrape: impl Fn(&'a dyn Scraper) -> ScrapeFuture<'a, Option<Match<T>>>,
+) -> anyhow::Result<Option<Match<T>>> {
+    let mut best: Option<Match<T>> = None;
+    let mut errors = vec![];
+    for scraper in scrapers {
+        match scrape(scraper.as_ref()).await {
+            Ok(Some(matched)) if matched.confidence >= MATCH_THRESHOLD => {
+                best = Some(matched);
+                break;
+            }
+            Ok(Some(matched)) => {
+                if best
+                    .as_ref()
+                    .is_none_or(|best| matched.confidence > best.confidence)
+                {
+                    best = Some(matched);
+                }
+            }
+            Ok(None) => {}
+            Err(err) if matches!(FailureKind::of(&err), FailureKind::Auth(_)) => {
+                log::warn!("provider rejected its key, trying the next one: {:#}", err);
+                errors.push(err);
+            }
+            Err(err) => {
+                best = None;
+                errors.push(err);
+                break;
+            }
+        }
+    }
+
+    // a returned error is reported by the caller, but a rejected key needs fixing even when
+    // another provider matched instead
+    let error = if best.is_none() { errors.pop() } else { None };
+    for err in &errors {
+        if let FailureKind::Auth(_) = FailureKind::of(err) {
+            failures.report(ScrapeFailure::new(title, err, vec![]));
+        }
+    }
+
+    match (best, error) {
+        (Some(best), _) => Ok(Some(best)),
+        (None, Some(err)) => Err(err),
+        (None, None) => Ok(None),
+    }
+}
+
+impl Scraper for ScraperChain {
+    fn provides(&self, provider: Provider) -> bool {
+        self.movies
+            .iter()
+            .chain(&self.series)
+            .any(|scraper| scraper.provides(provider))
+    }
+
+    fn scrape_movie_metadata<'a>(
+        &'a self,
+        storage: &'a Path,
+        title: &'a str,
+        year: u16,
+    ) -> ScrapeFuture<'a, Option<Match<MovieMetadata>>> {
+        Box::pin(first_match(
+            title,
+            &self.movies,
+            &self.failures,
+            move |scraper| scraper.scrape_movie_metadata(storage, title, year),
+        ))
+    }
+
+    fn scrape_series_metadata<'a>(
+        &'a self,
+        storage: &'a Path,
+        title: &'a str,
+        year: Option<u16>,
+    ) -> ScrapeFuture<'a, Option<Match<SeriesMetadata>>> {
+        Box::pin(first_match(
+            title,
+            &self.series,
+            &self.failures,
+            move |scraper| scraper.scrape_series_metadata(storage, title, year),
+        ))
+    }
+
+    fn scrape_season_metadata<'a>(
+        &'a self,
+        storage: &'a Path,
+        series: ProviderId,
+        season: u16,
+        order: &'a EpisodeOrder,
+    ) -> ScrapeFuture<'a, Option<(SeasonMetadata, Vec<EpisodeMetadata>)>> {
+        Box::pin(async move {
+            self.by_provider(MediaKind::Series, series.provider)?
+                .scrape_season_metadata(storage, series, season, order)
+                .await
+        })
+    }
+
+    fn episode_orders<'a>(&'a self, series: ProviderId) -> ScrapeFuture<'a, Vec<EpisodeOrder>> {
+        Box::pin(async move {
+            self.by_provider(MediaKind::Series, series.provider)?
+                .episode_orders(series)
+                .await
+        })
+    }
+
+    fn episode_counts<'a>(
+        &'a self,
+        series: ProviderId,
+        order: &'a EpisodeOrder,
+    ) -> ScrapeFuture<'a, Vec<(u16, u16)>> {
+        Box::pin(async move {
+            self.by_provider(MediaKind::Series, series.provider)?
+                .episode_counts(series, order)
+                .await
+        })
+    }
+
+    fn search<'a>(
+        &'a self,
+        kind: MediaKind,
+        title: &'a str,
+        year: Option<u16>,
+    ) -> ScrapeFuture<'a, Vec<SearchResult>> {
+        Box::pin(async move {
+            let (results, mut errors) = self.search_all(kind, title, year).await;
+            for err in &errors {
+                log::warn!("provider search failed: {:#}", err);
+            }
+            match errors.pop() {
+                Some(err) if results.is_empty() => Err(err),
+                _ => Ok(results),
+            }
+        })
+    }
+
+    fn movie_metadata<'a>(
+        &'a self,
+        storage: &'a Path,
+        id: ProviderId,
+    ) -> ScrapeFuture<'a, MovieMetadata> {
+        Box::pin(async move {
+            self.by_provider(MediaKind::Movie, id.provider)?
+                .movie_metadata(storage, id)
+                .await
+        })
+    }
+
+    fn series_metadata<'a>(
+        &'a self,
+        storage: &'a Path,
+        id: ProviderId,
+    ) -> ScrapeFuture<'a, SeriesMetadata> {
+        Box::pin(async move {
+            self.by_provider(MediaKind::Series, id.provider)?
+                .series_metadata(storage, id)
+                .await
+        })
+    }
+
+    fn take_failures(&self) -> Vec<ScrapeFailure> {
+        let mut failures = self.failures.take();
+        for scraper in self.movies.iter().chain(&self.series) {
+            failures.extend(scraper.take_failures());
+        }
+        failures
+    }
+}
@@ -1,13 +1,18 @@
 use super::{
-    Episode, EpisodeMetadata, Library, Media, MediaId, Movie, MovieMetadata, Season,
-    SeasonMetadata, Series, SeriesMetadata,
+    Client, Credit, Details, Episode, EpisodeMetadata, EpisodeOrder, Field, Fixtures, Library,
+    Media, MediaId, Movie, MovieMetadata, Provider, ProviderId, ResponseCache, Season,
+    SeasonMetadata, Series, SeriesMetadata, Unauthorized, find_all_episodes, find_episodes, tmdb,
 };
-use async_std::stream::StreamExt;
+use anyhow::Context;
 use chrono::Datelike;
-use futures::{AsyncWriteExt, Future};
+use futures::{AsyncWriteExt, StreamExt, future::BoxFuture};
+use itertools::Itertools;
 use regex::Regex;
-use std::{path::Path, sync::LazyLock};
-use tmdb_api::{self as tmdb, prelude::Command, reqwest};
+use rustc_hash::{FxHashMap, FxHashSet};
+use std::{
+    path::{Path, PathBuf},
+    sync::{Arc, LazyLock, Mutex},
+};
 
 #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub enum MediaType {
@@ -18,9 +23,16 @@ pub enum MediaType {
     },
     Episode {
         series_title: String,
+        year: Option<u16>,
         season: u16,
         episode: u16,
     },
+    /// Numbered from the first episode of the series, as anime usually is.
+    AbsoluteEpisode {
+        series_title: String,
+        year: Option<u16>,
+        episode: u16,
+    },
 }
 
 pub fn detect_media_type(filename: &str) -> MediaType {
@@ -34,20 +46,61 @@ pub fn detect_media_type(filename: &str) -> MediaType {
     static RE_EPISODES: LazyLock<Vec<Regex>> = LazyLock::new(|| {
         vec![
             // tv show 2000 s01e01
-            Regex::new(r"^([a-z\d\s]+) \d{4} s(\d+)e(\d+) ").unwrap(),
+            Regex::new(r"^([a-z\d\s]+) (\d{4}) s(\d+)e(\d+) ").unwrap(),
             // tv show s01e01
             Regex::new(r"^([a-z\d\s]+) s(\d+)e(\d+) ").unwrap(),
         ]
     });
+    // [group] tv show - 137 [1080p]
+    static RE_ABSOLUTE: LazyLock<Regex> = LazyLock::new(|| {
+        Regex::new(r"(?i)^(.+?)\s+-\s+(?:ep?\s*)?(\d{1,4})(?:v\d+)?(?:[\s.(\[]|$)").unwrap()
+    });
+    // "alien - 3 (1992)" is a movie, not episode 3
+    static RE_YEAR_AFTER: LazyLock<Regex> =
+        LazyLock::new(|| Regex::new(r"^\s*\((?:19|20)\d{2}\)").unwrap());
+    static RE_TAGS: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"\[[^\]]*\]").unwrap());
+    static RE_TITLE_YEAR: LazyLock<Regex> =
+        LazyLock::new(|| Regex::new(r"^([a-z\d\s]+?) (\d{4})$").unwrap());
 
     let clean = RE_ALPHANUM.replace_all(filename, " ").to_lowercase();
 
     for re in &*RE_EPISODES {
         if let Some(capture) = re.captures(&clean) {
+            let n = capture.len();
             return MediaType::Episode {
                 series_title: capture.get(1).unwrap().as_str().into(),
-                season: capture.get(2).unwrap().as_str().parse().unwrap(),
-                episode: capture.get(3).unwrap().as_str().parse().unwrap(),
+                year: (n == 5).then(|| capture.get(2).unwrap().as_str().parse().unwrap()),
+                season: capture.get(n - 2).unwrap().as_str().parse().unwrap(),
+                episode: capture.get(n - 1).unwrap().as_str().parse().unwrap(),
+            };
+        }
+    }
+
+    let untagged = RE_TAGS.replace_all(filename, "");
+    let untagged = untagged.trim();
+    let absolute = RE_ABSOLUTE
+        .captures(untagged)
+        .filter(|capture| !RE_YEAR_AFTER.is_match(&untagged[capture.get(2).unwrap().end()..]));
+    if let Some(capture) = absolute {
+        let episode: u16 = capture.get(2).unwrap().as_str().parse().unwrap();
+        let title = RE_ALPHANUM
+            .replace_all(capture.get(1).unwrap().as_str(), " ")
+            .trim()
+            .to_lowercase();
+        // "movie - 2049" is a title, not episode 2049
+        let year_like = (1900..=2100).contains(&episode) && capture.get(2).unwrap().len() == 4;
+        if !title.is_empty() && !year_like {
+            let (series_title, year) = match RE_TITLE_YEAR.captures(&title) {
+                Some(capture) => (
+                    capture.get(1).unwrap().as_str().to_string(),
+                    capture.get(2).unwrap().as_str().parse().ok(),
+                ),
+                None => (title, None),
+            };
+            return MediaType::AbsoluteEpisode {
+                series_title,
+                year,
+                episode,
             };
         }
     }
@@ -64,34 +117,236 @@ pub fn detect_media_type(filename: &str) -> MediaType {
     MediaType::Unknown
 }
 
-pub trait Scraper {
-    async fn scrape_movie_metadata(
-        &self,
-        storage: &Path,
-        title: &str,
+/// Minimum confidence a scraped match needs to be applied without user review.
+pub const MATCH_THRESHOLD: f32 = 0.6;
+
+#[derive(Debug, Clone)]
+pub struct Match<T> {
+    pub metadata: T,
+    pub confidence: f32,
+}
+
+#[derive(Debug, Clone)]
+pub struct Candidate<T> {
+    pub value: T,
+    pub titles: Vec<String>,
+    pub year: Option<u16>,
+    pub popularity: f64,
+    pub vote_count: u64,
+}
+
+pub fn normalise_title(title: &str) -> String {
+    static RE_NON_WORD: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"[^\p{L}\p{N}]+").unwrap());
+    RE_NON_WORD
+        .replace_all(&title.replace('&', " and "), " ")
+        .trim()
+        .to_lowercase()
+}
+
+fn title_similarity(a: &str, b: &str) -> f32 {
+    if a == b {
+        return 1.0;
+    }
+
+    // Sørensen-Dice coefficient over words
+    let a: FxHashSet<_> = a.split_whitespace().collect();
+    let b: FxHashSet<_> = b.split_whitespace().collect();
+    if a.is_empty() || b.is_empty() {
+        return 0.0;
+    }
+    // an exact match should always beat a near-identical one
+    0.95 * 2.0 * a.intersection(&b).count() as f32 / (a.len() + b.len()) as f32
+}
+
+fn year_proximity(a: Option<u16>, b: Option<u16>) -> f32 {
+    match (a, b) {
+        (Some(a), Some(b)) => match a.abs_diff(b) {
+            0 => 1.0,
+            1 => 0.8,
+            2 => 0.4,
+            _ => 0.0,
+        },
+        _ => 0.5,
+    }
+}
+
+/// Scores every candidate against the parsed title and year, best first.
+///
+/// The score combines title similarity (against the title, original title and any alternative
+/// titles), year proximity and the candidate's popularity and vote count relative to the others.
+pub fn rank_candidates<T>(
+    title: &str,
+    year: Option<u16>,
+    candidates: Vec<Candidate<T>>,
+) -> Vec<(f32, Candidate<T>)> {
+    let title = normalise_title(title);
+
+    let max_popularity = candidates
+        .iter()
+        .map(|candidate| candidate.popularity)
+        .fold(0.0, f64::max);
+    let max_votes = candidates
+        .iter()
+        .map(|candidate| candidate.vote_count)
+        .max()
+        .unwrap_or(0);
+
+    let relative = |value: f64, max: f64| {
+        if max > 0.0 {
+            ((1.0 + value.max(0.0)).ln() / (1.0 + max).ln()) as f32
+        } else {
+            0.0
+        }
+    };
+
+    candidates
+        .into_iter()
+        .map(|candidate| {
+            let title_score = candidate
+                .titles
+                .iter()
+                .map(|other| title_similarity(&title, &normalise_title(other)))
+                .fold(0.0, f32::max);
+            let popularity_score = 0.5 * relative(candidate.popularity, max_popularity)
+                + 0.5 * relative(candidate.vote_count as f64, max_votes as f64);
+
+            let score = 0.65 * title_score
+                + 0.2 * year_proximity(year, candidate.year)
+                + 0.15 * popularity_score;
+            (score, candidate)
+        })
+        .sorted_by(|(a, _), (b, _)| b.total_cmp(a))
+        .collect()
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum MediaKind {
+    Movie,
+    Series,
+}
+
+/// A single search hit, as presented to the user when manually identifying media.
+#[derive(Debug, Clone)]
+pub struct SearchResult {
+    pub id: ProviderId,
+    pub title: String,
+    pub year: Option<u16>,
+    pub overview: String,
+    /// Remote image path for TMDB, which is fetched with [`TmdbScraper::fetch_thumbnail`], and a
+    /// full URL for other providers.
+    pub poster: Option<String>,
+}
+
+/// Boxed so that scrapers can be chained as trait objects.
+pub type ScrapeFuture<'a, T> = BoxFuture<'a, anyhow::Result<T>>;
+
+pub trait Scraper: Send + Sync {
+    /// Whether ids of `provider` can be looked up.
+    fn provides(&self, provider: Provider) -> bool;
+
+    fn scrape_movie_metadata<'a>(
+        &'a self,
+        storage: &'a Path,
+        title: &'a str,
         year: u16,
-    ) -> anyhow::Result<Option<MovieMetadata>>;
-    async fn scrape_series_metadata(
-        &self,
-        storage: &Path,
-        title: &str,
-    ) -> anyhow::Result<Option<SeriesMetadata>>;
-    async fn scrape_season_metadata(
-        &self,
-        storage: &Path,
-        series_id: u64,
+    ) -> ScrapeFuture<'a, Option<Match<MovieMetadata>>>;
+    fn scrape_series_metadata<'a>(
+        &'a self,
+        storage: &'a Path,
+        title: &'a str,
+        year: Option<u16>,
+    ) -> ScrapeFuture<'a, Option<Match<SeriesMetadata>>>;
+    /// The season as numbered by `order`. Providers without the ordering number it as aired.
+    fn scrape_season_metadata<'a>(
+        &'a self,
+        storage: &'a Path,
+        series: ProviderId,
         season: u16,
-    ) -> anyhow::Result<Option<(SeasonMetadata, Vec<EpisodeMetadata>)>>;
+        order: &'a EpisodeOrder,
+    ) -> ScrapeFuture<'a, Option<(SeasonMetadata, Vec<EpisodeMetadata>)>>;
+    /// Orderings the series can be numbered by besides the aired order.
+    fn episode_orders<'a>(&'a self, _series: ProviderId) -> ScrapeFuture<'a, Vec<EpisodeOrder>> {
+        Box::pin(async { Ok(vec![]) })
+    }
+    /// Each season's number and episode count in `order`, for placing absolutely numbered
+    /// episodes. Providers that can't tell leave those episodes unmatched.
+    fn episode_counts<'a>(
+        &'a self,
+        _series: ProviderId,
+        _order: &'a EpisodeOrder,
+    ) -> ScrapeFuture<'a, Vec<(u16, u16)>> {
+        Box::pin(async { Ok(vec![]) })
+    }
+
+    fn search<'a>(
+        &'a self,
+        kind: MediaKind,
+        title: &'a str,
+        year: Option<u16>,
+    ) -> ScrapeFuture<'a, Vec<SearchResult>>;
+    fn movie_metadata<'a>(
+        &'a self,
+        storage: &'a Path,
+        id: ProviderId,
+    ) -> ScrapeFuture<'a, MovieMetadata>;
+    fn series_metadata<'a>(
+        &'a self,
+        storage: &'a Path,
+        id: ProviderId,
+    ) -> ScrapeFuture<'a, SeriesMetadata>;
+
+    /// Takes the failures reported since the last call, which didn't stop anything from being
+    /// scraped.
+    fn take_failures(&self) -> Vec<ScrapeFailure> {
+        vec![]
+    }
+}
+
+pub const DEFAULT_IMAGE_BASE: &str = "https://image.tmdb.org/t/p";
+
+/// The kinds of TMDB artwork that are cached, each shown at a fixed logical width.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Artwork {
+    Poster,
+    Backdrop,
+    Logo,
+    Still,
 }
 
-async fn download_image(name: &str, dest: &Path) -> anyhow::Result<()> {
-    if dest.exists() {
-        return Ok(());
+impl Artwork {
+    /// Widths TMDB serves this kind of image at, besides `original`.
+    fn sizes(self) -> &'static [u32] {
+        match self {
+            Artwork::Poster => &[92, 154, 185, 342, 500, 780],
+            Artwork::Backdrop => &[300, 780, 1280],
+            Artwork::Logo => &[45, 92, 154, 185, 300, 500],
+            Artwork::Still => &[92, 185, 300],
+        }
+    }
+
+    /// Widest the image is ever drawn, in logical pixels.
+    fn display_width(self) -> f32 {
+        match self {
+            Artwork::Poster => 150.0,
+            Artwork::Backdrop => 1000.0,
+            Artwork::Logo => 300.0,
+            Artwork::Still => 160.0,
+        }
+    }
+
+    /// The smallest size that is still sharp at `scale_factor`.
+    fn size(self, scale_factor: f32) -> String {
+        let width = (self.display_width() * scale_factor).ceil() as u32;
+        self.sizes()
+            .iter()
+            .find(|&&size| size >= width)
+            .map(|size| format!("w{}", size))
+            .unwrap_or_else(|| String::from("original"))
     }
+}
 
-    let mut data = reqwest::get(format!("https://image.tmdb.org/t/p/w200/{}", name))
-        .await?
-        .bytes_stream();
+async fn download_image(url: &str, dest: &Path) -> anyhow::Result<()> {
+    let mut data = reqwest::get(url).await?.error_for_status()?.bytes_stream();
 
     let mut file = async_std::fs::File::create(dest).await?;
     while let Some(chunk) = data.next().await {
@@ -103,184 +358,1490 @@ async fn download_image(name: &str, dest: &Path) -> anyhow::Result<()> {
     Ok(())
 }
 
+/// Saves the image at `url` to `dest` unless it is already there. With `fixtures`, the image is
+/// recorded under or replayed from `key`.
+pub async fn save_image(
+    url: &str,
+    key: &str,
+    dest: &Path,
+    fixtures: Option<&Fixtures>,
+) -> anyhow::Result<()> {
+    match fixtures {
+        Some(fixtures) if fixtures.is_replaying() => {
+            if !dest.exists() {
+                async_std::fs::write(dest, fixtures.image(key).await?).await?;
+            }
+        }
+        fixtures => {
+            if !dest.exists() {
+                download_image(url, dest).await?;
+            }
+            // artwork from earlier scrapes is recorded too, so the fixtures are complete
+            if let Some(fixtures) = fixtures {
+                fixtures
+                    .record_image(key, &async_std::fs::read(dest).await?)
+                    .await;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Downloads artwork from a full URL into `storage/artwork/<dir>/`, for providers that serve
+/// images at a single size. Named like TMDB artwork, after its kind and the remote file name.
+pub async fn fetch_artwork(
+    storage: &Path,
+    dir: &str,
+    name: &str,
+    url: Option<String>,
+    fixtures: Option<&Fixtures>,
+    failures: &Failures,
+) -> Option<PathBuf> {
+    let url = url.filter(|url| url.starts_with("http"))?;
+
+    let key = url.split_once("://").map_or(url.as_str(), |(_, key)| key);
+    let remote_name = Path::new(key);
+    let stem = remote_name
+        .file_stem()
+        .and_then(|stem| stem.to_str())
+        .unwrap_or_default();
+    let extension = remote_name
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .unwrap_or("jpg");
+
+    let dir = storage.join("artwork").join(dir);
+    let path = dir.join(format!("{}-{}.{}", name, stem, extension));
+    let saved: anyhow::Result<()> = async {
+        if !path.exists() {
+            async_std::fs::create_dir_all(&dir).await?;
+        }
+        save_image(&url, key, &path, fixtures).await
+    }
+    .await;
+    artwork_saved(saved, &url, path, failures)
+}
+
+/// Artwork is optional, so failing to download it is reported rather than failing the media it
+/// belongs to.
+fn artwork_saved(
+    saved: anyhow::Result<()>,
+    url: &str,
+    path: PathBuf,
+    failures: &Failures,
+) -> Option<PathBuf> {
+    match saved {
+        Ok(()) => Some(path),
+        Err(err) => {
+            log::warn!("failed to download artwork {}: {:#}", url, err);
+            failures.report(ScrapeFailure {
+                kind: FailureKind::Artwork,
+                ..ScrapeFailure::new(url, &err, vec![])
+            });
+            None
+        }
+    }
+}
+
+/// Copies a user-chosen image into the artwork directory, so the library doesn't depend on the
+/// original file staying around.
+pub async fn import_poster(storage: &Path, source: &Path) -> anyhow::Result<PathBuf> {
+    let extension = source
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .unwrap_or("jpg");
+    let dir = storage.join("artwork").join("custom");
+    async_std::fs::create_dir_all(&dir).await?;
+    let dest = dir.join(format!(
+        "{}.{}",
+        chrono::Local::now().timestamp_millis(),
+        extension
+    ));
+    async_std::fs::copy(source, &dest).await?;
+    Ok(dest)
+}
+
+/// How many of the top-billed cast members to keep.
+pub const CAST_LIMIT: usize = 12;
+
+/// Crew jobs worth showing on a detail page, in display order.
+const KEY_CREW_JOBS: &[&str] = &[
+    "Director",
+    "Screenplay",
+    "Writer",
+    "Novel",
+    "Story",
+    "Original Music Composer",
+    "Director of Photography",
+];
+
+/// TMDB returns empty strings rather than nothing for missing translations.
+fn untranslated(text: &Option<String>) -> bool {
+    text.as_deref().is_none_or(str::is_empty)
+}
+
+/// The groups of an episode group numbered as seasons: by their order, with a group of specials
+/// as season 0.
+fn group_seasons(details: tmdb::EpisodeGroupDetails) -> Vec<(u16, tmdb::EpisodeGroup)> {
+    let mut number = 0;
+    details
+        .groups
+        .into_iter()
+        .sorted_by_key(|group| group.order)
+        .map(|group| {
+            if group.name.to_lowercase().contains("special") {
+                (0, group)
+            } else {
+                number += 1;
+                (number, group)
+            }
+        })
+        .collect()
+}
+
+fn top_billed(cast: Vec<tmdb::CastMember>) -> Vec<Credit> {
+    cast.into_iter()
+        .take(CAST_LIMIT)
+        .map(|member| Credit {
+            name: member.name,
+            role: member.character,
+        })
+        .collect()
+}
+
+fn key_crew(crew: Vec<tmdb::CrewMember>) -> Vec<Credit> {
+    KEY_CREW_JOBS
+        .iter()
+        .flat_map(|job| {
+            crew.iter()
+                .filter(move |member| member.job == *job)
+                .map(|member| Credit {
+                    name: member.name.clone(),
+                    role: member.job.clone(),
+                })
+        })
+        .collect()
+}
+
+#[derive(Clone)]
 pub struct TmdbScraper {
-    client: tmdb::client::ReqwestClient,
+    client: Client,
+    image_base: String,
+    scale_factor: f32,
+    /// IETF tag such as `de-DE`.
+    language: String,
+    /// Used for overviews and taglines that have not been translated into `language`.
+    fallback_language: String,
+    /// ISO 3166-1 country whose certification and release date are recorded.
+    region: String,
+    fixtures: Option<Fixtures>,
+    failures: Failures,
 }
 
 impl TmdbScraper {
+    /// How many of the top-ranked candidates to fetch alternative titles for.
+    const ALTERNATIVE_TITLE_CANDIDATES: usize = 3;
+
     pub fn new(secret: &str) -> Self {
-        let client = tmdb::client::ReqwestClient::new(secret.into());
-        TmdbScraper { client }
+        let client = tmdb::client(secret);
+        TmdbScraper {
+            client,
+            image_base: String::from(DEFAULT_IMAGE_BASE),
+            scale_factor: 1.0,
+            language: String::from("en-US"),
+            fallback_language: String::from("en-US"),
+            region: String::from("US"),
+            fixtures: None,
+            failures: Failures::default(),
+        }
     }
-}
 
-impl Scraper for TmdbScraper {
-    async fn scrape_movie_metadata(
+    /// Sends API requests to a mirror or stand-in server instead of TMDB itself.
+    pub fn api_base(mut self, api_base: &str) -> Self {
+        self.client = self.client.api_base(api_base);
+        self
+    }
+
+    /// Downloads images from a mirror instead of TMDB's image server. Sizes such as `w185` are
+    /// appended to this.
+    pub fn image_base(mut self, image_base: &str) -> Self {
+        self.image_base = image_base.trim_end_matches('/').to_string();
+        self
+    }
+
+    pub fn cache(mut self, cache: ResponseCache) -> Self {
+        self.client = self.client.cache(cache);
+        self
+    }
+
+    /// Records every response and image into `fixtures`, or answers only from them when
+    /// replaying.
+    pub fn fixtures(mut self, fixtures: Fixtures) -> Self {
+        self.client = self.client.fixtures(fixtures.clone());
+        self.fixtures = Some(fixtures);
+        self
+    }
+
+    pub fn language(mut self, language: &str) -> Self {
+        self.language = language.trim().to_string();
+        self
+    }
+
+    pub fn fallback_language(mut self, language: &str) -> Self {
+        self.fallback_language = language.trim().to_string();
+        self
+    }
+
+    pub fn region(mut self, region: &str) -> Self {
+        self.region = region.trim().to_uppercase();
+        self
+    }
+
+    /// Makes a request that needs nothing but the secret, to check it before anything is scraped.
+    /// Meant for a scraper without a response cache, as a cached response vouches for any secret.
+    pub async fn check_secret(&self) -> anyhow::Result<()> {
+        let auth: tmdb::Authentication = self
+            .client
+            .get("/authentication", &[])
+            .await
+            .context("tmdb authentication failed")?;
+        anyhow::ensure!(auth.success, "tmdb didn't accept the secret");
+        Ok(())
+    }
+
+    /// Image languages to request, most preferred first. Images are tagged with ISO 639-1 codes
+    /// only, and `null` for those without text.
+    fn image_languages(&self) -> Vec<&str> {
+        let mut languages = vec![];
+        for language in [&self.language, &self.fallback_language] {
+            let code = language.split('-').next().unwrap_or_default();
+            if !code.is_empty() && !languages.contains(&code) {
+                languages.push(code);
+            }
+        }
+        languages.push("null");
+        languages
+    }
+
+    /// Picks the image in the most preferred language.
+    fn preferred_image(&self, images: Vec<tmdb::Image>) -> Option<String> {
+        let languages = self.image_languages();
+        images
+            .into_iter()
+            .min_by_key(|image| {
+                let language = image.iso_639_1.as_deref().unwrap_or("null");
+                languages
+                    .iter()
+                    .position(|other| *other == language)
+                    .unwrap_or(languages.len())
+            })
+            .map(|image| image.file_path)
+    }
+
+    /// Fetches `path` again in the fallback language, if anything is missing a translation.
+    async fn get_fallback<T: serde::de::DeserializeOwned>(
         &self,
-        storage: &Path,
-        title: &str,
-        year: u16,
-    ) -> anyhow::Result<Option<MovieMetadata>> {
-        let search = tmdb::movie::search::MovieSearch::new(title.to_string()).with_year(Some(year));
-        let result = search
-            .execute(&self.client)
+        path: &str,
+        untranslated: bool,
+    ) -> Option<T> {
+        if !untranslated
+            || self.fallback_language.is_empty()
+            || self.fallback_language == self.language
+        {
+            return None;
+        }
+        self.get_localised(path, &self.fallback_language, &[])
             .await
-            .map_err(|_| anyhow::anyhow!("tmdb movie search failed"))?;
+            .ok()
+    }
 
-        let Some(result) = result.results.first().map(|movie| movie.inner.clone()) else {
-            return Ok(None);
+    async fn get_localised<T: serde::de::DeserializeOwned>(
+        &self,
+        path: &str,
+        language: &str,
+        query: &[(&str, String)],
+    ) -> anyhow::Result<T> {
+        let mut query = query.to_vec();
+        if !language.is_empty() {
+            query.push(("language", language.to_string()));
+        }
+        self.client.get(path, &query).await
+    }
+
+    /// Sets the display scale factor artwork is downloaded for.
+    pub fn scale_factor(mut self, scale_factor: f32) -> Self {
+        self.scale_factor = scale_factor.max(1.0);
+        self
+    }
+
+    /// Fetches a small poster into memory, for previews that shouldn't touch the storage
+    /// directory. Other providers' posters are full URLs, which are fetched as they are.
+    pub async fn fetch_thumbnail(&self, poster: &str) -> anyhow::Result<Vec<u8>> {
+        let (url, image) = match poster.split_once("://") {
+            Some((_, image)) => (poster.to_string(), image.to_string()),
+            None => {
+                let image = format!("w92/{}", poster.trim_start_matches('/'));
+                (format!("{}/{}", self.image_base, image), image)
+            }
         };
+        if let Some(fixtures) = self.fixtures.as_ref().filter(|f| f.is_replaying()) {
+            return fixtures.image(&image).await;
+        }
+
+        let data = reqwest::get(url)
+            .await?
+            .error_for_status()?
+            .bytes()
+            .await?
+            .to_vec();
+        if let Some(fixtures) = &self.fixtures {
+            fixtures.record_image(&image, &data).await;
+        }
+        Ok(data)
+    }
+
+    /// Downloads artwork into `storage/artwork/<dir>/`, unless it is already cached.
+    ///
+    /// Files are named after the kind of artwork and TMDB's own file name, so replaced artwork is
+    /// picked up. Images are fetched at the smallest size that is sharp at the current scale
+    /// factor, and anything above 1x gets an `@<n>x` suffix so a blurry copy isn't reused.
+    async fn download_artwork(
+        &self,
+        storage: &Path,
+        dir: &str,
+        name: &str,
+        kind: Artwork,
+        remote_path: Option<String>,
+    ) -> Option<PathBuf> {
+        let remote_path = remote_path?;
 
-        let poster = if let Some(poster_path) = result.poster_path {
-            let poster_path = poster_path.replace('/', "");
-            let path = storage.join(&poster_path);
-            download_image(&poster_path, &path).await?;
-            Some(path)
+        let remote_name = Path::new(&remote_path);
+        let stem = remote_name
+            .file_stem()
+            .and_then(|stem| stem.to_str())
+            .unwrap_or_default();
+        let extension = remote_name
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .unwrap_or("jpg");
+        let scale = self.scale_factor.ceil() as u32;
+        let file_name = if scale > 1 {
+            format!("{}-{}@{}x.{}", name, stem, scale, extension)
         } else {
-            None
+            format!("{}-{}.{}", name, stem, extension)
+        };
+
+        let dir = storage.join("artwork").join(dir);
+        let path = dir.join(file_name);
+        let image = format!(
+            "{}/{}",
+            kind.size(self.scale_factor),
+            remote_path.trim_start_matches('/')
+        );
+        let url = format!("{}/{}", self.image_base, image);
+        let saved: anyhow::Result<()> = async {
+            if !path.exists() {
+                async_std::fs::create_dir_all(&dir).await?;
+            }
+            save_image(&url, &image, &path, self.fixtures.as_ref()).await
+        }
+        .await;
+        artwork_saved(saved, &url, path, &self.failures)
+    }
+
+    async fn search_movies(
+        &self,
+        title: &str,
+        year: Option<u16>,
+    ) -> anyhow::Result<Vec<tmdb::MovieResult>> {
+        let mut query = vec![("query", title.to_string())];
+        if let Some(year) = year {
+            query.push(("year", year.to_string()));
+        }
+
+        let results: tmdb::Page<tmdb::MovieResult> = self
+            .get_localised("/search/movie", &self.language, &query)
+            .await
+            .context("tmdb movie search failed")?;
+        Ok(results.results)
+    }
+
+    async fn search_series(
+        &self,
+        title: &str,
+        year: Option<u16>,
+    ) -> anyhow::Result<Vec<tmdb::TvResult>> {
+        let search = async |year: Option<u16>| {
+            let mut query = vec![("query", title.to_string())];
+            if let Some(year) = year {
+                query.push(("first_air_date_year", year.to_string()));
+            }
+            self.get_localised::<tmdb::Page<tmdb::TvResult>>("/search/tv", &self.language, &query)
+                .await
+                .context("tmdb tv show search failed")
         };
 
-        Ok(Some(MovieMetadata {
-            tmdb_id: result.id,
+        // the year in a file name is not always the first air date (e.g. a reboot's production
+        // year), so fall back to an unfiltered search and let ranking weigh the year instead
+        let mut results = search(year).await?;
+        if results.results.is_empty() && year.is_some() {
+            results = search(None).await?;
+        }
+        Ok(results.results)
+    }
+
+    /// Re-ranks the leading candidates with their alternative titles if none of them matched the
+    /// query exactly by primary or original title.
+    async fn rank_with_alternative_titles<T>(
+        &self,
+        title: &str,
+        year: Option<u16>,
+        candidates: Vec<Candidate<T>>,
+        kind: MediaKind,
+        tmdb_id: impl Fn(&T) -> u64,
+    ) -> Vec<(f32, Candidate<T>)> {
+        let mut ranked = rank_candidates(title, year, candidates);
+
+        let query = normalise_title(title);
+        let exact = ranked.iter().any(|(_, candidate)| {
+            candidate
+                .titles
+                .iter()
+                .any(|other| normalise_title(other) == query)
+        });
+        if exact || ranked.is_empty() {
+            return ranked;
+        }
+
+        for (_, candidate) in ranked.iter_mut().take(Self::ALTERNATIVE_TITLE_CANDIDATES) {
+            if let Ok(titles) = self
+                .alternative_titles(kind, tmdb_id(&candidate.value))
+                .await
+            {
+                candidate.titles.extend(titles);
+            }
+        }
+
+        rank_candidates(
+            title,
+            year,
+            ranked.into_iter().map(|(_, candidate)| candidate).collect(),
+        )
+    }
+
+    async fn alternative_titles(
+        &self,
+        kind: MediaKind,
+        tmdb_id: u64,
+    ) -> anyhow::Result<Vec<String>> {
+        Ok(match kind {
+            MediaKind::Movie => {
+                self.client
+                    .get::<tmdb::MovieAlternativeTitles>(
+                        &format!("/movie/{}/alternative_titles", tmdb_id),
+                        &[],
+                    )
+                    .await?
+                    .titles
+            }
+            MediaKind::Series => {
+                self.client
+                    .get::<tmdb::TvAlternativeTitles>(
+                        &format!("/tv/{}/alternative_titles", tmdb_id),
+                        &[],
+                    )
+                    .await?
+                    .results
+            }
+        }
+        .into_iter()
+        .map(|title| title.title)
+        .collect())
+    }
+
+    /// Metadata straight from a search result, without details or artwork.
+    fn movie_from_result(result: tmdb::MovieResult) -> MovieMetadata {
+        MovieMetadata {
+            provider: Provider::Tmdb,
+            provider_id: result.id,
             title: result.title,
             year: result
                 .release_date
                 .map(|date| date.year() as u16)
                 .unwrap_or(0),
-            poster,
+            poster: None,
+            backdrop: None,
             released: result.release_date,
-        }))
+            details: Details::default(),
+            locked: Default::default(),
+        }
     }
 
-    async fn scrape_series_metadata(
+    async fn movie_from_details(
         &self,
         storage: &Path,
-        title: &str,
-    ) -> anyhow::Result<Option<SeriesMetadata>> {
-        let search = tmdb::tvshow::search::TVShowSearch::new(title.to_string());
-        let result = search
-            .execute(&self.client)
-            .await
-            .map_err(|_| anyhow::anyhow!("tmdb tv show search failed"))?;
-
-        let Some(result) = result.results.first().map(|series| series.inner.clone()) else {
-            return Ok(None);
+        details: tmdb::MovieDetails,
+    ) -> anyhow::Result<MovieMetadata> {
+        let dir = format!("movie/{}", details.id);
+        let regional = details
+            .release_dates
+            .and_then(|dates| {
+                dates
+                    .results
+                    .into_iter()
+                    .find(|country| country.iso_3166_1 == self.region)
+            })
+            .map(|country| country.release_dates)
+            .unwrap_or_default();
+        let certification = regional
+            .iter()
+            .map(|date| date.certification.clone())
+            .find(|certification| !certification.is_empty());
+        // prefer the theatrical release, then the earliest of any other kind
+        let earliest = |theatrical: bool| {
+            regional
+                .iter()
+                .filter(|date| !theatrical || date.kind == 3)
+                .filter_map(|date| date.release_date)
+                .min()
         };
+        let released = earliest(true)
+            .or_else(|| earliest(false))
+            .or(details.release_date);
+        let poster = self
+            .preferred_image(details.images.posters)
+            .or(details.poster_path);
 
-        let poster = if let Some(poster_path) = result.poster_path {
-            let poster_path = poster_path.replace('/', "");
-            let path = storage.join(&poster_path);
-            download_image(&poster_path, &path).await?;
-            Some(path)
-        } else {
-            None
-        };
+        Ok(MovieMetadata {
+            provider: Provider::Tmdb,
+            provider_id: details.id,
+            title: details.title,
+            year: details
+                .release_date
+                .map(|date| date.year() as u16)
+                .unwrap_or(0),
+            poster: self
+                .download_artwork(storage, &dir, "poster", Artwork::Poster, poster)
+                .await,
+            backdrop: self
+                .download_artwork(
+                    storage,
+                    &dir,
+                    "backdrop",
+                    Artwork::Backdrop,
+                    details.backdrop_path,
+                )
+                .await,
+            released,
+            details: Details {
+                overview: details.overview.filter(|overview| !overview.is_empty()),
+                tagline: details.tagline.filter(|tagline| !tagline.is_empty()),
+                genres: details.genres.into_iter().map(|genre| genre.name).collect(),
+                runtime: details.runtime.filter(|&runtime| runtime > 0),
+                certification,
+                rating: (details.vote_count > 0).then_some(details.vote_average),
+                original_title: Some(details.original_title).filter(|title| !title.is_empty()),
+                original_language: Some(details.original_language)
+                    .filter(|language| !language.is_empty()),
+                studios: details
+                    .production_companies
+                    .into_iter()
+                    .map(|company| company.name)
+                    .collect(),
+                cast: top_billed(details.credits.cast),
+                crew: key_crew(details.credits.crew),
+            },
+            locked: Default::default(),
+        })
+    }
 
-        Ok(Some(SeriesMetadata {
-            tmdb_id: result.id,
+    /// Metadata straight from a search result, without details or artwork.
+    fn series_from_result(result: tmdb::TvResult) -> SeriesMetadata {
+        SeriesMetadata {
+            provider: Provider::Tmdb,
+            provider_id: result.id,
             title: result.name,
-            poster,
+            poster: None,
+            backdrop: None,
+            logo: None,
             aired: result.first_air_date,
-        }))
+            details: Details::default(),
+            next_episode: None,
+            locked: Default::default(),
+        }
     }
 
-    async fn scrape_season_metadata(
+    async fn series_from_details(
         &self,
         storage: &Path,
-        series_id: u64,
-        season: u16,
-    ) -> anyhow::Result<Option<(SeasonMetadata, Vec<EpisodeMetadata>)>> {
-        let details =
-            tmdb::tvshow::season::details::TVShowSeasonDetails::new(series_id, season as _)
-                .execute(&self.client)
-                .await
-                .map_err(|err| anyhow::anyhow!("tmdb tv show season details failed: {}", err))?;
-
-        let poster = if let Some(poster_path) = details.inner.poster_path {
-            let poster_path = poster_path.replace('/', "");
-            let path = storage.join(&poster_path);
-            download_image(&poster_path, &path).await?;
-            Some(path)
-        } else {
-            None
-        };
+        details: tmdb::TvDetails,
+    ) -> anyhow::Result<SeriesMetadata> {
+        let dir = format!("tv/{}", details.id);
+        // SVG logos can't be displayed
+        let logo = self.preferred_image(
+            details
+                .images
+                .logos
+                .into_iter()
+                .filter(|logo| logo.file_path.ends_with(".png"))
+                .collect(),
+        );
+        let poster = self
+            .preferred_image(details.images.posters)
+            .or(details.poster_path);
+        let certification = details.content_ratings.and_then(|ratings| {
+            ratings
+                .results
+                .into_iter()
+                .find(|rating| rating.iso_3166_1 == self.region)
+                .map(|rating| rating.rating)
+                .filter(|rating| !rating.is_empty())
+        });
 
-        let episodes = details
-            .episodes
+        let mut crew: Vec<_> = details
+            .created_by
             .into_iter()
-            .map(|episode| EpisodeMetadata {
-                series_tmdb_id: series_id,
-                title: episode.inner.name,
-                season,
-                episode: episode.inner.episode_number as _,
-                aired: episode.inner.air_date,
+            .map(|creator| Credit {
+                name: creator.name,
+                role: String::from("Creator"),
             })
             .collect();
+        crew.extend(key_crew(details.credits.crew));
 
-        Ok(Some((
-            SeasonMetadata {
-                series_tmdb_id: series_id,
-                title: details.inner.name,
-                season,
-                poster,
-                aired: details.inner.air_date,
-                overview: details.inner.overview,
+        Ok(SeriesMetadata {
+            provider: Provider::Tmdb,
+            provider_id: details.id,
+            title: details.name,
+            poster: self
+                .download_artwork(storage, &dir, "poster", Artwork::Poster, poster)
+                .await,
+            backdrop: self
+                .download_artwork(
+                    storage,
+                    &dir,
+                    "backdrop",
+                    Artwork::Backdrop,
+                    details.backdrop_path,
+                )
+                .await,
+            logo: self
+                .download_artwork(storage, &dir, "logo", Artwork::Logo, logo)
+                .await,
+            aired: details.first_air_date,
+            details: Details {
+                overview: details.overview.filter(|overview| !overview.is_empty()),
+                tagline: details.tagline.filter(|tagline| !tagline.is_empty()),
+                genres: details.genres.into_iter().map(|genre| genre.name).collect(),
+                runtime: details.episode_run_time.first().copied(),
+                certification,
+                rating: (details.vote_count > 0).then_some(details.vote_average),
+                original_title: Some(details.original_name).filter(|title| !title.is_empty()),
+                original_language: Some(details.original_language)
+                    .filter(|language| !language.is_empty()),
+                studios: details
+                    .networks
+                    .into_iter()
+                    .map(|network| network.name)
+                    .collect(),
+                cast: top_billed(details.credits.cast),
+                crew,
             },
-            episodes,
-        )))
+            next_episode: details.next_episode_to_air.map(|episode| EpisodeMetadata {
+                provider: Provider::Tmdb,
+                series_provider_id: details.id,
+                title: episode.name,
+                season: episode.season_number,
+                episode: episode.episode_number,
+                aired: episode.air_date,
+                overview: episode.overview.filter(|overview| !overview.is_empty()),
+                runtime: episode.runtime,
+                still: None,
+                locked: Default::default(),
+            }),
+            locked: Default::default(),
+        })
     }
 }
 
-#[derive(Debug, Clone)]
-struct SeasonScrapeResult {
-    metadata: SeasonMetadata,
-    episodes: Vec<(MediaId, EpisodeMetadata)>,
-    unmatched: Vec<EpisodeMetadata>,
-}
+/// The TMDB side of [`Scraper`], by TMDB id.
+impl TmdbScraper {
+    async fn scrape_movie_metadata(
+        &self,
+        storage: &Path,
+        title: &str,
+        year: u16,
+    ) -> anyhow::Result<Option<Match<MovieMetadata>>> {
+        let candidates = self
+            .search_movies(title, Some(year))
+            .await?
+            .into_iter()
+            .map(|movie| Candidate {
+                titles: vec![movie.title.clone(), movie.original_title.clone()],
+                year: movie.release_date.map(|date| date.year() as u16),
+                popularity: movie.popularity,
+                vote_count: movie.vote_count,
+                value: movie,
+            })
+            .collect();
 
-#[derive(Debug, Clone)]
-struct SeriesScrapeResult {
-    metadata: SeriesMetadata,
-    seasons: Vec<SeasonScrapeResult>,
-}
+        let ranked = self
+            .rank_with_alternative_titles(
+                title,
+                Some(year),
+                candidates,
+                MediaKind::Movie,
+                |movie| movie.id,
+            )
+            .await;
 
-#[derive(Debug, Clone)]
-pub struct ScrapeResult {
-    movies: Vec<(MediaId, MovieMetadata)>,
-    series: Vec<SeriesScrapeResult>,
+        let Some((confidence, Candidate { value: result, .. })) = ranked.into_iter().next() else {
+            return Ok(None);
+        };
+
+        let metadata = if confidence < MATCH_THRESHOLD {
+            // not applied automatically, so don't bother fetching details or artwork
+            Self::movie_from_result(result)
+        } else {
+            self.movie_metadata(storage, result.id).await?
+        };
+
+        Ok(Some(Match {
+            metadata,
+            confidence,
+        }))
+    }
+
+    async fn scrape_series_metadata(
+        &self,
+        storage: &Path,
+        title: &str,
+        year: Option<u16>,
+    ) -> anyhow::Result<Option<Match<SeriesMetadata>>> {
+        let candidates = self
+            .search_series(title, year)
+            .await?
+            .into_iter()
+            .map(|series| Candidate {
+                titles: vec![series.name.clone(), series.original_name.clone()],
+                year: series.first_air_date.map(|date| date.year() as u16),
+                popularity: series.popularity,
+                vote_count: series.vote_count,
+                value: series,
+            })
+            .collect();
+
+        let ranked = self
+            .rank_with_alternative_titles(title, year, candidates, MediaKind::Series, |series| {
+                series.id
+            })
+            .await;
+
+        let Some((confidence, Candidate { value: result, .. })) = ranked.into_iter().next() else {
+            return Ok(None);
+        };
+
+        let metadata = if confidence < MATCH_THRESHOLD {
+            Self::series_from_result(result)
+        } else {
+            self.series_metadata(storage, result.id).await?
+        };
+
+        Ok(Some(Match {
+            metadata,
+            confidence,
+        }))
+    }
+
+    async fn scrape_season_metadata(
+        &self,
+        storage: &Path,
+        series_id: u64,
+        season: u16,
+    ) -> anyhow::Result<Option<(SeasonMetadata, Vec<EpisodeMetadata>)>> {
+        let path = format!("/tv/{}/season/{}", series_id, season);
+        let mut details: tmdb::SeasonDetails = self
+            .get_localised(&path, &self.language, &[])
+            .await
+            .context("tmdb tv show season details failed")?;
+
+        let missing = untranslated(&details.overview)
+            || details
+                .episodes
+                .iter()
+                .any(|episode| untranslated(&episode.overview) || episode.name.is_empty());
+        if let Some(fallback) = self
+            .get_fallback::<tmdb::SeasonDetails>(&path, missing)
+            .await
+        {
+            if untranslated(&details.overview) {
+                details.overview = fallback.overview;
+            }
+            for episode in &mut details.episodes {
+                let Some(other) = fallback
+                    .episodes
+                    .iter()
+                    .find(|other| other.episode_number == episode.episode_number)
+                else {
+                    continue;
+                };
+                if untranslated(&episode.overview) {
+                    episode.overview = other.overview.clone();
+                }
+                if episode.name.is_empty() {
+                    episode.name = other.name.clone();
+                }
+            }
+        }
+
+        let dir = format!("tv/{}/season-{}", series_id, season);
+        let poster = self
+            .download_artwork(
+                storage,
+                &dir,
+                "poster",
+                Artwork::Poster,
+                details.poster_path,
+            )
+            .await;
+
+        let mut episodes = Vec::with_capacity(details.episodes.len());
+        for episode in details.episodes {
+            let still = self
+                .download_artwork(
+                    storage,
+                    &dir,
+                    &format!("episode-{}", episode.episode_number),
+                    Artwork::Still,
+                    episode.still_path,
+                )
+                .await;
+
+            episodes.push(EpisodeMetadata {
+                provider: Provider::Tmdb,
+                series_provider_id: series_id,
+                title: episode.name,
+                season,
+                episode: episode.episode_number,
+                aired: episode.air_date,
+                overview: episode.overview.filter(|overview| !overview.is_empty()),
+                runtime: episode.runtime,
+                still,
+                locked: Default::default(),
+            });
+        }
+
+        Ok(Some((
+            SeasonMetadata {
+                provider: Provider::Tmdb,
+                series_provider_id: series_id,
+                title: details.name,
+                season,
+                poster,
+                aired: details.air_date,
+                overview: details.overview,
+                episodes: episodes.clone(),
+                locked: Default::default(),
+            },
+            episodes,
+        )))
+    }
+
+    async fn episode_groups(&self, series_id: u64) -> anyhow::Result<Vec<EpisodeOrder>> {
+        let groups: tmdb::Page<tmdb::EpisodeGroupSummary> = self
+            .get_localised(
+                &format!("/tv/{}/episode_groups", series_id),
+                &self.language,
+                &[],
+            )
+            .await
+            .context("tmdb episode groups failed")?;
+
+        Ok(groups
+            .results
+            .into_iter()
+            .map(|group| {
+                let kind = match group.kind {
+                    1 => "Original air date",
+                    2 => "Absolute",
+                    3 => "DVD",
+                    4 => "Digital",
+                    5 => "Story arc",
+                    6 => "Production",
+                    7 => "TV",
+                    _ => "Other",
+                };
+                EpisodeOrder::Group {
+                    id: group.id,
+                    name: format!("{} ({})", group.name, kind),
+                }
+            })
+            .collect())
+    }
+
+    async fn episode_counts(
+        &self,
+        series_id: u64,
+        order: &EpisodeOrder,
+    ) -> anyhow::Result<Vec<(u16, u16)>> {
+        if let EpisodeOrder::Group { id, .. } = order {
+            let details = self.episode_group(id).await?;
+            return Ok(group_seasons(details)
+                .into_iter()
+                .map(|(season, group)| (season, group.episodes.len() as u16))
+                .collect());
+        }
+
+        let details: tmdb::TvDetails = self
+            .get_localised(&format!("/tv/{}", series_id), &self.language, &[])
+            .await
+            .context("tmdb tv show details failed")?;
+        Ok(details
+            .seasons
+            .into_iter()
+            .map(|season| (season.season_number, season.episode_count))
+            .collect())
+    }
+
+    async fn episode_group(&self, group_id: &str) -> anyhow::Result<tmdb::EpisodeGroupDetails> {
+        self.get_localised(
+            &format!("/tv/episode_group/{}", group_id),
+            &self.language,
+            &[],
+        )
+        .await
+        .context("tmdb episode group failed")
+    }
+
+    /// A season of an episode group, numbered as by [`group_seasons`], with its episodes numbered
+    /// by their position in the group.
+    async fn scrape_group_season(
+        &self,
+        storage: &Path,
+        series_id: u64,
+        group_id: &str,
+        season: u16,
+    ) -> anyhow::Result<Option<(SeasonMetadata, Vec<EpisodeMetadata>)>> {
+        let details = self.episode_group(group_id).await?;
+        let group = group_seasons(details)
+            .into_iter()
+            .find(|(number, _)| *number == season);
+        let Some((_, group)) = group else {
+            return Ok(None);
+        };
+
+        // groups have no artwork of their own, so borrow the aired season's poster
+        let poster = self
+            .get_localised::<tmdb::SeasonDetails>(
+                &format!("/tv/{}/season/{}", series_id, season),
+                &self.language,
+                &[],
+            )
+            .await
+            .ok()
+            .and_then(|details| details.poster_path);
+        let dir = format!("tv/{}/season-{}", series_id, season);
+        let poster = self
+            .download_artwork(storage, &dir, "poster", Artwork::Poster, poster)
+            .await;
+
+        let mut episodes = Vec::with_capacity(group.episodes.len());
+        for (i, episode) in group
+            .episodes
+            .into_iter()
+            .sorted_by_key(|episode| episode.order)
+            .enumerate()
+        {
+            let number = i as u16 + 1;
+            let still = self
+                .download_artwork(
+                    storage,
+                    &dir,
+                    &format!("episode-{}", number),
+                    Artwork::Still,
+                    episode.still_path,
+                )
+                .await;
+
+            episodes.push(EpisodeMetadata {
+                provider: Provider::Tmdb,
+                series_provider_id: series_id,
+                title: episode.name,
+                season,
+                episode: number,
+                aired: episode.air_date,
+                overview: episode.overview.filter(|overview| !overview.is_empty()),
+                runtime: episode.runtime,
+                still,
+                locked: Default::default(),
+            });
+        }
+
+        Ok(Some((
+            SeasonMetadata {
+                provider: Provider::Tmdb,
+                series_provider_id: series_id,
+                title: group.name,
+                season,
+                poster,
+                aired: episodes.iter().filter_map(|episode| episode.aired).min(),
+                overview: None,
+                episodes: episodes.clone(),
+                locked: Default::default(),
+            },
+            episodes,
+        )))
+    }
+
+    async fn search(
+        &self,
+        kind: MediaKind,
+        title: &str,
+        year: Option<u16>,
+    ) -> anyhow::Result<Vec<SearchResult>> {
+        Ok(match kind {
+            MediaKind::Movie => self
+                .search_movies(title, year)
+                .await?
+                .into_iter()
+                .map(|movie| SearchResult {
+                    id: ProviderId::new(Provider::Tmdb, movie.id),
+                    title: movie.title,
+                    year: movie.release_date.map(|date| date.year() as u16),
+                    overview: movie.overview.unwrap_or_default(),
+                    poster: movie.poster_path,
+                })
+                .collect(),
+            MediaKind::Series => self
+                .search_series(title, year)
+                .await?
+                .into_iter()
+                .map(|series| SearchResult {
+                    id: ProviderId::new(Provider::Tmdb, series.id),
+                    title: series.name,
+                    year: series.first_air_date.map(|date| date.year() as u16),
+                    overview: series.overview.unwrap_or_default(),
+                    poster: series.poster_path,
+                })
+                .collect(),
+        })
+    }
+
+    async fn movie_metadata(&self, storage: &Path, tmdb_id: u64) -> anyhow::Result<MovieMetadata> {
+        let path = format!("/movie/{}", tmdb_id);
+        let mut details: tmdb::MovieDetails = self
+            .get_localised(
+                &path,
+                &self.language,
+                &[
+                    (
+                        "append_to_response",
+                        "credits,release_dates,images".to_string(),
+                    ),
+                    ("include_image_language", self.image_languages().join(",")),
+                ],
+            )
+            .await
+            .context("tmdb movie details failed")?;
+
+        let missing = untranslated(&details.overview) || untranslated(&details.tagline);
+        if let Some(fallback) = self
+            .get_fallback::<tmdb::MovieDetails>(&path, missing)
+            .await
+        {
+            if untranslated(&details.overview) {
+                details.overview = fallback.overview;
+            }
+            if untranslated(&details.tagline) {
+                details.tagline = fallback.tagline;
+            }
+        }
+
+        self.movie_from_details(storage, details).await
+    }
+
+    async fn series_metadata(
+        &self,
+        storage: &Path,
+        tmdb_id: u64,
+    ) -> anyhow::Result<SeriesMetadata> {
+        let path = format!("/tv/{}", tmdb_id);
+        let mut details: tmdb::TvDetails = self
+            .get_localised(
+                &path,
+                &self.language,
+                &[
+                    (
+                        "append_to_response",
+                        "credits,content_ratings,images".to_string(),
+                    ),
+                    ("include_image_language", self.image_languages().join(",")),
+                ],
+            )
+            .await
+            .context("tmdb tv show details failed")?;
+
+        let missing = untranslated(&details.overview) || untranslated(&details.tagline);
+        if let Some(fallback) = self.get_fallback::<tmdb::TvDetails>(&path, missing).await {
+            if untranslated(&details.overview) {
+                details.overview = fallback.overview;
+            }
+            if untranslated(&details.tagline) {
+                details.tagline = fallback.tagline;
+            }
+        }
+
+        self.series_from_details(storage, details).await
+    }
+}
+
+impl Scraper for TmdbScraper {
+    fn provides(&self, provider: Provider) -> bool {
+        provider == Provider::Tmdb
+    }
+
+    fn scrape_movie_metadata<'a>(
+        &'a self,
+        storage: &'a Path,
+        title: &'a str,
+        year: u16,
+    ) -> ScrapeFuture<'a, Option<Match<MovieMetadata>>> {
+        Box::pin(TmdbScraper::scrape_movie_metadata(
+            self, storage, title, year,
+        ))
+    }
+
+    fn scrape_series_metadata<'a>(
+        &'a self,
+        storage: &'a Path,
+        title: &'a str,
+        year: Option<u16>,
+    ) -> ScrapeFuture<'a, Option<Match<SeriesMetadata>>> {
+        Box::pin(TmdbScraper::scrape_series_metadata(
+            self, storage, title, year,
+        ))
+    }
+
+    fn scrape_season_metadata<'a>(
+        &'a self,
+        storage: &'a Path,
+        series: ProviderId,
+        season: u16,
+        order: &'a EpisodeOrder,
+    ) -> ScrapeFuture<'a, Option<(SeasonMetadata, Vec<EpisodeMetadata>)>> {
+        Box::pin(async move {
+            let series = series.at(Provider::Tmdb)?;
+            match order {
+                EpisodeOrder::Aired => {
+                    TmdbScraper::scrape_season_metadata(self, storage, series, season).await
+                }
+                EpisodeOrder::Group { id, .. } => {
+                    self.scrape_group_season(storage, series, id, season).await
+                }
+            }
+        })
+    }
+
+    fn episode_orders<'a>(&'a self, series: ProviderId) -> ScrapeFuture<'a, Vec<EpisodeOrder>> {
+        Box::pin(async move { self.episode_groups(series.at(Provider::Tmdb)?).await })
+    }
+
+    fn episode_counts<'a>(
+        &'a self,
+        series: ProviderId,
+        order: &'a EpisodeOrder,
+    ) -> ScrapeFuture<'a, Vec<(u16, u16)>> {
+        Box::pin(async move { self.episode_counts(series.at(Provider::Tmdb)?, order).await })
+    }
+
+    fn search<'a>(
+        &'a self,
+        kind: MediaKind,
+        title: &'a str,
+        year: Option<u16>,
+    ) -> ScrapeFuture<'a, Vec<SearchResult>> {
+        Box::pin(TmdbScraper::search(self, kind, title, year))
+    }
+
+    fn movie_metadata<'a>(
+        &'a self,
+        storage: &'a Path,
+        id: ProviderId,
+    ) -> ScrapeFuture<'a, MovieMetadata> {
+        Box::pin(
+            async move { TmdbScraper::movie_metadata(self, storage, id.at(Provider::Tmdb)?).await },
+        )
+    }
+
+    fn series_metadata<'a>(
+        &'a self,
+        storage: &'a Path,
+        id: ProviderId,
+    ) -> ScrapeFuture<'a, SeriesMetadata> {
+        Box::pin(async move {
+            TmdbScraper::series_metadata(self, storage, id.at(Provider::Tmdb)?).await
+        })
+    }
+
+    fn take_failures(&self) -> Vec<ScrapeFailure> {
+        self.failures.take()
+    }
+}
+
+#[derive(Debug, Clone)]
+struct SeasonScrapeResult {
+    metadata: SeasonMetadata,
+    episodes: Vec<(MediaId, EpisodeMetadata)>,
+    unmatched: Vec<EpisodeMetadata>,
+}
+
+#[derive(Debug, Clone)]
+struct SeriesScrapeResult {
+    metadata: SeriesMetadata,
+    seasons: Vec<SeasonScrapeResult>,
+}
+
+/// Why something couldn't be scraped, as far as it makes a difference to the user.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum FailureKind {
+    /// The provider turned down its API key.
+    Auth(Provider),
+    Network,
+    RateLimited,
+    /// Nothing matched the file name.
+    NoResults,
+    /// The metadata was scraped, but some of its artwork couldn't be downloaded.
+    Artwork,
+    Other,
+}
+
+impl FailureKind {
+    pub fn of(err: &anyhow::Error) -> Self {
+        for cause in err.chain() {
+            if let Some(Unauthorized(provider)) = cause.downcast_ref::<Unauthorized>() {
+                return FailureKind::Auth(*provider);
+            }
+            match cause.downcast_ref::<reqwest::Error>() {
+                Some(err) if err.status() == Some(reqwest::StatusCode::TOO_MANY_REQUESTS) => {
+                    return FailureKind::RateLimited;
+                }
+                Some(err) if err.is_connect() || err.is_timeout() => return FailureKind::Network,
+                _ => {}
+            }
+        }
+        FailureKind::Other
+    }
+
+    /// Whether trying again could turn out differently.
+    pub fn is_retryable(self) -> bool {
+        self != FailureKind::NoResults
+    }
+}
+
+impl std::fmt::Display for FailureKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            FailureKind::Auth(provider) => write!(f, "{} rejected the API key", provider),
+            FailureKind::Network => f.write_str("Couldn't reach the provider"),
+            FailureKind::RateLimited => f.write_str("Rate limited by the provider"),
+            FailureKind::NoResults => f.write_str("No results"),
+            FailureKind::Artwork => f.write_str("Artwork failed to download"),
+            FailureKind::Other => f.write_str("Failed"),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ScrapeFailure {
+    pub kind: FailureKind,
+    /// What was being scraped, such as a file name or a series.
+    pub title: String,
+    pub message: String,
+    /// The media to scrape again when retrying.
+    pub ids: Vec<MediaId>,
+}
+
+impl ScrapeFailure {
+    pub fn new(title: &str, err: &anyhow::Error, ids: Vec<MediaId>) -> Self {
+        ScrapeFailure {
+            kind: FailureKind::of(err),
+            title: title.to_string(),
+            message: format!("{:#}", err),
+            ids,
+        }
+    }
+
+    fn no_results(title: &str) -> Self {
+        ScrapeFailure {
+            kind: FailureKind::NoResults,
+            title: title.to_string(),
+            message: String::from("no provider had a match"),
+            ids: vec![],
+        }
+    }
+}
+
+/// Failures that didn't stop anything from being scraped, such as artwork that couldn't be
+/// downloaded or a provider that was passed over for the next one. They happen deep inside a
+/// scraper, so it collects them until the scan or refresh it was made for is done. Clones share
+/// the same list.
+#[derive(Debug, Clone, Default)]
+pub struct Failures(Arc<Mutex<Vec<ScrapeFailure>>>);
+
+impl Failures {
+    pub fn report(&self, failure: ScrapeFailure) {
+        self.0.lock().unwrap().push(failure);
+    }
+
+    pub fn take(&self) -> Vec<ScrapeFailure> {
+        std::mem::take(&mut self.0.lock().unwrap())
+    }
+}
+
+/// What went wrong during a scrape, for showing to the user.
+#[derive(Debug, Clone, Default)]
+pub struct ScrapeReport {
+    pub failures: Vec<ScrapeFailure>,
+    /// The media to scrape again when retrying.
+    pub retry: Vec<MediaId>,
+}
+
+impl ScrapeReport {
+    pub fn is_empty(&self) -> bool {
+        self.failures.is_empty()
+    }
+
+    /// The providers that turned down their API keys.
+    pub fn rejected_keys(&self) -> FxHashSet<Provider> {
+        self.failures
+            .iter()
+            .filter_map(|failure| match failure.kind {
+                FailureKind::Auth(provider) => Some(provider),
+                _ => None,
+            })
+            .collect()
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct ScrapeResult {
+    movies: Vec<(MediaId, MovieMetadata)>,
+    series: Vec<SeriesScrapeResult>,
+    review: Vec<MediaId>,
+    /// Media that couldn't be scraped because of an error rather than a lack of matches.
+    failed: Vec<MediaId>,
+    failures: Vec<ScrapeFailure>,
 }
 
 impl ScrapeResult {
+    /// Combines results scraped separately, e.g. in different languages.
+    pub fn extend(&mut self, other: ScrapeResult) {
+        self.movies.extend(other.movies);
+        self.series.extend(other.series);
+        self.review.extend(other.review);
+        self.failed.extend(other.failed);
+        self.failures.extend(other.failures);
+    }
+
+    fn fail(&mut self, failure: ScrapeFailure) {
+        self.failed.extend(&failure.ids);
+        self.failures.push(failure);
+    }
+
+    /// Takes in the failures `scraper` reported while scraping.
+    fn take_reported(&mut self, scraper: &impl Scraper) {
+        self.failures.extend(scraper.take_failures());
+    }
+
+    pub fn report(&self) -> ScrapeReport {
+        let mut retry: Vec<MediaId> = self
+            .failures
+            .iter()
+            .filter(|failure| failure.kind.is_retryable())
+            .flat_map(|failure| failure.ids.iter().copied())
+            .collect();
+        // artwork isn't tied to the media it was for, so everything scraped is refreshed; only
+        // what's missing is downloaded again
+        if self
+            .failures
+            .iter()
+            .any(|failure| failure.kind == FailureKind::Artwork)
+        {
+            retry.extend(self.movies.iter().map(|(id, _)| *id));
+            retry.extend(
+                self.series
+                    .iter()
+                    .flat_map(|series| &series.seasons)
+                    .flat_map(|season| &season.episodes)
+                    .map(|(id, _)| *id),
+            );
+        }
+
+        ScrapeReport {
+            failures: self.failures.clone(),
+            retry: retry.into_iter().unique().collect(),
+        }
+    }
+
     pub fn insert(self, library: &mut Library) {
-        let Self { movies, series } = self;
+        let Self {
+            movies,
+            series,
+            review,
+            failed,
+            failures: _,
+        } = self;
+
+        for id in review {
+            if let Some(Media::Uncategorised(uncategorised)) = library.get_mut(id) {
+                uncategorised.needs_review = true;
+            }
+        }
+
+        for id in failed {
+            if let Some(Media::Uncategorised(uncategorised)) = library.get_mut(id) {
+                // picked up again by the next scan
+                uncategorised.dont_scrape = false;
+                uncategorised.scrape_failed = true;
+            }
+        }
 
         for (id, metadata) in movies {
             let Some(media) = library.get_mut(id) else {
                 continue;
             };
 
-            let Media::Uncategorised(uncategorised) = media else {
+            if let Media::Movie(movie) = media {
+                movie.metadata.merge(metadata);
+                continue;
+            }
+
+            // anything with a video can be (re-)identified, not only uncategorised media
+            let Some(video) = media.video().cloned() else {
                 continue;
             };
 
-            *media = Media::Movie(Movie {
-                video: uncategorised.video.clone(),
-                metadata,
-            });
+            *media = Media::Movie(Movie { video, metadata });
         }
 
         for series in series {
             let series_id = library.iter().find_map(|(id, media)| match media {
-                Media::Series(other) if other.metadata.tmdb_id == series.metadata.tmdb_id => {
+                Media::Series(other) if other.metadata.source() == series.metadata.source() => {
                     Some(*id)
                 }
                 _ => None,
             });
-            let series_id = series_id.unwrap_or_else(|| {
-                library.insert(Media::Series(Series {
+            let series_id = match series_id {
+                Some(id) => {
+                    if let Some(Media::Series(existing)) = library.get_mut(id) {
+                        existing.metadata.merge(series.metadata);
+                    }
+                    id
+                }
+                None => library.insert(Media::Series(Series {
                     metadata: series.metadata,
-                }))
-            });
+                    episode_order: EpisodeOrder::default(),
+                })),
+            };
 
             for season in series.seasons {
                 let season_id = library.iter().find_map(|(id, media)| match media {
@@ -292,24 +1853,42 @@ impl ScrapeResult {
                     }
                     _ => None,
                 });
-                let season_id = season_id.unwrap_or_else(|| {
-                    library.insert(Media::Season(Season {
+                let season_id = match season_id {
+                    Some(id) => {
+                        if let Some(Media::Season(existing)) = library.get_mut(id) {
+                            existing.metadata.merge(season.metadata);
+                        }
+                        id
+                    }
+                    None => library.insert(Media::Season(Season {
                         metadata: season.metadata,
                         series: series_id,
-                    }))
-                });
+                    })),
+                };
 
                 for (id, metadata) in season.episodes {
                     let Some(media) = library.get_mut(id) else {
                         continue;
                     };
 
-                    let Media::Uncategorised(uncategorised) = media else {
+                    match media {
+                        Media::Episode(episode) if episode.series == series_id => {
+                            // a locked season number keeps the episode where the user put it
+                            if !episode.metadata.locked.contains(&Field::Season) {
+                                episode.season = season_id;
+                            }
+                            episode.metadata.merge(metadata);
+                            continue;
+                        }
+                        _ => {}
+                    }
+
+                    let Some(video) = media.video().cloned() else {
                         continue;
                     };
 
                     *media = Media::Episode(Episode {
-                        video: uncategorised.video.clone(),
+                        video,
                         series: series_id,
                         season: season_id,
                         metadata,
@@ -317,99 +1896,658 @@ impl ScrapeResult {
                 }
             }
         }
+
+        // re-identified episodes may have left their previous season or series behind
+        library.remove_empty();
     }
 }
 
-async fn find_or_insert<T, F>(
-    v: &mut Vec<T>,
-    pred: impl FnMut(&T) -> bool,
-    insert: impl FnOnce() -> F,
-) -> Option<&mut T>
-where
-    F: Future<Output = Option<T>>,
-{
-    if let Some(i) = v.iter().position(pred) {
-        Some(&mut v[i])
-    } else {
-        if let Some(insert) = insert().await {
-            v.push(insert);
-            v.last_mut()
-        } else {
-            None
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Identification {
+    Movie {
+        id: ProviderId,
+    },
+    Episode {
+        series: ProviderId,
+        season: u16,
+        episode: u16,
+    },
+}
+
+/// Resolves a manual identification of a single video into a [`ScrapeResult`], so that it is
+/// applied the same way as an automatic scrape.
+///
+/// Episodes are numbered by `order`, which should be the order of the series if it is already in
+/// the library.
+pub async fn identify(
+    scraper: &impl Scraper,
+    storage: &Path,
+    id: MediaId,
+    identification: Identification,
+    order: &EpisodeOrder,
+) -> anyhow::Result<ScrapeResult> {
+    let mut result = ScrapeResult::default();
+
+    match identification {
+        Identification::Movie { id: movie } => {
+            let metadata = scraper.movie_metadata(storage, movie).await?;
+            result.movies.push((id, metadata));
+        }
+        Identification::Episode {
+            series: source,
+            season,
+            episode,
+        } => {
+            let series = scraper.series_metadata(storage, source).await?;
+            let (season, mut episodes) = scraper
+                .scrape_season_metadata(storage, source, season, order)
+                .await?
+                .ok_or_else(|| anyhow::anyhow!("season {} not found", season))?;
+            let i = episodes
+                .iter()
+                .position(|e| e.episode == episode)
+                .ok_or_else(|| anyhow::anyhow!("episode {} not found", episode))?;
+            let metadata = episodes.remove(i);
+
+            result.series.push(SeriesScrapeResult {
+                metadata: series,
+                seasons: vec![SeasonScrapeResult {
+                    metadata: season,
+                    episodes: vec![(id, metadata)],
+                    unmatched: episodes,
+                }],
+            });
+        }
+    }
+
+    result.take_reported(scraper);
+    Ok(result)
+}
+
+/// How many titles are scraped at once. Each provider's rate limiter keeps the requests this
+/// produces within its limits.
+const CONCURRENCY: usize = 8;
+
+/// Local episodes as `(id, episode number)`, by season number.
+type SeasonEpisodes = FxHashMap<u16, Vec<(MediaId, u16)>>;
+/// Episodes numbered from the start of their series, yet to be placed in seasons.
+type AbsoluteEpisodes = Vec<(MediaId, u16)>;
+
+/// Pairs local episodes with the season's scraped episodes; scraped ones left over stay
+/// unmatched. Local episodes the season doesn't list are returned as failures.
+fn match_episodes(
+    series: &str,
+    metadata: SeasonMetadata,
+    mut scraped: Vec<EpisodeMetadata>,
+    episodes: Vec<(MediaId, u16)>,
+) -> (SeasonScrapeResult, Vec<ScrapeFailure>) {
+    let mut failures = vec![];
+    let episodes = episodes
+        .into_iter()
+        .filter_map(|(id, episode)| {
+            let Some(i) = scraped.iter().position(|e| e.episode == episode) else {
+                failures.push(ScrapeFailure {
+                    kind: FailureKind::NoResults,
+                    title: format!("{} S{:02}E{:02}", series, metadata.season, episode),
+                    message: String::from("the season doesn't list this episode"),
+                    ids: vec![id],
+                });
+                return None;
+            };
+            Some((id, scraped.remove(i)))
+        })
+        .collect();
+
+    let season = SeasonScrapeResult {
+        metadata,
+        episodes,
+        unmatched: scraped,
+    };
+    (season, failures)
+}
+
+/// Seasons already in the library along with their full episode lists, by series and season
+/// number, and the order each series is numbered by. Scans reuse these instead of fetching them
+/// again, unless a season lacks one of the episodes being scraped; refreshing updates them.
+#[derive(Debug, Clone, Default)]
+pub struct KnownSeasons {
+    seasons: FxHashMap<(ProviderId, u16), SeasonMetadata>,
+    orders: FxHashMap<ProviderId, EpisodeOrder>,
+}
+
+impl KnownSeasons {
+    pub fn new(library: &Library) -> Self {
+        let mut known = KnownSeasons::default();
+        for (_, media) in library.iter() {
+            match media {
+                // libraries from before episode lists were kept have to fetch them once
+                Media::Season(season)
+                    if season.metadata.series_provider_id != 0
+                        && !season.metadata.episodes.is_empty() =>
+                {
+                    known.seasons.insert(
+                        (season.metadata.series_source(), season.metadata.season),
+                        season.metadata.clone(),
+                    );
+                }
+                Media::Series(series) if series.episode_order != EpisodeOrder::Aired => {
+                    known
+                        .orders
+                        .insert(series.metadata.source(), series.episode_order.clone());
+                }
+                _ => {}
+            }
+        }
+        known
+    }
+
+    fn order(&self, series: ProviderId) -> &EpisodeOrder {
+        self.orders.get(&series).unwrap_or(&EpisodeOrder::Aired)
+    }
+}
+
+/// Places an episode numbered from the start of the series into its season, given each season's
+/// number and episode count. Specials don't count towards the numbering, and numbers start at 1,
+/// so episode 0 isn't placed.
+pub fn absolute_episode(counts: &[(u16, u16)], absolute: u16) -> Option<(u16, u16)> {
+    if absolute == 0 {
+        return None;
+    }
+
+    let mut first = 1;
+    for &(season, count) in counts
+        .iter()
+        .filter(|(season, _)| *season > 0)
+        .sorted_by_key(|(season, _)| *season)
+    {
+        if absolute - first < count {
+            return Some((season, absolute - first + 1));
+        }
+        first += count;
+    }
+    None
+}
+
+/// Scrapes the seasons of an already identified series, reusing known seasons where possible.
+async fn scrape_seasons(
+    scraper: &impl Scraper,
+    storage: &Path,
+    known: &KnownSeasons,
+    metadata: SeriesMetadata,
+    mut seasons: SeasonEpisodes,
+    absolute: AbsoluteEpisodes,
+) -> ScrapeResult {
+    let mut result = ScrapeResult::default();
+
+    let source = metadata.source();
+    if !absolute.is_empty() {
+        match scraper.episode_counts(source, known.order(source)).await {
+            Ok(counts) => {
+                for (id, number) in absolute {
+                    match absolute_episode(&counts, number) {
+                        Some((season, episode)) => {
+                            seasons.entry(season).or_default().push((id, episode));
+                        }
+                        None => log::warn!(
+                            "episode {} of {} isn't in any known season",
+                            number,
+                            metadata.title
+                        ),
+                    }
+                }
+            }
+            Err(err) => {
+                log::warn!(
+                    "failed to look up the seasons of {}: {:#}",
+                    metadata.title,
+                    err
+                );
+                let ids = absolute.into_iter().map(|(id, _)| id).collect();
+                result.fail(ScrapeFailure::new(&metadata.title, &err, ids));
+            }
+        }
+    }
+
+    let scraped: Vec<_> = futures::stream::iter(seasons)
+        .map(|(season, episodes)| async move {
+            // an episode the list lacks may have been added since it was fetched
+            let reused = known.seasons.get(&(source, season)).filter(|known| {
+                episodes
+                    .iter()
+                    .all(|(_, number)| known.episodes.iter().any(|e| e.episode == *number))
+            });
+            let scraped = match reused {
+                Some(reused) => Ok(Some((reused.clone(), reused.episodes.clone()))),
+                None => {
+                    scraper
+                        .scrape_season_metadata(storage, source, season, known.order(source))
+                        .await
+                }
+            };
+            (season, episodes, scraped)
+        })
+        .buffer_unordered(CONCURRENCY)
+        .collect()
+        .await;
+
+    let mut series = SeriesScrapeResult {
+        metadata,
+        seasons: vec![],
+    };
+    for (season, episodes, scraped) in scraped {
+        match scraped {
+            Ok(Some((metadata, scraped))) => {
+                let (season, failures) =
+                    match_episodes(&series.metadata.title, metadata, scraped, episodes);
+                series.seasons.push(season);
+                result.failures.extend(failures);
+            }
+            Ok(None) => result.failures.push(ScrapeFailure::no_results(&format!(
+                "{} season {}",
+                series.metadata.title, season
+            ))),
+            Err(err) => {
+                log::warn!(
+                    "failed to scrape season {} of {}: {:#}",
+                    season,
+                    series.metadata.title,
+                    err
+                );
+                let title = format!("{} season {}", series.metadata.title, season);
+                let ids = episodes.into_iter().map(|(id, _)| id).collect();
+                result.fail(ScrapeFailure::new(&title, &err, ids));
+            }
         }
     }
+    result.series.push(series);
+
+    result
 }
 
 pub async fn scrape_all(
     scraper: &impl Scraper,
     storage: &Path,
+    known: &KnownSeasons,
     media: impl Iterator<Item = (MediaId, String)>,
 ) -> ScrapeResult {
-    let mut result = ScrapeResult {
-        movies: vec![],
-        series: vec![],
-    };
+    let mut movies = vec![];
+    // episodes by normalised series title and year, so each show is only searched for once, along
+    // with those numbered absolutely, which are placed in seasons once the show is known
+    let mut queries: FxHashMap<(String, Option<u16>), (SeasonEpisodes, AbsoluteEpisodes)> =
+        FxHashMap::default();
 
     for (id, filename) in media {
-        let media_type = detect_media_type(&filename);
-        match media_type {
+        match detect_media_type(&filename) {
             MediaType::Unknown => {}
-            MediaType::Movie { title, year } => {
-                if let Ok(Some(metadata)) =
-                    scraper.scrape_movie_metadata(storage, &title, year).await
-                {
-                    result.movies.push((id, metadata));
-                }
-            }
+            MediaType::Movie { title, year } => movies.push((id, title, year)),
             MediaType::Episode {
                 series_title,
+                year,
                 season,
                 episode,
-            } => {
-                let Some(series) = find_or_insert(
-                    &mut result.series,
-                    |series| series.metadata.title == series_title,
-                    || async {
-                        Some(SeriesScrapeResult {
-                            metadata: scraper
-                                .scrape_series_metadata(storage, &series_title)
-                                .await
-                                .ok()??,
-                            seasons: vec![],
-                        })
-                    },
-                )
-                .await
-                else {
-                    continue;
-                };
+            } => queries
+                .entry((normalise_title(&series_title), year))
+                .or_default()
+                .0
+                .entry(season)
+                .or_default()
+                .push((id, episode)),
+            MediaType::AbsoluteEpisode {
+                series_title,
+                year,
+                episode,
+            } => queries
+                .entry((normalise_title(&series_title), year))
+                .or_default()
+                .1
+                .push((id, episode)),
+        }
+    }
 
-                if let Some(season) = find_or_insert(
-                    &mut series.seasons,
-                    |s| s.metadata.season == season,
-                    || async {
-                        let (metadata, episodes) = scraper
-                            .scrape_season_metadata(storage, series.metadata.tmdb_id, season)
-                            .await
-                            .ok()??;
-                        Some(SeasonScrapeResult {
-                            metadata,
-                            episodes: vec![],
-                            unmatched: episodes,
-                        })
-                    },
-                )
-                .await
-                {
-                    let Some(i) = season.unmatched.iter().position(|e| e.episode == episode) else {
-                        continue;
-                    };
-                    let metadata = season.unmatched.remove(i);
-                    season.episodes.push((id, metadata));
+    let mut result = futures::stream::iter(movies)
+        .map(|(id, title, year)| async move {
+            let matched = scraper.scrape_movie_metadata(storage, &title, year).await;
+            let mut result = ScrapeResult::default();
+            match matched {
+                Ok(Some(matched)) if matched.confidence >= MATCH_THRESHOLD => {
+                    result.movies.push((id, matched.metadata));
+                }
+                Ok(Some(_)) => result.review.push(id),
+                Ok(None) => result.failures.push(ScrapeFailure::no_results(&title)),
+                Err(err) => {
+                    log::warn!("failed to scrape movie {:?}: {:#}", title, err);
+                    result.fail(ScrapeFailure::new(&title, &err, vec![id]));
+                }
+            }
+            result
+        })
+        .buffer_unordered(CONCURRENCY)
+        .fold(ScrapeResult::default(), async |mut result, other| {
+            result.extend(other);
+            result
+        })
+        .await;
+
+    let matched: Vec<_> = futures::stream::iter(queries)
+        .map(|((title, year), (seasons, absolute))| async move {
+            let matched = scraper.scrape_series_metadata(storage, &title, year).await;
+            (title, seasons, absolute, matched)
+        })
+        .buffer_unordered(CONCURRENCY)
+        .collect()
+        .await;
+
+    // differently named files can still be the same show, which only needs its seasons once
+    let mut series: FxHashMap<ProviderId, (SeriesMetadata, SeasonEpisodes, AbsoluteEpisodes)> =
+        FxHashMap::default();
+    for (title, seasons, absolute, matched) in matched {
+        let ids = || {
+            seasons
+                .values()
+                .flatten()
+                .chain(&absolute)
+                .map(|(id, _)| *id)
+        };
+        match matched {
+            Ok(Some(matched)) if matched.confidence >= MATCH_THRESHOLD => {
+                let (_, merged, merged_absolute) = series
+                    .entry(matched.metadata.source())
+                    .or_insert_with(|| (matched.metadata, SeasonEpisodes::default(), vec![]));
+                for (season, episodes) in seasons {
+                    merged.entry(season).or_default().extend(episodes);
+                }
+                merged_absolute.extend(absolute);
+            }
+            Ok(Some(_)) => result.review.extend(ids()),
+            Ok(None) => result.failures.push(ScrapeFailure::no_results(&title)),
+            Err(err) => {
+                log::warn!("failed to scrape tv show {:?}: {:#}", title, err);
+                result.fail(ScrapeFailure::new(&title, &err, ids().collect()));
+            }
+        }
+    }
+
+    let mut result = futures::stream::iter(series.into_values())
+        .map(|(metadata, seasons, absolute)| {
+            scrape_seasons(scraper, storage, known, metadata, seasons, absolute)
+        })
+        .buffer_unordered(CONCURRENCY)
+        .fold(result, async |mut result, other| {
+            result.extend(other);
+            result
+        })
+        .await;
+    result.take_reported(scraper);
+    result
+}
+
+/// What to re-query when refreshing metadata, gathered up front so the scraping itself doesn't
+/// need access to the library.
+#[derive(Debug, Clone, Default)]
+pub struct RefreshPlan {
+    movies: Vec<(MediaId, ProviderId)>,
+    /// Episodes to refresh, by series and season number.
+    series: FxHashMap<ProviderId, SeasonEpisodes>,
+    /// Each series' order, where it isn't the aired order.
+    orders: FxHashMap<ProviderId, EpisodeOrder>,
+}
+
+impl RefreshPlan {
+    /// Plans a refresh of `ids`; series and seasons refresh all of their episodes.
+    pub fn new(library: &Library, ids: impl IntoIterator<Item = MediaId>) -> Self {
+        let mut plan = RefreshPlan::default();
+        // an episode may be asked for along with its season or series
+        let mut planned = FxHashSet::default();
+        let mut add_episode = |id: MediaId, episode: &Episode| {
+            if episode.metadata.series_provider_id == 0 || !planned.insert(id) {
+                return;
+            }
+            match library.get(episode.series) {
+                Some(Media::Series(series)) if series.episode_order != EpisodeOrder::Aired => {
+                    plan.orders.insert(
+                        episode.metadata.series_source(),
+                        series.episode_order.clone(),
+                    );
+                }
+                _ => {}
+            }
+            plan.series
+                .entry(episode.metadata.series_source())
+                .or_default()
+                .entry(episode.metadata.season)
+                .or_default()
+                .push((id, episode.metadata.episode));
+        };
+
+        let mut movies = vec![];
+        for id in ids {
+            match library.get(id) {
+                Some(Media::Movie(movie)) if movie.metadata.provider_id != 0 => {
+                    movies.push((id, movie.metadata.source()));
+                }
+                Some(Media::Series(_)) => {
+                    for (id, episode) in find_all_episodes(id, library) {
+                        add_episode(*id, episode);
+                    }
                 }
+                Some(Media::Season(_)) => {
+                    for (id, episode) in find_episodes(id, library) {
+                        add_episode(*id, episode);
+                    }
+                }
+                Some(Media::Episode(episode)) => add_episode(id, episode),
+                _ => {}
+            }
+        }
+
+        plan.movies = movies;
+        plan
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.movies.is_empty() && self.series.is_empty()
+    }
+}
+
+/// Re-queries metadata by stored provider id. The result merges into existing media when inserted,
+/// leaving videos and locked fields alone.
+pub async fn refresh(scraper: &impl Scraper, storage: &Path, plan: RefreshPlan) -> ScrapeResult {
+    let mut result = ScrapeResult::default();
+
+    for (id, movie) in plan.movies {
+        match scraper.movie_metadata(storage, movie).await {
+            Ok(metadata) => result.movies.push((id, metadata)),
+            Err(err) => {
+                log::error!("failed to refresh movie {}: {:#}", movie, err);
+                result.fail(ScrapeFailure::new(&movie.to_string(), &err, vec![id]));
+            }
+        }
+    }
+
+    for (source, seasons) in plan.series {
+        let order = plan.orders.get(&source).unwrap_or(&EpisodeOrder::Aired);
+        let metadata = match scraper.series_metadata(storage, source).await {
+            Ok(metadata) => metadata,
+            Err(err) => {
+                log::error!("failed to refresh series {}: {:#}", source, err);
+                let ids = seasons.values().flatten().map(|(id, _)| *id).collect();
+                result.fail(ScrapeFailure::new(&source.to_string(), &err, ids));
+                continue;
             }
+        };
+
+        let mut series = SeriesScrapeResult {
+            metadata,
+            seasons: vec![],
+        };
+        for (season, episodes) in seasons {
+            let (metadata, scraped) = match scraper
+                .scrape_season_metadata(storage, source, season, order)
+                .await
+            {
+                Ok(Some(season)) => season,
+                Ok(None) => continue,
+                Err(err) => {
+                    log::error!(
+                        "failed to refresh season {} of {}: {:#}",
+                        season,
+                        source,
+                        err
+                    );
+                    let title = format!("{} season {}", series.metadata.title, season);
+                    let ids = episodes.into_iter().map(|(id, _)| id).collect();
+                    result.fail(ScrapeFailure::new(&title, &err, ids));
+                    continue;
+                }
+            };
+
+            let (season, failures) =
+                match_episodes(&series.metadata.title, metadata, scraped, episodes);
+            series.seasons.push(season);
+            result.failures.extend(failures);
         }
+        result.series.push(series);
     }
 
+    result.take_reported(scraper);
     result
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn movie(title: &str, year: u16) -> MediaType {
+        MediaType::Movie {
+            title: title.into(),
+            year,
+        }
+    }
+
+    fn absolute(series_title: &str, year: Option<u16>, episode: u16) -> MediaType {
+        MediaType::AbsoluteEpisode {
+            series_title: series_title.into(),
+            year,
+            episode,
+        }
+    }
+
+    #[test]
+    fn detects_movies() {
+        for (filename, expected) in [
+            ("The Matrix (1999).mkv", movie("the matrix", 1999)),
+            ("Alien - 3 (1992).mkv", movie("alien 3", 1992)),
+            ("Apollo - 13 (1995).mkv", movie("apollo 13", 1995)),
+            ("Blade Runner - 2049.mkv", movie("blade runner", 2049)),
+        ] {
+            assert_eq!(detect_media_type(filename), expected, "{}", filename);
+        }
+    }
+
+    #[test]
+    fn detects_episodes() {
+        for (filename, expected) in [
+            (
+                "The Office S01E02.mkv",
+                MediaType::Episode {
+                    series_title: "the office".into(),
+                    year: None,
+                    season: 1,
+                    episode: 2,
+                },
+            ),
+            (
+                "The Office 2005 S01E02.mkv",
+                MediaType::Episode {
+                    series_title: "the office".into(),
+                    year: Some(2005),
+                    season: 1,
+                    episode: 2,
+                },
+            ),
+            (
+                "[SubsPlease] Frieren - 05 (1080p) [ABCD1234].mkv",
+                absolute("frieren", None, 5),
+            ),
+            ("Show (2019) - 137v2.mkv", absolute("show", Some(2019), 137)),
+            ("Show - 00.mkv", absolute("show", None, 0)),
+            ("Home Video.mkv", MediaType::Unknown),
+        ] {
+            assert_eq!(detect_media_type(filename), expected, "{}", filename);
+        }
+    }
+
+    #[test]
+    fn places_absolute_episodes() {
+        let counts = [(0, 3), (2, 10), (1, 12)];
+        assert_eq!(absolute_episode(&counts, 0), None);
+        assert_eq!(absolute_episode(&counts, 1), Some((1, 1)));
+        assert_eq!(absolute_episode(&counts, 12), Some((1, 12)));
+        assert_eq!(absolute_episode(&counts, 13), Some((2, 1)));
+        assert_eq!(absolute_episode(&counts, 22), Some((2, 10)));
+        assert_eq!(absolute_episode(&counts, 23), None);
+        assert_eq!(absolute_episode(&[], 1), None);
+    }
+
+    fn candidate(value: u64, titles: &[&str], year: u16, popularity: f64) -> Candidate<u64> {
+        Candidate {
+            value,
+            titles: titles.iter().map(|title| title.to_string()).collect(),
+            year: Some(year),
+            popularity,
+            vote_count: popularity as u64 * 10,
+        }
+    }
+
+    #[test]
+    fn ranks_year_over_popularity() {
+        let ranked = rank_candidates(
+            "The Office",
+            Some(2005),
+            vec![
+                candidate(2996, &["The Office"], 2001, 80.0),
+                candidate(2316, &["The Office"], 2005, 50.0),
+                candidate(1, &["The Office Olympics"], 2005, 5.0),
+            ],
+        );
+        let order: Vec<_> = ranked
+            .iter()
+            .map(|(_, candidate)| candidate.value)
+            .collect();
+        assert_eq!(order, [2316, 2996, 1]);
+        assert!(ranked[0].0 >= MATCH_THRESHOLD);
+    }
+
+    #[test]
+    fn ranks_by_any_title() {
+        let ranked = rank_candidates(
+            "Spirited Away",
+            Some(2001),
+            vec![candidate(
+                129,
+                &["Sen to Chihiro no Kamikakushi", "Spirited Away"],
+                2001,
+                10.0,
+            )],
+        );
+        assert!(ranked[0].0 >= MATCH_THRESHOLD);
+
+        let ranked = rank_candidates(
+            "Law & Order",
+            None,
+            vec![candidate(549, &["Law and Order"], 1990, 10.0)],
+        );
+        assert!(ranked[0].0 >= MATCH_THRESHOLD);
+    }
+
+    #[test]
+    fn ranks_unrelated_titles_below_threshold() {
+        let ranked = rank_candidates(
+            "Home Video",
+            Some(2010),
+            vec![candidate(1, &["Something Else Entirely"], 2010, 100.0)],
+        );
+        assert!(ranked[0].0 < MATCH_THRESHOLD);
+        assert!(rank_candidates::<u64>("Home Video", None, vec![]).is_empty());
+    }
+}
@@ -0,0 +1,657 @@
+use super::{
+    Credit, Details, EpisodeMetadata, EpisodeOrder, Match, MediaKind, MediaType, MovieMetadata,
+    Provider, ProviderId, ScrapeFailure, ScrapeFuture, Scraper, SearchResult, SeasonMetadata,
+    SeriesMetadata, detect_media_type, is_video, normalise_title, parse_imdb_id,
+};
+use async_std::stream::StreamExt;
+use chrono::{Datelike, NaiveDate};
+use rustc_hash::FxHashMap;
+use std::path::{Path, PathBuf};
+
+const IMAGE_EXTENSIONS: &[&str] = &["jpg", "jpeg", "png"];
+
+/// How many directories above a video to look for `tvshow.nfo`, for `Show/Season 1/episode.mkv`.
+const SHOW_DEPTH: usize = 3;
+
+/// Kodi-style `.nfo` files and artwork found next to videos. They are only ever read.
+///
+/// Media is keyed the same way [`scrape_all`](super::scrape_all) parses file names, so lookups
+/// by parsed title find the sidecars of the very files they were parsed from. Shows need a
+/// provider id in their `tvshow.nfo`, as that is what their seasons and episodes are grouped by.
+/// Movies without one are recorded as [`Provider::Local`], so they're never looked up by id.
+#[derive(Debug, Clone, Default)]
+pub struct Sidecars {
+    movies: FxHashMap<(String, u16), MovieMetadata>,
+    series: FxHashMap<(String, Option<u16>), SeriesMetadata>,
+    seasons: FxHashMap<(ProviderId, u16), SidecarSeason>,
+    /// From `<namedseason>`, by series id and season number.
+    season_names: FxHashMap<(ProviderId, u16), String>,
+    /// The show each directory of episodes belongs to, and the show's own directory.
+    shows: FxHashMap<PathBuf, Option<(PathBuf, SeriesMetadata)>>,
+}
+
+#[derive(Debug, Clone)]
+struct SidecarSeason {
+    metadata: SeasonMetadata,
+    episodes: Vec<EpisodeMetadata>,
+    /// Whether any episode had its own `.nfo`, rather than only a title made up from its number.
+    described: bool,
+}
+
+impl Sidecars {
+    pub async fn load(videos: impl IntoIterator<Item = PathBuf>) -> Self {
+        let mut sidecars = Sidecars::default();
+        for video in videos {
+            if let Err(err) = sidecars.load_video(&video).await {
+                log::warn!("failed to read sidecars of {:?}: {:#}", video, err);
+            }
+        }
+        sidecars
+    }
+
+    async fn load_video(&mut self, video: &Path) -> anyhow::Result<()> {
+        let (Some(dir), Some(stem), Some(filename)) = (
+            video.parent(),
+            video.file_stem().and_then(|stem| stem.to_str()),
+            video.file_name().and_then(|name| name.to_str()),
+        ) else {
+            return Ok(());
+        };
+
+        match detect_media_type(filename) {
+            // sidecars are found by season, which these don't have
+            MediaType::Unknown | MediaType::AbsoluteEpisode { .. } => {}
+            MediaType::Movie { title, year } => {
+                let key = (normalise_title(&title), year);
+                if self.movies.contains_key(&key) {
+                    return Ok(());
+                }
+                let mut nfos = vec![dir.join(format!("{}.nfo", stem))];
+                // `movie.nfo` only describes a movie that has its directory to itself
+                if videos_in(dir).await? == 1 {
+                    nfos.push(dir.join("movie.nfo"));
+                }
+                for nfo in nfos {
+                    if let Some(xml) = read_nfo(&nfo).await? {
+                        self.movies.insert(key, parse_movie(&xml, dir, stem));
+                        break;
+                    }
+                }
+            }
+            MediaType::Episode {
+                series_title,
+                year,
+                season,
+                episode,
+            } => {
+                let key = (normalise_title(&series_title), year);
+                let Some((show_dir, series)) = self.find_series(dir, &key).await? else {
+                    return Ok(());
+                };
+
+                let nfo = read_nfo(&dir.join(format!("{}.nfo", stem))).await?;
+                let title = self
+                    .season_names
+                    .get(&(series.source(), season))
+                    .cloned()
+                    .unwrap_or_else(|| season_title(season));
+                let entry = self
+                    .seasons
+                    .entry((series.source(), season))
+                    .or_insert_with(|| SidecarSeason {
+                        metadata: SeasonMetadata {
+                            provider: series.provider,
+                            series_provider_id: series.provider_id,
+                            title,
+                            season,
+                            poster: season_poster(&show_dir, dir, season),
+                            aired: None,
+                            overview: None,
+                            episodes: vec![],
+                            locked: Default::default(),
+                        },
+                        episodes: vec![],
+                        described: false,
+                    });
+                if entry.episodes.iter().any(|other| other.episode == episode) {
+                    return Ok(());
+                }
+
+                entry.described |= nfo.is_some();
+                entry.episodes.push(parse_episode(
+                    nfo.as_deref().unwrap_or_default(),
+                    series.source(),
+                    season,
+                    episode,
+                    dir,
+                    stem,
+                ));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// The show an episode in `dir` belongs to, along with the show's directory. `key` is what
+    /// the episode's file name was parsed as.
+    async fn find_series(
+        &mut self,
+        dir: &Path,
+        key: &(String, Option<u16>),
+    ) -> anyhow::Result<Option<(PathBuf, SeriesMetadata)>> {
+        let show = match self.shows.get(dir) {
+            Some(show) => show.clone(),
+            None => self.read_show(dir).await?,
+        };
+        if let Some((_, series)) = &show {
+            self.series.insert(key.clone(), series.clone());
+        }
+        Ok(show)
+    }
+
+    async fn read_show(&mut self, dir: &Path) -> anyhow::Result<Option<(PathBuf, SeriesMetadata)>> {
+        let mut show = None;
+        for show_dir in dir.ancestors().take(SHOW_DEPTH) {
+            let Some(xml) = read_nfo(&show_dir.join("tvshow.nfo")).await? else {
+                continue;
+            };
+            let Some(series) = parse_series(&xml, show_dir) else {
+                break;
+            };
+
+            for named in elements(&xml, "namedseason") {
+                let number = attribute(named.attributes, "number").and_then(|n| n.parse().ok());
+                if let (Some(number), Some(name)) = (number, decode(named.content)) {
+                    self.season_names.insert((series.source(), number), name);
+                }
+            }
+            show = Some((show_dir.to_path_buf(), series));
+            break;
+        }
+
+        self.shows.insert(dir.to_path_buf(), show.clone());
+        Ok(show)
+    }
+
+    fn movie(&self, title: &str, year: u16) -> Option<&MovieMetadata> {
+        self.movies.get(&(normalise_title(title), year))
+    }
+
+    fn series(&self, title: &str, year: Option<u16>) -> Option<&SeriesMetadata> {
+        self.series.get(&(normalise_title(title), year))
+    }
+
+    fn movie_by_id(&self, id: ProviderId) -> Option<&MovieMetadata> {
+        self.movies
+            .values()
+            .find(|movie| movie.provider != Provider::Local && movie.source() == id)
+    }
+
+    fn series_by_id(&self, id: ProviderId) -> Option<&SeriesMetadata> {
+        self.series.values().find(|series| series.source() == id)
+    }
+
+    /// The season and its episodes, and whether any episode was described by a `.nfo`.
+    fn season(
+        &self,
+        series: ProviderId,
+        season: u16,
+    ) -> Option<(SeasonMetadata, Vec<EpisodeMetadata>, bool)> {
+        let season = self.seasons.get(&(series, season))?;
+        let mut episodes = season.episodes.clone();
+        episodes.sort_unstable_by_key(|episode| episode.episode);
+
+        let mut metadata = season.metadata.clone();
+        metadata.aired = episodes.iter().filter_map(|episode| episode.aired).min();
+        metadata.episodes = episodes.clone();
+        Some((metadata, episodes, season.described))
+    }
+}
+
+/// Answers from sidecars first, and only asks `fallback` about media without any. Without a
+/// fallback it works entirely offline.
+#[derive(Clone)]
+pub struct SidecarScraper<S> {
+    sidecars: Sidecars,
+    fallback: Option<S>,
+}
+
+impl<S: Scraper> SidecarScraper<S> {
+    pub fn new(sidecars: Sidecars, fallback: Option<S>) -> Self {
+        SidecarScraper { sidecars, fallback }
+    }
+}
+
+impl<S: Scraper> SidecarScraper<S> {
+    async fn scrape_movie(
+        &self,
+        storage: &Path,
+        title: &str,
+        year: u16,
+    ) -> anyhow::Result<Option<Match<MovieMetadata>>> {
+        if let Some(metadata) = self.sidecars.movie(title, year) {
+            return Ok(Some(Match {
+                metadata: metadata.clone(),
+                confidence: 1.0,
+            }));
+        }
+        match &self.fallback {
+            Some(fallback) => fallback.scrape_movie_metadata(storage, title, year).await,
+            None => Ok(None),
+        }
+    }
+
+    async fn scrape_series(
+        &self,
+        storage: &Path,
+        title: &str,
+        year: Option<u16>,
+    ) -> anyhow::Result<Option<Match<SeriesMetadata>>> {
+        if let Some(metadata) = self.sidecars.series(title, year) {
+            return Ok(Some(Match {
+                metadata: metadata.clone(),
+                confidence: 1.0,
+            }));
+        }
+        match &self.fallback {
+            Some(fallback) => fallback.scrape_series_metadata(storage, title, year).await,
+            None => Ok(None),
+        }
+    }
+
+    async fn scrape_season(
+        &self,
+        storage: &Path,
+        series: ProviderId,
+        season: u16,
+        order: &EpisodeOrder,
+    ) -> anyhow::Result<Option<(SeasonMetadata, Vec<EpisodeMetadata>)>> {
+        let sidecars = self.sidecars.season(series, season);
+        // episodes without their own .nfo are better off with the fallback's titles
+        match (sidecars, &self.fallback) {
+            (Some((metadata, episodes, true)), _) | (Some((metadata, episodes, _)), None) => {
+                Ok(Some((metadata, episodes)))
+            }
+            (_, Some(fallback)) => {
+                fallback
+                    .scrape_season_metadata(storage, series, season, order)
+                    .await
+            }
+            (None, None) => Ok(None),
+        }
+    }
+
+    async fn movie(&self, storage: &Path, id: ProviderId) -> anyhow::Result<MovieMetadata> {
+        if let Some(metadata) = self.sidecars.movie_by_id(id) {
+            return Ok(metadata.clone());
+        }
+        match &self.fallback {
+            Some(fallback) => fallback.movie_metadata(storage, id).await,
+            None => Err(anyhow::anyhow!("no sidecar describes movie {}", id)),
+        }
+    }
+
+    async fn series(&self, storage: &Path, id: ProviderId) -> anyhow::Result<SeriesMetadata> {
+        if let Some(metadata) = self.sidecars.series_by_id(id) {
+            return Ok(metadata.clone());
+        }
+        match &self.fallback {
+            Some(fallback) => fallback.series_metadata(storage, id).await,
+            None => Err(anyhow::anyhow!("no sidecar describes tv show {}", id)),
+        }
+    }
+}
+
+impl<S: Scraper> Scraper for SidecarScraper<S> {
+    /// Sidecars mostly carry TMDB ids.
+    fn provides(&self, provider: Provider) -> bool {
+        provider == Provider::Tmdb
+            || self
+                .fallback
+                .as_ref()
+                .is_some_and(|fallback| fallback.provides(provider))
+    }
+
+    fn scrape_movie_metadata<'a>(
+        &'a self,
+        storage: &'a Path,
+        title: &'a str,
+        year: u16,
+    ) -> ScrapeFuture<'a, Option<Match<MovieMetadata>>> {
+        Box::pin(self.scrape_movie(storage, title, year))
+    }
+
+    fn scrape_series_metadata<'a>(
+        &'a self,
+        storage: &'a Path,
+        title: &'a str,
+        year: Option<u16>,
+    ) -> ScrapeFuture<'a, Option<Match<SeriesMetadata>>> {
+        Box::pin(self.scrape_series(storage, title, year))
+    }
+
+    fn scrape_season_metadata<'a>(
+        &'a self,
+        storage: &'a Path,
+        series: ProviderId,
+        season: u16,
+        order: &'a EpisodeOrder,
+    ) -> ScrapeFuture<'a, Option<(SeasonMetadata, Vec<EpisodeMetadata>)>> {
+        Box::pin(self.scrape_season(storage, series, season, order))
+    }
+
+    fn episode_orders<'a>(&'a self, series: ProviderId) -> ScrapeFuture<'a, Vec<EpisodeOrder>> {
+        match &self.fallback {
+            Some(fallback) => fallback.episode_orders(series),
+            None => Box::pin(async { Ok(vec![]) }),
+        }
+    }
+
+    fn episode_counts<'a>(
+        &'a self,
+        series: ProviderId,
+        order: &'a EpisodeOrder,
+    ) -> ScrapeFuture<'a, Vec<(u16, u16)>> {
+        match &self.fallback {
+            Some(fallback) => fallback.episode_counts(series, order),
+            None => Box::pin(async { Ok(vec![]) }),
+        }
+    }
+
+    fn search<'a>(
+        &'a self,
+        kind: MediaKind,
+        title: &'a str,
+        year: Option<u16>,
+    ) -> ScrapeFuture<'a, Vec<SearchResult>> {
+        match &self.fallback {
+            Some(fallback) => fallback.search(kind, title, year),
+            None => Box::pin(async { Ok(vec![]) }),
+        }
+    }
+
+    fn movie_metadata<'a>(
+        &'a self,
+        storage: &'a Path,
+        id: ProviderId,
+    ) -> ScrapeFuture<'a, MovieMetadata> {
+        Box::pin(self.movie(storage, id))
+    }
+
+    fn series_metadata<'a>(
+        &'a self,
+        storage: &'a Path,
+        id: ProviderId,
+    ) -> ScrapeFuture<'a, SeriesMetadata> {
+        Box::pin(self.series(storage, id))
+    }
+
+    fn take_failures(&self) -> Vec<ScrapeFailure> {
+        self.fallback
+            .as_ref()
+            .map_or_else(Vec::new, |fallback| fallback.take_failures())
+    }
+}
+
+async fn read_nfo(path: &Path) -> anyhow::Result<Option<String>> {
+    if !path.exists() {
+        return Ok(None);
+    }
+    let xml = async_std::fs::read_to_string(path).await?;
+    Ok(Some(xml.trim_start_matches('\u{feff}').to_string()))
+}
+
+fn parse_movie(xml: &str, dir: &Path, stem: &str) -> MovieMetadata {
+    let released = date(xml, "premiered").or_else(|| date(xml, "releasedate"));
+    let mut crew: Vec<_> = texts(xml, "director")
+        .into_iter()
+        .map(|name| Credit {
+            name,
+            role: String::from("Director"),
+        })
+        .collect();
+    crew.extend(texts(xml, "credits").into_iter().map(|name| Credit {
+        name,
+        role: String::from("Writer"),
+    }));
+
+    let id = unique_id(xml).unwrap_or(ProviderId::new(Provider::Local, 0));
+    MovieMetadata {
+        provider: id.provider,
+        provider_id: id.id,
+        title: text(xml, "title").unwrap_or_else(|| stem.to_string()),
+        year: text(xml, "year")
+            .and_then(|year| year.parse().ok())
+            .or(released.map(|date| date.year() as u16))
+            .unwrap_or(0),
+        poster: find_image(dir, &[&format!("{}-poster", stem), "poster", "folder"]),
+        backdrop: find_image(dir, &[&format!("{}-fanart", stem), "fanart"]),
+        released,
+        details: Details {
+            crew,
+            ..details(xml)
+        },
+        locked: Default::default(),
+    }
+}
+
+/// `None` without a provider id.
+fn parse_series(xml: &str, dir: &Path) -> Option<SeriesMetadata> {
+    let id = unique_id(xml)?;
+    Some(SeriesMetadata {
+        provider: id.provider,
+        provider_id: id.id,
+        title: text(xml, "title")?,
+        poster: find_image(dir, &["poster", "folder"]),
+        backdrop: find_image(dir, &["fanart"]),
+        logo: find_image(dir, &["clearlogo", "logo"]),
+        aired: date(xml, "premiered"),
+        details: details(xml),
+        next_episode: None,
+        locked: Default::default(),
+    })
+}
+
+/// The season and episode number come from the file name, as that is what episodes are matched
+/// by. `xml` is empty for episodes without a `.nfo`.
+fn parse_episode(
+    xml: &str,
+    series: ProviderId,
+    season: u16,
+    episode: u16,
+    dir: &Path,
+    stem: &str,
+) -> EpisodeMetadata {
+    EpisodeMetadata {
+        provider: series.provider,
+        series_provider_id: series.id,
+        title: text(xml, "title").unwrap_or_else(|| format!("Episode {}", episode)),
+        season,
+        episode,
+        aired: date(xml, "aired"),
+        overview: text(xml, "plot"),
+        runtime: text(xml, "runtime").and_then(|runtime| runtime.parse().ok()),
+        still: find_image(dir, &[&format!("{}-thumb", stem)]),
+        locked: Default::default(),
+    }
+}
+
+fn details(xml: &str) -> Details {
+    Details {
+        overview: text(xml, "plot"),
+        tagline: text(xml, "tagline"),
+        genres: texts(xml, "genre"),
+        runtime: text(xml, "runtime").and_then(|runtime| runtime.parse().ok()),
+        certification: text(xml, "mpaa").map(|mpaa| {
+            // Kodi writes either "Rated PG-13" or "US:PG-13"
+            let mpaa = mpaa.trim_start_matches("Rated ");
+            mpaa.split_once(':')
+                .map_or(mpaa, |(_, rating)| rating)
+                .to_string()
+        }),
+        rating: text(xml, "value")
+            .or_else(|| text(xml, "rating"))
+            .and_then(|rating| rating.parse().ok()),
+        original_title: text(xml, "originaltitle"),
+        original_language: None,
+        studios: texts(xml, "studio"),
+        cast: elements(xml, "actor")
+            .into_iter()
+            .filter_map(|actor| {
+                Some(Credit {
+                    name: text(actor.content, "name")?,
+                    role: text(actor.content, "role").unwrap_or_default(),
+                })
+            })
+            .collect(),
+        crew: vec![],
+    }
+}
+
+/// The id of the first provider, in [`Provider::ALL`] order, that `<uniqueid>` has one for.
+fn unique_id(xml: &str) -> Option<ProviderId> {
+    let ids: Vec<_> = elements(xml, "uniqueid")
+        .into_iter()
+        .filter_map(|id| Some((attribute(id.attributes, "type")?, decode(id.content)?)))
+        .collect();
+    let find = |kind: &str| {
+        ids.iter()
+            .find(|(other, _)| *other == kind)
+            .map(|(_, id)| id.clone())
+    };
+
+    Provider::ALL.into_iter().find_map(|provider| {
+        let id = match provider {
+            Provider::Tmdb => find("tmdb").or_else(|| text(xml, "tmdbid"))?.parse().ok()?,
+            Provider::Tvmaze => find("tvmaze")?.parse().ok()?,
+            Provider::Omdb => parse_imdb_id(&find("imdb")?).ok()?,
+            Provider::Tvdb => find("tvdb")?.parse().ok()?,
+            Provider::Local => return None,
+        };
+        Some(ProviderId::new(provider, id)).filter(|id| id.id != 0)
+    })
+}
+
+/// How many videos are directly in `dir`.
+async fn videos_in(dir: &Path) -> anyhow::Result<usize> {
+    let mut count = 0;
+    let mut entries = async_std::fs::read_dir(dir).await?;
+    while let Some(entry) = entries.next().await {
+        if is_video(entry?.path().as_ref()) {
+            count += 1;
+        }
+    }
+    Ok(count)
+}
+
+fn season_title(season: u16) -> String {
+    if season == 0 {
+        String::from("Specials")
+    } else {
+        format!("Season {}", season)
+    }
+}
+
+/// Kodi keeps season posters in the show's directory, or in the season's own directory.
+fn season_poster(show_dir: &Path, dir: &Path, season: u16) -> Option<PathBuf> {
+    let name = if season == 0 {
+        String::from("season-specials-poster")
+    } else {
+        format!("season{:02}-poster", season)
+    };
+    find_image(show_dir, &[&name]).or_else(|| {
+        Some(dir)
+            .filter(|dir| *dir != show_dir)
+            .and_then(|dir| find_image(dir, &["poster", "folder"]))
+    })
+}
+
+fn find_image(dir: &Path, names: &[&str]) -> Option<PathBuf> {
+    names.iter().find_map(|name| {
+        IMAGE_EXTENSIONS
+            .iter()
+            .map(|extension| dir.join(format!("{}.{}", name, extension)))
+            .find(|path| path.exists())
+    })
+}
+
+struct Element<'a> {
+    attributes: &'a str,
+    content: &'a str,
+}
+
+/// Every `<name>` element, nested or not. Enough for the flat XML Kodi writes, without pulling in
+/// an XML parser.
+fn elements<'a>(xml: &'a str, name: &str) -> Vec<Element<'a>> {
+    let open = format!("<{}", name);
+    let close = format!("</{}>", name);
+
+    let mut out = vec![];
+    let mut rest = xml;
+    while let Some(start) = rest.find(&open) {
+        let after = &rest[start + open.len()..];
+        let Some(end) = after.find('>') else {
+            break;
+        };
+        let attributes = &after[..end];
+        let body = &after[end + 1..];
+
+        // `<genre>` shouldn't match `<genres>`
+        if !attributes.is_empty() && !attributes.starts_with(char::is_whitespace) {
+            rest = after;
+            continue;
+        }
+        if attributes.ends_with('/') {
+            out.push(Element {
+                attributes: attributes.trim_end_matches('/').trim(),
+                content: "",
+            });
+            rest = body;
+            continue;
+        }
+
+        let Some(end) = body.find(&close) else {
+            break;
+        };
+        out.push(Element {
+            attributes: attributes.trim(),
+            content: &body[..end],
+        });
+        rest = &body[end + close.len()..];
+    }
+    out
+}
+
+fn attribute<'a>(attributes: &'a str, name: &str) -> Option<&'a str> {
+    let start = attributes.find(&format!("{}=\"", name))? + name.len() + 2;
+    let end = attributes[start..].find('"')?;
+    Some(&attributes[start..start + end])
+}
+
+fn decode(content: &str) -> Option<String> {
+    let content = content.trim();
+    let content = content
+        .strip_prefix("<![CDATA[")
+        .and_then(|content| content.strip_suffix("]]>"))
+        .map(str::to_string)
+        .unwrap_or_else(|| html_escape::decode_html_entities(content).to_string());
+    let content = content.trim();
+    (!content.is_empty()).then(|| content.to_string())
+}
+
+fn text(xml: &str, name: &str) -> Option<String> {
+    elements(xml, name)
+        .into_iter()
+        .find_map(|element| decode(element.content))
+}
+
+fn texts(xml: &str, name: &str) -> Vec<String> {
+    elements(xml, name)
+        .into_iter()
+        .filter_map(|element| decode(element.content))
+        .collect()
+}
+
+fn date(xml: &str, name: &str) -> Option<NaiveDate> {
+    NaiveDate::parse_from_str(&text(xml, name)?, "%Y-%m-%d").ok()
+}
@@ -0,0 +1,322 @@
+use super::{Auth, Client, Provider, RateLimiter};
+use chrono::NaiveDate;
+use serde::{Deserialize, Deserializer};
+use std::sync::LazyLock;
+
+pub const DEFAULT_API_BASE: &str = "https://api.themoviedb.org/3";
+
+/// TMDB allows around 50 requests per second from one address; stay comfortably below that.
+static RATE_LIMITER: LazyLock<RateLimiter> = LazyLock::new(|| RateLimiter::new(40.0, 20.0));
+
+/// A client for the TMDB v3 API, authenticated with either kind of secret.
+pub fn client(secret: &str) -> Client {
+    let secret = secret.trim().to_string();
+    // v4 read access tokens are JWTs, everything else is treated as a v3 API key
+    let auth = if secret.starts_with("eyJ") {
+        Auth::Bearer(secret)
+    } else {
+        Auth::Query("api_key", secret)
+    };
+    Client::new(Provider::Tmdb, DEFAULT_API_BASE, auth, &RATE_LIMITER)
+}
+
+pub fn optional_date<'de, D: Deserializer<'de>>(de: D) -> Result<Option<NaiveDate>, D::Error> {
+    let date = Option::<String>::deserialize(de)?;
+    Ok(date.and_then(|date| NaiveDate::parse_from_str(&date, "%Y-%m-%d").ok()))
+}
+
+/// Release dates are full timestamps, but only the day is of interest.
+fn optional_timestamp_date<'de, D: Deserializer<'de>>(
+    de: D,
+) -> Result<Option<NaiveDate>, D::Error> {
+    let date = Option::<String>::deserialize(de)?;
+    Ok(date.and_then(|date| NaiveDate::parse_from_str(date.get(..10)?, "%Y-%m-%d").ok()))
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct Page<T> {
+    pub results: Vec<T>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct MovieResult {
+    pub id: u64,
+    pub title: String,
+    #[serde(default)]
+    pub original_title: String,
+    #[serde(default, deserialize_with = "optional_date")]
+    pub release_date: Option<NaiveDate>,
+    #[serde(default)]
+    pub overview: Option<String>,
+    #[serde(default)]
+    pub poster_path: Option<String>,
+    #[serde(default)]
+    pub popularity: f64,
+    #[serde(default)]
+    pub vote_count: u64,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct TvResult {
+    pub id: u64,
+    pub name: String,
+    #[serde(default)]
+    pub original_name: String,
+    #[serde(default, deserialize_with = "optional_date")]
+    pub first_air_date: Option<NaiveDate>,
+    #[serde(default)]
+    pub overview: Option<String>,
+    #[serde(default)]
+    pub poster_path: Option<String>,
+    #[serde(default)]
+    pub popularity: f64,
+    #[serde(default)]
+    pub vote_count: u64,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct AlternativeTitle {
+    pub title: String,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct MovieAlternativeTitles {
+    #[serde(default)]
+    pub titles: Vec<AlternativeTitle>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct TvAlternativeTitles {
+    #[serde(default)]
+    pub results: Vec<AlternativeTitle>,
+}
+
+/// A season as listed in `/tv/{id}`.
+#[derive(Deserialize, Debug, Clone)]
+pub struct SeasonSummary {
+    pub season_number: u16,
+    #[serde(default)]
+    pub episode_count: u16,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct SeasonDetails {
+    pub name: String,
+    #[serde(default, deserialize_with = "optional_date")]
+    pub air_date: Option<NaiveDate>,
+    #[serde(default)]
+    pub overview: Option<String>,
+    #[serde(default)]
+    pub poster_path: Option<String>,
+    #[serde(default)]
+    pub episodes: Vec<EpisodeResult>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct EpisodeResult {
+    #[serde(default)]
+    pub season_number: u16,
+    pub episode_number: u16,
+    pub name: String,
+    #[serde(default, deserialize_with = "optional_date")]
+    pub air_date: Option<NaiveDate>,
+    #[serde(default)]
+    pub overview: Option<String>,
+    #[serde(default)]
+    pub runtime: Option<u32>,
+    #[serde(default)]
+    pub still_path: Option<String>,
+    /// Position within an episode group, from zero. Seasons don't have it.
+    #[serde(default)]
+    pub order: u16,
+}
+
+/// An entry of `/tv/{id}/episode_groups`.
+#[derive(Deserialize, Debug, Clone)]
+pub struct EpisodeGroupSummary {
+    pub id: String,
+    pub name: String,
+    /// 1 original air date, 2 absolute, 3 DVD, 4 digital, 5 story arc, 6 production, 7 TV.
+    #[serde(rename = "type", default)]
+    pub kind: u8,
+}
+
+/// `/tv/episode_group/{id}`, whose groups stand in for seasons.
+#[derive(Deserialize, Debug, Clone)]
+pub struct EpisodeGroupDetails {
+    #[serde(default)]
+    pub groups: Vec<EpisodeGroup>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct EpisodeGroup {
+    pub name: String,
+    #[serde(default)]
+    pub order: u16,
+    #[serde(default)]
+    pub episodes: Vec<EpisodeResult>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct Genre {
+    pub name: String,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct Company {
+    pub name: String,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct Creator {
+    pub name: String,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct CastMember {
+    pub name: String,
+    #[serde(default)]
+    pub character: String,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct CrewMember {
+    pub name: String,
+    #[serde(default)]
+    pub job: String,
+}
+
+#[derive(Deserialize, Debug, Clone, Default)]
+pub struct Credits {
+    #[serde(default)]
+    pub cast: Vec<CastMember>,
+    #[serde(default)]
+    pub crew: Vec<CrewMember>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct ReleaseDate {
+    #[serde(default)]
+    pub certification: String,
+    #[serde(default, deserialize_with = "optional_timestamp_date")]
+    pub release_date: Option<NaiveDate>,
+    /// 1 premiere, 2 limited theatrical, 3 theatrical, 4 digital, 5 physical, 6 TV.
+    #[serde(rename = "type", default)]
+    pub kind: u8,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct CountryReleaseDates {
+    pub iso_3166_1: String,
+    #[serde(default)]
+    pub release_dates: Vec<ReleaseDate>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct ContentRating {
+    pub iso_3166_1: String,
+    #[serde(default)]
+    pub rating: String,
+}
+
+/// `/movie/{id}` with `credits`, `release_dates` and `images` appended.
+#[derive(Deserialize, Debug, Clone)]
+pub struct MovieDetails {
+    pub id: u64,
+    pub title: String,
+    #[serde(default)]
+    pub original_title: String,
+    #[serde(default)]
+    pub original_language: String,
+    #[serde(default, deserialize_with = "optional_date")]
+    pub release_date: Option<NaiveDate>,
+    #[serde(default)]
+    pub overview: Option<String>,
+    #[serde(default)]
+    pub tagline: Option<String>,
+    #[serde(default)]
+    pub poster_path: Option<String>,
+    #[serde(default)]
+    pub backdrop_path: Option<String>,
+    #[serde(default)]
+    pub genres: Vec<Genre>,
+    #[serde(default)]
+    pub runtime: Option<u32>,
+    #[serde(default)]
+    pub vote_average: f32,
+    #[serde(default)]
+    pub vote_count: u64,
+    #[serde(default)]
+    pub production_companies: Vec<Company>,
+    #[serde(default)]
+    pub credits: Credits,
+    #[serde(default)]
+    pub release_dates: Option<Page<CountryReleaseDates>>,
+    #[serde(default)]
+    pub images: Images,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct Image {
+    pub file_path: String,
+    /// `None` for images without text.
+    #[serde(default)]
+    pub iso_639_1: Option<String>,
+}
+
+#[derive(Deserialize, Debug, Clone, Default)]
+pub struct Images {
+    #[serde(default)]
+    pub posters: Vec<Image>,
+    #[serde(default)]
+    pub logos: Vec<Image>,
+}
+
+/// `/authentication`, which only checks the secret.
+#[derive(Deserialize, Debug, Clone)]
+pub struct Authentication {
+    pub success: bool,
+}
+
+/// `/tv/{id}` with `credits`, `content_ratings` and `images` appended.
+#[derive(Deserialize, Debug, Clone)]
+pub struct TvDetails {
+    pub id: u64,
+    pub name: String,
+    #[serde(default)]
+    pub original_name: String,
+    #[serde(default)]
+    pub original_language: String,
+    #[serde(default, deserialize_with = "optional_date")]
+    pub first_air_date: Option<NaiveDate>,
+    #[serde(default)]
+    pub overview: Option<String>,
+    #[serde(default)]
+    pub tagline: Option<String>,
+    #[serde(default)]
+    pub poster_path: Option<String>,
+    #[serde(default)]
+    pub backdrop_path: Option<String>,
+    #[serde(default)]
+    pub genres: Vec<Genre>,
+    #[serde(default)]
+    pub episode_run_time: Vec<u32>,
+    #[serde(default)]
+    pub vote_average: f32,
+    #[serde(default)]
+    pub vote_count: u64,
+    #[serde(default)]
+    pub networks: Vec<Company>,
+    #[serde(default)]
+    pub created_by: Vec<Creator>,
+    #[serde(default)]
+    pub credits: Credits,
+    #[serde(default)]
+    pub content_ratings: Option<Page<ContentRating>>,
+    #[serde(default)]
+    pub images: Images,
+    #[serde(default)]
+    pub seasons: Vec<SeasonSummary>,
+    #[serde(default)]
+    pub next_episode_to_air: Option<EpisodeResult>,
+}
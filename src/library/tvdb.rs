@@ -0,0 +1,627 @@
+use super::{
+    Auth, CAST_LIMIT, Candidate, Client, Credit, Details, EpisodeMetadata, EpisodeOrder, Failures,
+    Fixtures, MATCH_THRESHOLD, Match, MediaKind, MovieMetadata, Provider, ProviderId, RateLimiter,
+    ResponseCache, ScrapeFailure, ScrapeFuture, Scraper, SearchResult, SeasonMetadata,
+    SeriesMetadata, fetch_artwork, rank_candidates, tmdb::optional_date,
+};
+use anyhow::Context;
+use chrono::{Datelike, NaiveDate};
+use itertools::Itertools;
+use serde::{Deserialize, de::DeserializeOwned};
+use std::{
+    path::Path,
+    sync::{Arc, LazyLock, Mutex},
+};
+
+const API_BASE: &str = "https://api4.thetvdb.com/v4";
+
+/// TheTVDB doesn't publish a limit, so stay well within what TMDB allows.
+static RATE_LIMITER: LazyLock<RateLimiter> = LazyLock::new(|| RateLimiter::new(10.0, 10.0));
+
+/// Roles from the cast list shown as crew, in display order.
+const CREW_TYPES: &[&str] = &["Creator", "Director", "Writer", "Producer"];
+
+#[derive(Deserialize, Debug, Clone)]
+struct Reply<T> {
+    data: T,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+struct Login {
+    token: String,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+struct SearchHit {
+    tvdb_id: String,
+    name: String,
+    #[serde(default)]
+    year: Option<String>,
+    #[serde(default)]
+    overview: Option<String>,
+    #[serde(default)]
+    image_url: Option<String>,
+    #[serde(default)]
+    aliases: Vec<String>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+struct Named {
+    name: String,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+struct Character {
+    /// The character's name, for actors.
+    #[serde(default)]
+    name: Option<String>,
+    #[serde(default)]
+    person_name: String,
+    #[serde(default)]
+    people_type: String,
+    /// Billing order.
+    #[serde(default)]
+    sort: u32,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+struct OverviewTranslation {
+    language: String,
+    #[serde(default)]
+    overview: Option<String>,
+}
+
+#[derive(Deserialize, Debug, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+struct Translations {
+    #[serde(default)]
+    overview_translations: Vec<OverviewTranslation>,
+}
+
+/// `/series/{id}/extended`, with translations.
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+struct Series {
+    id: u64,
+    name: String,
+    #[serde(default)]
+    image: Option<String>,
+    #[serde(default, deserialize_with = "optional_date")]
+    first_aired: Option<NaiveDate>,
+    #[serde(default)]
+    overview: Option<String>,
+    #[serde(default)]
+    genres: Vec<Named>,
+    #[serde(default)]
+    average_runtime: Option<u32>,
+    #[serde(default)]
+    original_network: Option<Named>,
+    #[serde(default)]
+    characters: Vec<Character>,
+    #[serde(default)]
+    translations: Translations,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+struct Release {
+    #[serde(default, deserialize_with = "optional_date")]
+    date: Option<NaiveDate>,
+}
+
+/// `/movies/{id}/extended`, with translations.
+#[derive(Deserialize, Debug, Clone)]
+struct Movie {
+    id: u64,
+    name: String,
+    #[serde(default)]
+    image: Option<String>,
+    #[serde(default)]
+    year: Option<String>,
+    #[serde(default)]
+    runtime: Option<u32>,
+    #[serde(default)]
+    overview: Option<String>,
+    #[serde(default)]
+    genres: Vec<Named>,
+    #[serde(default)]
+    studios: Vec<Named>,
+    #[serde(default)]
+    first_release: Option<Release>,
+    #[serde(default)]
+    characters: Vec<Character>,
+    #[serde(default)]
+    translations: Translations,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+struct SeasonEpisodes {
+    #[serde(default)]
+    episodes: Vec<Episode>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+struct Episode {
+    #[serde(default)]
+    name: Option<String>,
+    number: u16,
+    #[serde(default, deserialize_with = "optional_date")]
+    aired: Option<NaiveDate>,
+    #[serde(default)]
+    runtime: Option<u32>,
+    #[serde(default)]
+    overview: Option<String>,
+    #[serde(default)]
+    image: Option<String>,
+}
+
+fn year(value: Option<&str>) -> Option<u16> {
+    value?.get(..4)?.parse().ok()
+}
+
+/// The overview if there is one, else the English translation.
+fn overview(overview: Option<String>, translations: Translations) -> Option<String> {
+    overview
+        .filter(|overview| !overview.is_empty())
+        .or_else(|| {
+            translations
+                .overview_translations
+                .into_iter()
+                .find(|translation| translation.language == "eng")
+                .and_then(|translation| translation.overview)
+        })
+}
+
+fn credits(characters: Vec<Character>) -> (Vec<Credit>, Vec<Credit>) {
+    let cast = characters
+        .iter()
+        .filter(|character| character.people_type == "Actor")
+        .sorted_by_key(|character| character.sort)
+        .take(CAST_LIMIT)
+        .map(|character| Credit {
+            name: character.person_name.clone(),
+            role: character.name.clone().unwrap_or_default(),
+        })
+        .collect();
+    let crew = CREW_TYPES
+        .iter()
+        .flat_map(|kind| {
+            characters
+                .iter()
+                .filter(move |character| character.people_type == *kind)
+                .map(|character| Credit {
+                    name: character.person_name.clone(),
+                    role: character.people_type.clone(),
+                })
+        })
+        .collect();
+    (cast, crew)
+}
+
+/// Scrapes through TheTVDB v4 API, logging in with the key the first time it's needed.
+#[derive(Clone)]
+pub struct TvdbScraper {
+    key: String,
+    client: Client,
+    token: Arc<Mutex<Option<String>>>,
+    fixtures: Option<Fixtures>,
+    failures: Failures,
+}
+
+impl TvdbScraper {
+    pub fn new(key: &str) -> Self {
+        TvdbScraper {
+            key: key.trim().to_string(),
+            client: Client::new(Provider::Tvdb, API_BASE, Auth::None, &RATE_LIMITER),
+            token: Default::default(),
+            fixtures: None,
+            failures: Failures::default(),
+        }
+    }
+
+    pub fn cache(mut self, cache: ResponseCache) -> Self {
+        self.client = self.client.cache(cache);
+        self
+    }
+
+    pub fn fixtures(mut self, fixtures: Fixtures) -> Self {
+        self.client = self.client.fixtures(fixtures.clone());
+        self.fixtures = Some(fixtures);
+        self
+    }
+
+    /// The client with a bearer token. Replaying doesn't need one, so doesn't log in.
+    async fn client(&self) -> anyhow::Result<Client> {
+        if self.fixtures.as_ref().is_some_and(Fixtures::is_replaying) {
+            return Ok(self.client.clone());
+        }
+
+        let token = self.token.lock().unwrap().clone();
+        let token = match token {
+            Some(token) => token,
+            None => {
+                let login: Reply<Login> = self
+                    .client
+                    .post("/login", &serde_json::json!({ "apikey": self.key }))
+                    .await
+                    .context("tvdb login failed")?;
+                *self.token.lock().unwrap() = Some(login.data.token.clone());
+                login.data.token
+            }
+        };
+        Ok(self.client.clone().auth(Auth::Bearer(token)))
+    }
+
+    async fn get<T: DeserializeOwned>(
+        &self,
+        path: &str,
+        query: &[(&str, String)],
+    ) -> anyhow::Result<T> {
+        let reply: Reply<T> = self.client().await?.get(path, query).await?;
+        Ok(reply.data)
+    }
+
+    async fn search_titles(
+        &self,
+        kind: MediaKind,
+        title: &str,
+        year: Option<u16>,
+    ) -> anyhow::Result<Vec<SearchHit>> {
+        let kind = match kind {
+            MediaKind::Movie => "movie",
+            MediaKind::Series => "series",
+        };
+        let mut query = vec![("query", title.to_string()), ("type", kind.to_string())];
+        if let Some(year) = year {
+            query.push(("year", year.to_string()));
+        }
+
+        self.get("/search", &query)
+            .await
+            .with_context(|| format!("tvdb {} search failed", kind))
+    }
+
+    async fn best_match(
+        &self,
+        kind: MediaKind,
+        title: &str,
+        year: Option<u16>,
+    ) -> anyhow::Result<Option<(f32, SearchHit)>> {
+        let candidates = self
+            .search_titles(kind, title, year)
+            .await?
+            .into_iter()
+            .map(|hit| Candidate {
+                titles: std::iter::once(hit.name.clone())
+                    .chain(hit.aliases.iter().cloned())
+                    .collect(),
+                year: self::year(hit.year.as_deref()),
+                popularity: 0.0,
+                vote_count: 0,
+                value: hit,
+            })
+            .collect();
+
+        Ok(rank_candidates(title, year, candidates)
+            .into_iter()
+            .next()
+            .map(|(confidence, candidate)| (confidence, candidate.value)))
+    }
+
+    async fn scrape_movie_metadata(
+        &self,
+        storage: &Path,
+        title: &str,
+        year: u16,
+    ) -> anyhow::Result<Option<Match<MovieMetadata>>> {
+        let Some((confidence, hit)) = self.best_match(MediaKind::Movie, title, Some(year)).await?
+        else {
+            return Ok(None);
+        };
+
+        let id = hit.tvdb_id.parse()?;
+        let metadata = if confidence < MATCH_THRESHOLD {
+            MovieMetadata {
+                provider: Provider::Tvdb,
+                provider_id: id,
+                title: hit.name,
+                year: self::year(hit.year.as_deref()).unwrap_or(0),
+                poster: None,
+                backdrop: None,
+                released: None,
+                details: Details::default(),
+                locked: Default::default(),
+            }
+        } else {
+            self.movie_metadata(storage, id).await?
+        };
+
+        Ok(Some(Match {
+            metadata,
+            confidence,
+        }))
+    }
+
+    async fn scrape_series_metadata(
+        &self,
+        storage: &Path,
+        title: &str,
+        year: Option<u16>,
+    ) -> anyhow::Result<Option<Match<SeriesMetadata>>> {
+        let Some((confidence, hit)) = self.best_match(MediaKind::Series, title, year).await? else {
+            return Ok(None);
+        };
+
+        let id = hit.tvdb_id.parse()?;
+        let metadata = if confidence < MATCH_THRESHOLD {
+            SeriesMetadata {
+                provider: Provider::Tvdb,
+                provider_id: id,
+                title: hit.name,
+                poster: None,
+                backdrop: None,
+                logo: None,
+                aired: None,
+                details: Details::default(),
+                next_episode: None,
+                locked: Default::default(),
+            }
+        } else {
+            self.series_metadata(storage, id).await?
+        };
+
+        Ok(Some(Match {
+            metadata,
+            confidence,
+        }))
+    }
+
+    async fn scrape_season_metadata(
+        &self,
+        storage: &Path,
+        series_id: u64,
+        season: u16,
+    ) -> anyhow::Result<Option<(SeasonMetadata, Vec<EpisodeMetadata>)>> {
+        let details: SeasonEpisodes = self
+            .get(
+                &format!("/series/{}/episodes/default", series_id),
+                &[("season", season.to_string()), ("page", String::from("0"))],
+            )
+            .await
+            .context("tvdb season episodes failed")?;
+        if details.episodes.is_empty() {
+            return Ok(None);
+        }
+
+        let dir = format!("tvdb/series/{}/season-{}", series_id, season);
+        let mut episodes = Vec::with_capacity(details.episodes.len());
+        for episode in details.episodes {
+            let still = fetch_artwork(
+                storage,
+                &dir,
+                &format!("episode-{}", episode.number),
+                episode.image,
+                self.fixtures.as_ref(),
+                &self.failures,
+            )
+            .await;
+
+            episodes.push(EpisodeMetadata {
+                provider: Provider::Tvdb,
+                series_provider_id: series_id,
+                title: episode
+                    .name
+                    .unwrap_or_else(|| format!("Episode {}", episode.number)),
+                season,
+                episode: episode.number,
+                aired: episode.aired,
+                overview: episode.overview.filter(|overview| !overview.is_empty()),
+                runtime: episode.runtime,
+                still,
+                locked: Default::default(),
+            });
+        }
+
+        Ok(Some((
+            SeasonMetadata {
+                provider: Provider::Tvdb,
+                series_provider_id: series_id,
+                title: format!("Season {}", season),
+                season,
+                poster: None,
+                aired: episodes.iter().filter_map(|episode| episode.aired).min(),
+                overview: None,
+                episodes: episodes.clone(),
+                locked: Default::default(),
+            },
+            episodes,
+        )))
+    }
+
+    async fn search(
+        &self,
+        kind: MediaKind,
+        title: &str,
+        year: Option<u16>,
+    ) -> anyhow::Result<Vec<SearchResult>> {
+        Ok(self
+            .search_titles(kind, title, year)
+            .await?
+            .into_iter()
+            .filter_map(|hit| {
+                Some(SearchResult {
+                    id: ProviderId::new(Provider::Tvdb, hit.tvdb_id.parse().ok()?),
+                    title: hit.name,
+                    year: self::year(hit.year.as_deref()),
+                    overview: hit.overview.unwrap_or_default(),
+                    poster: hit.image_url,
+                })
+            })
+            .collect())
+    }
+
+    async fn movie_metadata(&self, storage: &Path, id: u64) -> anyhow::Result<MovieMetadata> {
+        let movie: Movie = self
+            .get(
+                &format!("/movies/{}/extended", id),
+                &[("meta", String::from("translations"))],
+            )
+            .await
+            .context("tvdb movie details failed")?;
+
+        let (cast, crew) = credits(movie.characters);
+        let released = movie.first_release.and_then(|release| release.date);
+        Ok(MovieMetadata {
+            provider: Provider::Tvdb,
+            provider_id: movie.id,
+            title: movie.name,
+            year: year(movie.year.as_deref())
+                .or(released.map(|date| date.year() as u16))
+                .unwrap_or(0),
+            poster: fetch_artwork(
+                storage,
+                &format!("tvdb/movie/{}", movie.id),
+                "poster",
+                movie.image,
+                self.fixtures.as_ref(),
+                &self.failures,
+            )
+            .await,
+            backdrop: None,
+            released,
+            details: Details {
+                overview: overview(movie.overview, movie.translations),
+                genres: movie.genres.into_iter().map(|genre| genre.name).collect(),
+                runtime: movie.runtime.filter(|&runtime| runtime > 0),
+                studios: movie
+                    .studios
+                    .into_iter()
+                    .map(|studio| studio.name)
+                    .collect(),
+                cast,
+                crew,
+                ..Default::default()
+            },
+            locked: Default::default(),
+        })
+    }
+
+    async fn series_metadata(&self, storage: &Path, id: u64) -> anyhow::Result<SeriesMetadata> {
+        let series: Series = self
+            .get(
+                &format!("/series/{}/extended", id),
+                &[("meta", String::from("translations"))],
+            )
+            .await
+            .context("tvdb series details failed")?;
+
+        let (cast, crew) = credits(series.characters);
+        Ok(SeriesMetadata {
+            provider: Provider::Tvdb,
+            provider_id: series.id,
+            title: series.name,
+            poster: fetch_artwork(
+                storage,
+                &format!("tvdb/series/{}", series.id),
+                "poster",
+                series.image,
+                self.fixtures.as_ref(),
+                &self.failures,
+            )
+            .await,
+            backdrop: None,
+            logo: None,
+            aired: series.first_aired,
+            details: Details {
+                overview: overview(series.overview, series.translations),
+                genres: series.genres.into_iter().map(|genre| genre.name).collect(),
+                runtime: series.average_runtime.filter(|&runtime| runtime > 0),
+                studios: series
+                    .original_network
+                    .map(|network| network.name)
+                    .into_iter()
+                    .collect(),
+                cast,
+                crew,
+                ..Default::default()
+            },
+            next_episode: None,
+            locked: Default::default(),
+        })
+    }
+}
+
+impl Scraper for TvdbScraper {
+    fn provides(&self, provider: Provider) -> bool {
+        provider == Provider::Tvdb
+    }
+
+    fn scrape_movie_metadata<'a>(
+        &'a self,
+        storage: &'a Path,
+        title: &'a str,
+        year: u16,
+    ) -> ScrapeFuture<'a, Option<Match<MovieMetadata>>> {
+        Box::pin(TvdbScraper::scrape_movie_metadata(
+            self, storage, title, year,
+        ))
+    }
+
+    fn scrape_series_metadata<'a>(
+        &'a self,
+        storage: &'a Path,
+        title: &'a str,
+        year: Option<u16>,
+    ) -> ScrapeFuture<'a, Option<Match<SeriesMetadata>>> {
+        Box::pin(TvdbScraper::scrape_series_metadata(
+            self, storage, title, year,
+        ))
+    }
+
+    fn scrape_season_metadata<'a>(
+        &'a self,
+        storage: &'a Path,
+        series: ProviderId,
+        season: u16,
+        _order: &'a EpisodeOrder,
+    ) -> ScrapeFuture<'a, Option<(SeasonMetadata, Vec<EpisodeMetadata>)>> {
+        Box::pin(async move {
+            let series = series.at(Provider::Tvdb)?;
+            TvdbScraper::scrape_season_metadata(self, storage, series, season).await
+        })
+    }
+
+    fn search<'a>(
+        &'a self,
+        kind: MediaKind,
+        title: &'a str,
+        year: Option<u16>,
+    ) -> ScrapeFuture<'a, Vec<SearchResult>> {
+        Box::pin(TvdbScraper::search(self, kind, title, year))
+    }
+
+    fn movie_metadata<'a>(
+        &'a self,
+        storage: &'a Path,
+        id: ProviderId,
+    ) -> ScrapeFuture<'a, MovieMetadata> {
+        Box::pin(
+            async move { TvdbScraper::movie_metadata(self, storage, id.at(Provider::Tvdb)?).await },
+        )
+    }
+
+    fn series_metadata<'a>(
+        &'a self,
+        storage: &'a Path,
+        id: ProviderId,
+    ) -> ScrapeFuture<'a, SeriesMetadata> {
+        Box::pin(async move {
+            TvdbScraper::series_metadata(self, storage, id.at(Provider::Tvdb)?).await
+        })
+    }
+
+    fn take_failures(&self) -> Vec<ScrapeFailure> {
+        self.failures.take()
+    }
+}
@@ -0,0 +1,458 @@
+use super::{
+    Auth, CAST_LIMIT, Candidate, Client, Credit, Details, EpisodeMetadata, EpisodeOrder, Failures,
+    Fixtures, MATCH_THRESHOLD, Match, MediaKind, MovieMetadata, Provider, ProviderId, RateLimiter,
+    ResponseCache, ScrapeFailure, ScrapeFuture, Scraper, SearchResult, SeasonMetadata,
+    SeriesMetadata, fetch_artwork, rank_candidates, tmdb::optional_date,
+};
+use anyhow::Context;
+use chrono::{Datelike, NaiveDate};
+use regex::Regex;
+use serde::Deserialize;
+use std::{path::Path, sync::LazyLock};
+
+const API_BASE: &str = "https://api.tvmaze.com";
+
+/// TVmaze allows 20 requests every 10 seconds from one address.
+static RATE_LIMITER: LazyLock<RateLimiter> = LazyLock::new(|| RateLimiter::new(2.0, 10.0));
+
+#[derive(Deserialize, Debug, Clone)]
+struct SearchHit {
+    show: Show,
+}
+
+#[derive(Deserialize, Debug, Clone, Default)]
+struct Image {
+    #[serde(default)]
+    medium: Option<String>,
+    #[serde(default)]
+    original: Option<String>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+struct Named {
+    name: String,
+}
+
+#[derive(Deserialize, Debug, Clone, Default)]
+struct Rating {
+    #[serde(default)]
+    average: Option<f32>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+struct CastCredit {
+    person: Named,
+    character: Named,
+}
+
+#[derive(Deserialize, Debug, Clone, Default)]
+struct Embedded {
+    #[serde(default)]
+    cast: Vec<CastCredit>,
+}
+
+/// `/shows/{id}`, with the cast embedded when looked up by id.
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+struct Show {
+    id: u64,
+    name: String,
+    #[serde(default, deserialize_with = "optional_date")]
+    premiered: Option<NaiveDate>,
+    #[serde(default)]
+    summary: Option<String>,
+    #[serde(default)]
+    image: Option<Image>,
+    #[serde(default)]
+    genres: Vec<String>,
+    #[serde(default)]
+    network: Option<Named>,
+    #[serde(default)]
+    web_channel: Option<Named>,
+    #[serde(default)]
+    rating: Rating,
+    #[serde(default)]
+    average_runtime: Option<u32>,
+    /// Popularity, from 0 to 100.
+    #[serde(default)]
+    weight: u64,
+    #[serde(default, rename = "_embedded")]
+    embedded: Embedded,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+struct Season {
+    id: u64,
+    number: u16,
+    /// Missing for seasons that haven't been scheduled yet.
+    #[serde(default)]
+    episode_order: Option<u16>,
+    #[serde(default)]
+    name: String,
+    #[serde(default, deserialize_with = "optional_date")]
+    premiere_date: Option<NaiveDate>,
+    #[serde(default)]
+    summary: Option<String>,
+    #[serde(default)]
+    image: Option<Image>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+struct Episode {
+    name: String,
+    /// `None` for specials, which are numbered separately.
+    #[serde(default)]
+    number: Option<u16>,
+    #[serde(default, deserialize_with = "optional_date")]
+    airdate: Option<NaiveDate>,
+    #[serde(default)]
+    runtime: Option<u32>,
+    #[serde(default)]
+    summary: Option<String>,
+    #[serde(default)]
+    image: Option<Image>,
+}
+
+/// Summaries are HTML fragments.
+fn plain_text(html: Option<String>) -> Option<String> {
+    static RE_TAG: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"<[^>]*>").unwrap());
+    let text = RE_TAG.replace_all(html.as_deref()?, "");
+    let text = html_escape::decode_html_entities(text.trim()).to_string();
+    (!text.is_empty()).then_some(text)
+}
+
+/// Scrapes series from TVmaze, which needs no API key but only knows about TV and only in English.
+#[derive(Clone)]
+pub struct TvmazeScraper {
+    client: Client,
+    fixtures: Option<Fixtures>,
+    failures: Failures,
+}
+
+impl Default for TvmazeScraper {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TvmazeScraper {
+    pub fn new() -> Self {
+        TvmazeScraper {
+            client: Client::new(Provider::Tvmaze, API_BASE, Auth::None, &RATE_LIMITER),
+            fixtures: None,
+            failures: Failures::default(),
+        }
+    }
+
+    pub fn cache(mut self, cache: ResponseCache) -> Self {
+        self.client = self.client.cache(cache);
+        self
+    }
+
+    pub fn fixtures(mut self, fixtures: Fixtures) -> Self {
+        self.client = self.client.fixtures(fixtures.clone());
+        self.fixtures = Some(fixtures);
+        self
+    }
+
+    async fn search_shows(&self, title: &str) -> anyhow::Result<Vec<Show>> {
+        let hits: Vec<SearchHit> = self
+            .client
+            .get("/search/shows", &[("q", title.to_string())])
+            .await
+            .context("tvmaze show search failed")?;
+        Ok(hits.into_iter().map(|hit| hit.show).collect())
+    }
+
+    /// Metadata straight from a search result, without the cast or artwork.
+    fn series_from_show(show: Show) -> SeriesMetadata {
+        let studios = show
+            .network
+            .or(show.web_channel)
+            .map(|studio| studio.name)
+            .into_iter()
+            .collect();
+        SeriesMetadata {
+            provider: Provider::Tvmaze,
+            provider_id: show.id,
+            title: show.name,
+            poster: None,
+            backdrop: None,
+            logo: None,
+            aired: show.premiered,
+            details: Details {
+                overview: plain_text(show.summary),
+                genres: show.genres,
+                runtime: show.average_runtime,
+                rating: show.rating.average,
+                studios,
+                cast: show
+                    .embedded
+                    .cast
+                    .into_iter()
+                    .take(CAST_LIMIT)
+                    .map(|credit| Credit {
+                        name: credit.person.name,
+                        role: credit.character.name,
+                    })
+                    .collect(),
+                ..Default::default()
+            },
+            next_episode: None,
+            locked: Default::default(),
+        }
+    }
+
+    async fn scrape_series_metadata(
+        &self,
+        storage: &Path,
+        title: &str,
+        year: Option<u16>,
+    ) -> anyhow::Result<Option<Match<SeriesMetadata>>> {
+        let candidates = self
+            .search_shows(title)
+            .await?
+            .into_iter()
+            .map(|show| Candidate {
+                titles: vec![show.name.clone()],
+                year: show.premiered.map(|date| date.year() as u16),
+                popularity: show.weight as f64,
+                vote_count: 0,
+                value: show,
+            })
+            .collect();
+
+        let Some((confidence, Candidate { value: show, .. })) =
+            rank_candidates(title, year, candidates).into_iter().next()
+        else {
+            return Ok(None);
+        };
+
+        let metadata = if confidence < MATCH_THRESHOLD {
+            Self::series_from_show(show)
+        } else {
+            self.series_metadata(storage, show.id).await?
+        };
+
+        Ok(Some(Match {
+            metadata,
+            confidence,
+        }))
+    }
+
+    async fn scrape_season_metadata(
+        &self,
+        storage: &Path,
+        series_id: u64,
+        season: u16,
+    ) -> anyhow::Result<Option<(SeasonMetadata, Vec<EpisodeMetadata>)>> {
+        let seasons: Vec<Season> = self
+            .client
+            .get(&format!("/shows/{}/seasons", series_id), &[])
+            .await
+            .context("tvmaze seasons failed")?;
+        let Some(details) = seasons.into_iter().find(|other| other.number == season) else {
+            return Ok(None);
+        };
+
+        let episodes: Vec<Episode> = self
+            .client
+            .get(&format!("/seasons/{}/episodes", details.id), &[])
+            .await
+            .context("tvmaze season episodes failed")?;
+
+        let dir = format!("tvmaze/{}/season-{}", series_id, season);
+        let poster = fetch_artwork(
+            storage,
+            &dir,
+            "poster",
+            details.image.and_then(|image| image.original),
+            self.fixtures.as_ref(),
+            &self.failures,
+        )
+        .await;
+
+        let mut metadata = Vec::with_capacity(episodes.len());
+        for episode in episodes {
+            let Some(number) = episode.number else {
+                continue;
+            };
+            let still = fetch_artwork(
+                storage,
+                &dir,
+                &format!("episode-{}", number),
+                episode.image.and_then(|image| image.medium),
+                self.fixtures.as_ref(),
+                &self.failures,
+            )
+            .await;
+
+            metadata.push(EpisodeMetadata {
+                provider: Provider::Tvmaze,
+                series_provider_id: series_id,
+                title: episode.name,
+                season,
+                episode: number,
+                aired: episode.airdate,
+                overview: plain_text(episode.summary),
+                runtime: episode.runtime,
+                still,
+                locked: Default::default(),
+            });
+        }
+
+        Ok(Some((
+            SeasonMetadata {
+                provider: Provider::Tvmaze,
+                series_provider_id: series_id,
+                title: Some(details.name)
+                    .filter(|name| !name.is_empty())
+                    .unwrap_or_else(|| format!("Season {}", season)),
+                season,
+                poster,
+                aired: details.premiere_date,
+                overview: plain_text(details.summary),
+                episodes: metadata.clone(),
+                locked: Default::default(),
+            },
+            metadata,
+        )))
+    }
+
+    async fn episode_counts(&self, series_id: u64) -> anyhow::Result<Vec<(u16, u16)>> {
+        let seasons: Vec<Season> = self
+            .client
+            .get(&format!("/shows/{}/seasons", series_id), &[])
+            .await
+            .context("tvmaze seasons failed")?;
+        Ok(seasons
+            .into_iter()
+            .map(|season| (season.number, season.episode_order.unwrap_or(0)))
+            .collect())
+    }
+
+    async fn search(&self, kind: MediaKind, title: &str) -> anyhow::Result<Vec<SearchResult>> {
+        if kind == MediaKind::Movie {
+            return Ok(vec![]);
+        }
+
+        Ok(self
+            .search_shows(title)
+            .await?
+            .into_iter()
+            .map(|show| SearchResult {
+                id: ProviderId::new(Provider::Tvmaze, show.id),
+                title: show.name,
+                year: show.premiered.map(|date| date.year() as u16),
+                overview: plain_text(show.summary).unwrap_or_default(),
+                poster: show.image.and_then(|image| image.medium),
+            })
+            .collect())
+    }
+
+    async fn series_metadata(&self, storage: &Path, id: u64) -> anyhow::Result<SeriesMetadata> {
+        let show: Show = self
+            .client
+            .get(
+                &format!("/shows/{}", id),
+                &[("embed", String::from("cast"))],
+            )
+            .await
+            .context("tvmaze show details failed")?;
+
+        let poster = show.image.clone().and_then(|image| image.original);
+        let mut metadata = Self::series_from_show(show);
+        metadata.poster = fetch_artwork(
+            storage,
+            &format!("tvmaze/{}", id),
+            "poster",
+            poster,
+            self.fixtures.as_ref(),
+            &self.failures,
+        )
+        .await;
+        Ok(metadata)
+    }
+}
+
+impl Scraper for TvmazeScraper {
+    fn provides(&self, provider: Provider) -> bool {
+        provider == Provider::Tvmaze
+    }
+
+    fn scrape_movie_metadata<'a>(
+        &'a self,
+        _storage: &'a Path,
+        _title: &'a str,
+        _year: u16,
+    ) -> ScrapeFuture<'a, Option<Match<MovieMetadata>>> {
+        Box::pin(async { Ok(None) })
+    }
+
+    fn scrape_series_metadata<'a>(
+        &'a self,
+        storage: &'a Path,
+        title: &'a str,
+        year: Option<u16>,
+    ) -> ScrapeFuture<'a, Option<Match<SeriesMetadata>>> {
+        Box::pin(TvmazeScraper::scrape_series_metadata(
+            self, storage, title, year,
+        ))
+    }
+
+    fn scrape_season_metadata<'a>(
+        &'a self,
+        storage: &'a Path,
+        series: ProviderId,
+        season: u16,
+        _order: &'a EpisodeOrder,
+    ) -> ScrapeFuture<'a, Option<(SeasonMetadata, Vec<EpisodeMetadata>)>> {
+        Box::pin(async move {
+            let series = series.at(Provider::Tvmaze)?;
+            TvmazeScraper::scrape_season_metadata(self, storage, series, season).await
+        })
+    }
+
+    /// TVmaze only numbers episodes as aired.
+    fn episode_counts<'a>(
+        &'a self,
+        series: ProviderId,
+        _order: &'a EpisodeOrder,
+    ) -> ScrapeFuture<'a, Vec<(u16, u16)>> {
+        Box::pin(
+            async move { TvmazeScraper::episode_counts(self, series.at(Provider::Tvmaze)?).await },
+        )
+    }
+
+    fn search<'a>(
+        &'a self,
+        kind: MediaKind,
+        title: &'a str,
+        _year: Option<u16>,
+    ) -> ScrapeFuture<'a, Vec<SearchResult>> {
+        Box::pin(TvmazeScraper::search(self, kind, title))
+    }
+
+    fn movie_metadata<'a>(
+        &'a self,
+        _storage: &'a Path,
+        id: ProviderId,
+    ) -> ScrapeFuture<'a, MovieMetadata> {
+        Box::pin(async move { Err(anyhow::anyhow!("TVmaze has no movies, so not {}", id)) })
+    }
+
+    fn series_metadata<'a>(
+        &'a self,
+        storage: &'a Path,
+        id: ProviderId,
+    ) -> ScrapeFuture<'a, SeriesMetadata> {
+        Box::pin(async move {
+            TvmazeScraper::series_metadata(self, storage, id.at(Provider::Tvmaze)?).await
+        })
+    }
+
+    fn take_failures(&self) -> Vec<ScrapeFailure> {
+        self.failures.take()
+    }
+}
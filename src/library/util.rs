@@ -2,22 +2,27 @@ use super::*;
 use futures::StreamExt;
 use itertools::Itertools;
 use normpath::PathExt;
+use rustc_hash::FxHashSet;
 use std::{
     collections::VecDeque,
     path::{Path, PathBuf},
 };
 
 const SUPPORTED_EXTENSIONS: &[&str] = &["mp4", "mkv"];
+/// Formats artwork is saved in, telling it apart from the other files in storage.
+const ARTWORK_EXTENSIONS: &[&str] = &["jpg", "jpeg", "png", "webp", "svg"];
+
+/// Whether `path` has the extension of a video that can be played.
+pub fn is_video(path: &Path) -> bool {
+    path.extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| SUPPORTED_EXTENSIONS.contains(&ext.to_lowercase().as_str()))
+}
 
 async fn scan_file(path: &Path) -> anyhow::Result<Media> {
     let path = path.normalize()?.into_path_buf();
 
-    let extension = path
-        .extension()
-        .map(|ext| ext.to_str().unwrap().to_string())
-        .ok_or_else(|| anyhow::anyhow!("failed to read file extension"))?;
-
-    if !SUPPORTED_EXTENSIONS.contains(&extension.to_lowercase().as_str()) {
+    if !is_video(&path) {
         return Err(anyhow::anyhow!("not a video file"));
     }
 
@@ -29,6 +34,8 @@ async fn scan_file(path: &Path) -> anyhow::Result<Media> {
             last_watched: None,
         },
         dont_scrape: false,
+        needs_review: false,
+        scrape_failed: false,
     }))
 }
 
@@ -68,6 +75,35 @@ pub async fn scan_directories(paths: impl Iterator<Item = &Path>) -> anyhow::Res
     Ok(out)
 }
 
+/// The videos in a directory, by what their file names look like, to preview a directory before
+/// it is scanned.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct VideoCounts {
+    pub movies: usize,
+    pub episodes: usize,
+    /// Neither going by the file name, so left for the user to identify.
+    pub unknown: usize,
+}
+
+pub async fn count_videos(path: &Path) -> anyhow::Result<VideoCounts> {
+    let mut counts = VideoCounts::default();
+    for media in scan_directories(std::iter::once(path)).await? {
+        let Some(name) = media
+            .video()
+            .and_then(|video| video.path.file_name())
+            .and_then(|name| name.to_str())
+        else {
+            continue;
+        };
+        match detect_media_type(name) {
+            MediaType::Movie { .. } => counts.movies += 1,
+            MediaType::Episode { .. } | MediaType::AbsoluteEpisode { .. } => counts.episodes += 1,
+            MediaType::Unknown => counts.unknown += 1,
+        }
+    }
+    Ok(counts)
+}
+
 pub async fn purge_media(media: impl Iterator<Item = (MediaId, PathBuf)>) -> Vec<MediaId> {
     futures::stream::iter(media)
         .filter_map(|(id, path)| async move {
@@ -77,6 +113,176 @@ pub async fn purge_media(media: impl Iterator<Item = (MediaId, PathBuf)>) -> Vec
         .await
 }
 
+/// Every artwork file the library refers to, including stills of episodes that aren't in it.
+pub fn referenced_artwork(library: &Library) -> FxHashSet<PathBuf> {
+    let mut paths = FxHashSet::default();
+    let mut add = |path: &Option<PathBuf>| {
+        if let Some(path) = path {
+            paths.insert(path.clone());
+        }
+    };
+    for (_, media) in library.iter() {
+        match media {
+            Media::Movie(movie) => {
+                add(&movie.metadata.poster);
+                add(&movie.metadata.backdrop);
+            }
+            Media::Series(series) => {
+                add(&series.metadata.poster);
+                add(&series.metadata.backdrop);
+                add(&series.metadata.logo);
+                if let Some(episode) = &series.metadata.next_episode {
+                    add(&episode.still);
+                }
+            }
+            Media::Season(season) => {
+                add(&season.metadata.poster);
+                for episode in &season.metadata.episodes {
+                    add(&episode.still);
+                }
+            }
+            Media::Episode(episode) => add(&episode.metadata.still),
+            Media::Uncategorised(_) => {}
+        }
+    }
+    paths
+}
+
+fn is_artwork(path: &Path) -> bool {
+    path.extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| ARTWORK_EXTENSIONS.contains(&ext.to_lowercase().as_str()))
+}
+
+/// Moves the artwork older versions saved in the storage root into the artwork directory and
+/// points the library at the new paths. Returns whether any path changed.
+pub fn migrate_artwork(library: &mut Library, storage: &Path) -> bool {
+    let dir = storage.join("artwork");
+    let mut moved = false;
+    let mut migrate = |artwork: &mut Option<PathBuf>| {
+        let Some(path) = artwork
+            .as_ref()
+            .filter(|path| path.parent() == Some(storage))
+        else {
+            return;
+        };
+
+        // episodes share their stills with the seasons listing them, so it may be there already
+        let dest = dir.join(path.file_name().unwrap());
+        if !dest.exists() {
+            let renamed = std::fs::create_dir_all(&dir).and_then(|()| std::fs::rename(path, &dest));
+            if let Err(err) = renamed {
+                log::warn!("failed to move artwork {:?}: {:#}", path, err);
+                return;
+            }
+        }
+        *artwork = Some(dest);
+        moved = true;
+    };
+    for (_, media) in library.iter_mut() {
+        match media {
+            Media::Movie(movie) => {
+                migrate(&mut movie.metadata.poster);
+                migrate(&mut movie.metadata.backdrop);
+            }
+            Media::Series(series) => {
+                migrate(&mut series.metadata.poster);
+                migrate(&mut series.metadata.backdrop);
+                migrate(&mut series.metadata.logo);
+                if let Some(episode) = &mut series.metadata.next_episode {
+                    migrate(&mut episode.still);
+                }
+            }
+            Media::Season(season) => {
+                migrate(&mut season.metadata.poster);
+                for episode in &mut season.metadata.episodes {
+                    migrate(&mut episode.still);
+                }
+            }
+            Media::Episode(episode) => migrate(&mut episode.metadata.still),
+            Media::Uncategorised(_) => {}
+        }
+    }
+    moved
+}
+
+/// Deletes the artwork in `storage` that isn't `referenced`, returning how many bytes were freed.
+/// Only the artwork directory and images left in the storage root by older versions are looked
+/// at, so images elsewhere, such as sidecar artwork next to videos, are never touched. Nothing
+/// may be scraped or imported meanwhile, or its artwork could go before the library refers to it.
+pub async fn collect_artwork(
+    storage: &Path,
+    referenced: &FxHashSet<PathBuf>,
+) -> anyhow::Result<u64> {
+    // relative, so a storage path spelled differently still matches
+    let referenced: FxHashSet<&Path> = referenced
+        .iter()
+        .filter_map(|path| path.strip_prefix(storage).ok())
+        .collect();
+
+    let mut files = vec![];
+    let mut entries = async_std::fs::read_dir(storage).await?;
+    while let Some(entry) = entries.next().await {
+        let entry = entry?;
+        let path: PathBuf = entry.path().into();
+        if entry.file_type().await?.is_file() && is_artwork(&path) {
+            files.push(path);
+        }
+    }
+
+    let mut dirs = vec![];
+    let mut queue = VecDeque::from([storage.join("artwork")]);
+    while let Some(dir) = queue.pop_front() {
+        let mut entries = match async_std::fs::read_dir(&dir).await {
+            Ok(entries) => entries,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => continue,
+            Err(err) => return Err(err.into()),
+        };
+        while let Some(entry) = entries.next().await {
+            let entry = entry?;
+            let path: PathBuf = entry.path().into();
+            if entry.file_type().await?.is_dir() {
+                queue.push_back(path);
+            } else if is_artwork(&path) {
+                files.push(path);
+            }
+        }
+        dirs.push(dir);
+    }
+
+    let mut freed = 0;
+    for path in files {
+        if path
+            .strip_prefix(storage)
+            .is_ok_and(|path| referenced.contains(path))
+        {
+            continue;
+        }
+
+        let size = async_std::fs::metadata(&path).await?.len();
+        match async_std::fs::remove_file(&path).await {
+            Ok(()) => freed += size,
+            Err(err) => log::warn!("failed to delete {:?}: {:#}", path, err),
+        }
+    }
+
+    // deepest first, so directories that only held emptied directories go too
+    for dir in dirs.iter().rev() {
+        let empty = match async_std::fs::read_dir(dir).await {
+            Ok(mut entries) => entries.next().await.is_none(),
+            Err(_) => false,
+        };
+        if !empty {
+            continue;
+        }
+        if let Err(err) = async_std::fs::remove_dir(dir).await {
+            log::warn!("failed to delete {:?}: {:#}", dir, err);
+        }
+    }
+
+    Ok(freed)
+}
+
 pub fn full_title(id: MediaId, library: &Library) -> String {
     library
         .get(id)
@@ -127,6 +333,137 @@ pub fn find_all_episodes(
     find_seasons(series, library).flat_map(|(season, _)| find_episodes(*season, library))
 }
 
+/// Episodes the provider lists for `season` that aren't in the library, whether aired or not.
+pub fn missing_episodes(season: MediaId, library: &Library) -> Vec<&EpisodeMetadata> {
+    let Some(Media::Season(metadata)) = library.get(season) else {
+        return vec![];
+    };
+    let local = find_episodes(season, library)
+        .map(|(_, episode)| episode.metadata.episode)
+        .collect_vec();
+    metadata
+        .metadata
+        .episodes
+        .iter()
+        .filter(|episode| !local.contains(&episode.episode))
+        .collect()
+}
+
+/// How many episodes of a series or season have aired without being in the library. A series
+/// doesn't count its missing specials.
+pub fn missing_count(id: MediaId, library: &Library) -> usize {
+    let today = chrono::Local::now().date_naive();
+    let count = |season: MediaId| {
+        missing_episodes(season, library)
+            .into_iter()
+            .filter(|episode| episode.aired.is_some_and(|aired| aired <= today))
+            .count()
+    };
+    match library.get(id) {
+        Some(Media::Series(_)) => find_seasons(id, library)
+            .filter(|(_, season)| season.metadata.season != 0)
+            .map(|(season, _)| count(*season))
+            .sum(),
+        Some(Media::Season(_)) => count(id),
+        _ => 0,
+    }
+}
+
+/// An episode on the calendar.
+#[derive(Debug, Clone, Copy)]
+pub struct CalendarEntry<'a> {
+    pub series: MediaId,
+    pub episode: &'a EpisodeMetadata,
+    /// The episode's media, if it's in the library.
+    pub media: Option<MediaId>,
+}
+
+/// Episodes of every series in the library airing from `from` to `to` inclusive, by air date.
+/// They come from the episode lists kept with seasons and each series' next episode, so this
+/// needs no network.
+pub fn calendar(
+    library: &Library,
+    from: chrono::NaiveDate,
+    to: chrono::NaiveDate,
+) -> Vec<CalendarEntry<'_>> {
+    let in_range = |episode: &EpisodeMetadata| {
+        episode
+            .aired
+            .is_some_and(|aired| aired >= from && aired <= to)
+    };
+
+    let mut entries = vec![];
+    for (id, media) in library.iter() {
+        match media {
+            Media::Season(season) => {
+                entries.extend(
+                    season
+                        .metadata
+                        .episodes
+                        .iter()
+                        .filter(|episode| in_range(episode))
+                        .map(|episode| CalendarEntry {
+                            series: season.series,
+                            episode,
+                            media: find_episodes(*id, library)
+                                .find(|(_, local)| local.metadata.episode == episode.episode)
+                                .map(|(id, _)| *id),
+                        }),
+                );
+            }
+            Media::Series(series) => {
+                // only needed when its season isn't in the library yet
+                let Some(episode) = series
+                    .metadata
+                    .next_episode
+                    .as_ref()
+                    .filter(|episode| in_range(episode))
+                else {
+                    continue;
+                };
+                let listed = find_seasons(*id, library)
+                    .any(|(_, season)| season.metadata.season == episode.season);
+                if !listed {
+                    entries.push(CalendarEntry {
+                        series: *id,
+                        episode,
+                        media: None,
+                    });
+                }
+            }
+            _ => {}
+        }
+    }
+
+    entries.sort_by_key(|entry| {
+        (
+            entry.episode.aired,
+            library.get(entry.series).map(Media::title),
+            entry.episode.season,
+            entry.episode.episode,
+        )
+    });
+    entries
+}
+
+/// Every video file that makes up `id`; all episodes for series and seasons.
+pub fn video_paths(id: MediaId, library: &Library) -> Vec<PathBuf> {
+    match library.get(id) {
+        Some(Media::Series(_)) => find_all_episodes(id, library)
+            .map(|(_, episode)| episode.video.path.clone())
+            .collect(),
+        Some(Media::Season(_)) => find_episodes(id, library)
+            .map(|(_, episode)| episode.video.path.clone())
+            .collect(),
+        Some(media) => media
+            .video()
+            .map(|video| video.path.clone())
+            .into_iter()
+            .collect(),
+        None => vec![],
+    }
+}
+
 pub fn calculate_season_watched(season: MediaId, library: &Library) -> Watched {
     let (count, percent_sum) =
         find_episodes(season, library).fold((0, 0.0), |(count, percent_sum), (_, episode)| {
@@ -145,14 +482,23 @@ pub fn calculate_season_watched(season: MediaId, library: &Library) -> Watched {
     }
 }
 
+/// Specials don't count towards a series' progress, unless they're all it has.
 pub fn calculate_series_watched(series: MediaId, library: &Library) -> Watched {
+    let mut seasons = find_seasons(series, library)
+        .filter(|(_, season)| season.metadata.season != 0)
+        .collect_vec();
+    if seasons.is_empty() {
+        seasons = find_seasons(series, library).collect_vec();
+    }
     let (count, percent_sum) =
-        find_seasons(series, library).fold((0, 0.0), |(count, percent_sum), (season, _)| {
-            (
-                count + 1,
-                percent_sum + calculate_season_watched(*season, library).percent(),
-            )
-        });
+        seasons
+            .into_iter()
+            .fold((0, 0.0), |(count, percent_sum), (season, _)| {
+                (
+                    count + 1,
+                    percent_sum + calculate_season_watched(*season, library).percent(),
+                )
+            });
     let total = percent_sum / count as f32;
     if total < f32::EPSILON {
         Watched::No
@@ -179,38 +525,126 @@ pub fn calculate_watched(id: MediaId, library: &Library) -> Option<Watched> {
     })
 }
 
-pub fn previous_in_list(id: MediaId, library: &Library) -> Option<MediaId> {
+/// The regular episode a special aired after, as a season and episode number, or `(0, 0)` if it
+/// aired before all of them. `None` when its air date isn't known.
+pub fn special_placement(special: &Episode, library: &Library) -> Option<(u16, u16)> {
+    let aired = special.metadata.aired?;
+    let listed = find_seasons(special.series, library)
+        .flat_map(|(_, season)| &season.metadata.episodes)
+        .map(|episode| (episode.season, episode.episode, episode.aired));
+    let local = find_all_episodes(special.series, library).map(|(_, episode)| {
+        (
+            episode.metadata.season,
+            episode.metadata.episode,
+            episode.metadata.aired,
+        )
+    });
+    Some(
+        listed
+            .chain(local)
+            .filter(|(season, _, other)| *season != 0 && other.is_some_and(|other| other <= aired))
+            .map(|(season, episode, _)| (season, episode))
+            .max()
+            .unwrap_or((0, 0)),
+    )
+}
+
+/// The episodes of `series` in the order they're watched in. Specials are left out, unless
+/// `specials` is set, which places each after the episode it aired after.
+pub fn watch_order(
+    series: MediaId,
+    library: &Library,
+    specials: bool,
+) -> Vec<(&MediaId, &Episode)> {
+    find_all_episodes(series, library)
+        .filter_map(|(id, episode)| {
+            let key = match episode.metadata.season {
+                0 => {
+                    let (season, number) =
+                        special_placement(episode, library).filter(|_| specials)?;
+                    (season, number, 1, episode.metadata.episode)
+                }
+                season => (season, episode.metadata.episode, 0, 0),
+            };
+            Some((key, (id, episode)))
+        })
+        .sorted_unstable_by_key(|(key, _)| *key)
+        .map(|(_, episode)| episode)
+        .collect()
+}
+
+/// The episodes to step through from `episode`; specials skipped by the watch order still step
+/// through the other specials.
+fn neighbours<'a>(
+    id: MediaId,
+    episode: &Episode,
+    library: &'a Library,
+    specials: bool,
+) -> Vec<(&'a MediaId, &'a Episode)> {
+    let mut episodes = watch_order(episode.series, library, specials);
+    if !episodes.iter().any(|(other, _)| **other == id) {
+        episodes = find_all_episodes(episode.series, library)
+            .filter(|(_, episode)| episode.metadata.season == 0)
+            .sorted_unstable_by_key(|(_, episode)| episode.metadata.episode)
+            .collect();
+    }
+    episodes
+}
+
+pub fn previous_in_list(id: MediaId, library: &Library, specials: bool) -> Option<MediaId> {
     library.get(id).and_then(|media| match media {
         Media::Episode(episode) => {
-            let se = (episode.metadata.season, episode.metadata.episode);
-            let mut episodes = find_all_episodes(episode.series, library)
-                .filter(|(_, e)| (e.metadata.season, e.metadata.episode) < se)
-                .collect_vec();
-            episodes.sort_unstable_by_key(|(_, episode)| {
-                (episode.metadata.season, episode.metadata.episode)
-            });
-            episodes.last().map(|(id, _)| **id)
+            let episodes = neighbours(id, episode, library, specials);
+            let i = episodes.iter().position(|(other, _)| **other == id)?;
+            i.checked_sub(1).map(|i| *episodes[i].0)
         }
         _ => None,
     })
 }
 
-pub fn next_in_list(id: MediaId, library: &Library) -> Option<MediaId> {
+pub fn next_in_list(id: MediaId, library: &Library, specials: bool) -> Option<MediaId> {
     library.get(id).and_then(|media| match media {
         Media::Episode(episode) => {
-            let se = (episode.metadata.season, episode.metadata.episode);
-            let mut episodes = find_all_episodes(episode.series, library)
-                .filter(|(_, e)| (e.metadata.season, e.metadata.episode) > se)
-                .collect_vec();
-            episodes.sort_unstable_by_key(|(_, episode)| {
-                (episode.metadata.season, episode.metadata.episode)
-            });
-            episodes.first().map(|(id, _)| **id)
+            let episodes = neighbours(id, episode, library, specials);
+            let i = episodes.iter().position(|(other, _)| **other == id)?;
+            episodes.get(i + 1).map(|(id, _)| **id)
         }
         _ => None,
     })
 }
 
+/// The episode to continue a series with: the most recently watched episode if it is unfinished,
+/// otherwise the next unwatched one after it, falling back to the first unwatched episode.
+/// Specials only come up when `specials` places them in the watch order.
+pub fn up_next(series: MediaId, library: &Library, specials: bool) -> Option<MediaId> {
+    let episodes = watch_order(series, library, specials);
+
+    if let Some((last_id, last)) = episodes
+        .iter()
+        .copied()
+        .max_by_key(|(_, episode)| episode.video.last_watched)
+        .filter(|(_, episode)| episode.video.last_watched.is_some())
+    {
+        if matches!(last.video.watched, Watched::Partial { .. }) {
+            return Some(*last_id);
+        }
+
+        let next = episodes
+            .iter()
+            .skip_while(|(id, _)| *id != last_id)
+            .skip(1)
+            .find(|(_, episode)| episode.video.watched != Watched::Yes);
+        if let Some((id, _)) = next {
+            return Some(**id);
+        }
+    }
+
+    episodes
+        .iter()
+        .find(|(_, episode)| episode.video.watched != Watched::Yes)
+        .map(|(id, _)| **id)
+}
+
 pub fn season_last_watched(season: MediaId, library: &Library) -> Option<(&MediaId, &Episode)> {
     find_episodes(season, library).max_by_key(|(_, episode)| episode.video.last_watched)
 }
@@ -279,3 +713,140 @@ pub fn set_watched(id: MediaId, value: Watched, library: &mut Library) {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn metadata(season: u16, episode: u16, aired: Option<&str>) -> EpisodeMetadata {
+        EpisodeMetadata {
+            provider: Provider::Tmdb,
+            series_provider_id: 1,
+            title: format!("S{:02}E{:02}", season, episode),
+            season,
+            episode,
+            aired: aired.map(|aired| aired.parse().unwrap()),
+            overview: None,
+            runtime: None,
+            still: None,
+            locked: Default::default(),
+        }
+    }
+
+    /// An episode number and air date.
+    type Aired = (u16, Option<&'static str>);
+
+    /// A series with two seasons of episodes and specials that aired before, between and after
+    /// them, or on an unknown date. Season 1 lists an episode that isn't in the library.
+    fn library() -> (Library, MediaId) {
+        let mut library = Library::new();
+        let series = library.insert(Media::Series(Series {
+            metadata: SeriesMetadata {
+                provider: Provider::Tmdb,
+                provider_id: 1,
+                title: String::from("Show"),
+                poster: None,
+                backdrop: None,
+                logo: None,
+                aired: None,
+                details: Details::default(),
+                next_episode: None,
+                locked: Default::default(),
+            },
+            episode_order: EpisodeOrder::Aired,
+        }));
+
+        let seasons: [(u16, &[Aired], &[Aired]); 3] = [
+            (
+                0,
+                &[
+                    (1, Some("2020-01-05")),
+                    (2, Some("2019-12-01")),
+                    (3, None),
+                    (4, Some("2020-01-20")),
+                ],
+                &[],
+            ),
+            (
+                1,
+                &[(1, Some("2020-01-01")), (2, Some("2020-01-08"))],
+                &[(3, Some("2020-01-15"))],
+            ),
+            (2, &[(1, Some("2021-01-01"))], &[]),
+        ];
+        for (number, episodes, unowned) in seasons {
+            let season = library.insert(Media::Season(Season {
+                metadata: SeasonMetadata {
+                    provider: Provider::Tmdb,
+                    series_provider_id: 1,
+                    title: format!("Season {}", number),
+                    season: number,
+                    poster: None,
+                    aired: None,
+                    overview: None,
+                    episodes: episodes
+                        .iter()
+                        .chain(unowned)
+                        .map(|(episode, aired)| metadata(number, *episode, *aired))
+                        .collect(),
+                    locked: Default::default(),
+                },
+                series,
+            }));
+            for (episode, aired) in episodes {
+                library.insert(Media::Episode(Episode {
+                    video: Video {
+                        path: PathBuf::from(format!("S{:02}E{:02}.mkv", number, episode)),
+                        watched: Watched::No,
+                        added: chrono::Local::now(),
+                        last_watched: None,
+                    },
+                    series,
+                    season,
+                    metadata: metadata(number, *episode, *aired),
+                }));
+            }
+        }
+        (library, series)
+    }
+
+    fn titles(episodes: Vec<(&MediaId, &Episode)>) -> Vec<String> {
+        episodes
+            .into_iter()
+            .map(|(_, episode)| episode.metadata.title.clone())
+            .collect()
+    }
+
+    #[test]
+    fn places_specials_by_air_date() {
+        let (library, series) = library();
+        let special = |number| {
+            find_all_episodes(series, &library)
+                .find(|(_, episode)| {
+                    (episode.metadata.season, episode.metadata.episode) == (0, number)
+                })
+                .unwrap()
+                .1
+        };
+
+        assert_eq!(special_placement(special(1), &library), Some((1, 1)));
+        assert_eq!(special_placement(special(2), &library), Some((0, 0)));
+        assert_eq!(special_placement(special(3), &library), None);
+        // after the listed episode, though it isn't in the library
+        assert_eq!(special_placement(special(4), &library), Some((1, 3)));
+    }
+
+    #[test]
+    fn orders_episodes_for_watching() {
+        let (library, series) = library();
+
+        assert_eq!(
+            titles(watch_order(series, &library, false)),
+            ["S01E01", "S01E02", "S02E01"]
+        );
+        assert_eq!(
+            titles(watch_order(series, &library, true)),
+            ["S00E02", "S01E01", "S00E01", "S01E02", "S00E04", "S02E01"]
+        );
+    }
+}
@@ -1,8 +1,15 @@
+use crate::library::{MediaKind, Provider};
+use rustc_hash::FxHashMap;
 use serde::{Deserialize, Serialize};
 use std::path::{Path, PathBuf};
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct UserSettings {
+    /// Whether the first-run setup was finished or skipped. Settings saved before there was one
+    /// were set up by hand.
+    #[serde(default = "default_onboarded")]
+    pub onboarded: bool,
+
     pub tmdb_secret: String,
     pub directories: Vec<PathBuf>,
 
@@ -15,11 +22,90 @@ pub struct UserSettings {
 
     /// In seconds
     pub thumbnail_interval: u32,
+
+    /// Slots specials in among a series' episodes by air date when playing through it, rather
+    /// than leaving them out.
+    #[serde(default)]
+    pub specials_in_airing_order: bool,
+
+    /// How often to refresh the metadata of the whole library, in days. Zero disables it.
+    #[serde(default)]
+    pub refresh_interval_days: u32,
+
+    /// IETF language tag metadata is requested in, such as `de-DE`.
+    #[serde(default = "default_language")]
+    pub metadata_language: String,
+    /// Used where no translation into the preferred language exists. Empty disables it.
+    #[serde(default = "default_language")]
+    pub fallback_language: String,
+    /// ISO 3166-1 country whose certifications and release dates are shown.
+    #[serde(default = "default_region")]
+    pub metadata_region: String,
+    /// Overrides `metadata_language` for everything inside a library directory.
+    #[serde(default)]
+    pub directory_languages: FxHashMap<PathBuf, String>,
+
+    /// Replaces the TMDB API base, e.g. with a caching proxy. Empty uses TMDB itself.
+    #[serde(default)]
+    pub tmdb_api_base: String,
+    /// Replaces the TMDB image base. Empty uses TMDB itself.
+    #[serde(default)]
+    pub tmdb_image_base: String,
+
+    #[serde(default)]
+    pub omdb_key: String,
+    #[serde(default)]
+    pub tvdb_key: String,
+    /// Providers asked about movies, in order, until one has a confident match. Ones missing
+    /// from the list are never asked.
+    #[serde(default = "default_movie_providers")]
+    pub movie_providers: Vec<Provider>,
+    #[serde(default = "default_series_providers")]
+    pub series_providers: Vec<Provider>,
+}
+
+pub const API_BASE_VAR: &str = "JANGAL_TMDB_API_BASE";
+pub const IMAGE_BASE_VAR: &str = "JANGAL_TMDB_IMAGE_BASE";
+/// A directory to record every provider response and image into, e.g. to reproduce a mismatch.
+pub const RECORD_FIXTURES_VAR: &str = "JANGAL_RECORD_FIXTURES";
+/// A recording to answer every provider request from, without touching the network.
+pub const REPLAY_FIXTURES_VAR: &str = "JANGAL_REPLAY_FIXTURES";
+
+/// An environment variable takes precedence over the setting, so test setups don't need to touch
+/// the user's settings file.
+fn endpoint(var: &str, setting: &str) -> Option<String> {
+    std::env::var(var)
+        .ok()
+        .filter(|base| !base.trim().is_empty())
+        .or_else(|| (!setting.trim().is_empty()).then(|| setting.trim().to_string()))
+}
+
+fn default_onboarded() -> bool {
+    true
+}
+
+fn default_language() -> String {
+    String::from("en-US")
+}
+
+fn default_region() -> String {
+    String::from("US")
+}
+
+fn default_movie_providers() -> Vec<Provider> {
+    vec![Provider::Tmdb]
+}
+
+/// Other providers are opt-in, so existing series don't start matching against them unasked.
+fn default_series_providers() -> Vec<Provider> {
+    vec![Provider::Tmdb]
 }
 
 impl UserSettings {
     pub fn new() -> Self {
         UserSettings {
+            onboarded: false,
+
             tmdb_secret: String::new(),
             directories: vec![],
 
@@ -31,9 +117,61 @@ impl UserSettings {
             subtitle_size: 24.0,
 
             thumbnail_interval: 300,
+
+            specials_in_airing_order: false,
+
+            refresh_interval_days: 0,
+
+            metadata_language: default_language(),
+            fallback_language: default_language(),
+            metadata_region: default_region(),
+            directory_languages: FxHashMap::default(),
+
+            tmdb_api_base: String::new(),
+            tmdb_image_base: String::new(),
+
+            omdb_key: String::new(),
+            tvdb_key: String::new(),
+            movie_providers: default_movie_providers(),
+            series_providers: default_series_providers(),
+        }
+    }
+
+    /// The TMDB API base to use instead of the default, if any.
+    pub fn api_base(&self) -> Option<String> {
+        endpoint(API_BASE_VAR, &self.tmdb_api_base)
+    }
+
+    /// The TMDB image base to use instead of the default, if any.
+    pub fn image_base(&self) -> Option<String> {
+        endpoint(IMAGE_BASE_VAR, &self.tmdb_image_base)
+    }
+
+    /// The provider order for `kind`.
+    pub fn providers(&self, kind: MediaKind) -> &[Provider] {
+        match kind {
+            MediaKind::Movie => &self.movie_providers,
+            MediaKind::Series => &self.series_providers,
         }
     }
 
+    pub fn providers_mut(&mut self, kind: MediaKind) -> &mut Vec<Provider> {
+        match kind {
+            MediaKind::Movie => &mut self.movie_providers,
+            MediaKind::Series => &mut self.series_providers,
+        }
+    }
+
+    /// The metadata language for a video, from the innermost directory with its own language.
+    pub fn language_for(&self, path: &Path) -> &str {
+        self.directory_languages
+            .iter()
+            .filter(|(directory, language)| path.starts_with(directory) && !language.is_empty())
+            .max_by_key(|(directory, _)| directory.components().count())
+            .map(|(_, language)| language.as_str())
+            .unwrap_or(&self.metadata_language)
+    }
+
     pub fn load(storage: &Path) -> Self {
         std::fs::File::open(storage.join("user.json"))
             .ok()
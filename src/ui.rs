@@ -4,13 +4,21 @@ pub mod screen;
 
 pub use menu_button::menu_button;
 
-use crate::{library, settings::UserSettings, ui::screen::home::cards};
-use iced::widget::{button, checkbox, container, row, scrollable, text, text_input};
+use crate::{
+    library,
+    settings::{self, UserSettings},
+    ui::screen::home::cards,
+};
+use iced::widget::{
+    button, center, checkbox, container, mouse_area, opaque, row, scrollable, text, text_input,
+};
+use rustc_hash::{FxHashMap, FxHashSet};
 use std::{
     collections::VecDeque,
     future::Future,
     path::{Path, PathBuf},
     process::Command,
+    sync::Arc,
 };
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
@@ -18,6 +26,8 @@ pub enum Tab {
     Home,
     Movies,
     TvShows,
+    Calendar,
+    Movie(library::MediaId),
     TvShow(library::MediaId),
     Season(library::MediaId),
     Collection(library::CollectionId),
@@ -27,10 +37,10 @@ impl Tab {
     pub fn overwrites(&self, other: &Tab) -> bool {
         matches!(
             self,
-            Tab::Home | Tab::Movies | Tab::TvShows | Tab::Collection(_)
+            Tab::Home | Tab::Movies | Tab::TvShows | Tab::Calendar | Tab::Collection(_)
         ) && matches!(
             other,
-            Tab::Home | Tab::Movies | Tab::TvShows | Tab::Collection(_)
+            Tab::Home | Tab::Movies | Tab::TvShows | Tab::Calendar | Tab::Collection(_)
         )
     }
 }
@@ -48,10 +58,153 @@ pub struct AppState {
 
     pub card_cache: cards::Cache,
     pub library_status: LibraryStatus,
+    /// Whether unused artwork is being deleted, during which nothing may be identified or
+    /// imported, as its artwork could be taken for unused.
+    pub collecting_artwork: bool,
     pub tab_stack: VecDeque<Tab>,
+    /// Of the main window, so artwork is downloaded at a size that stays sharp.
+    pub scale_factor: f32,
+    /// What went wrong during the last scan or refresh, until dismissed.
+    pub scrape_report: library::ScrapeReport,
+    /// Providers that turned down their API keys, until the keys are changed.
+    pub rejected_keys: FxHashSet<library::Provider>,
 }
 
 impl AppState {
+    /// A scraper requesting metadata in `language`, with the user's fallback and region.
+    pub fn scraper(&self, language: &str) -> library::TmdbScraper {
+        let mut scraper = library::TmdbScraper::new(&self.settings.tmdb_secret)
+            .cache(library::ResponseCache::new(&self.storage_path))
+            .scale_factor(self.scale_factor)
+            .language(language)
+            .fallback_language(&self.settings.fallback_language)
+            .region(&self.settings.metadata_region);
+        if let Some(api_base) = self.settings.api_base() {
+            scraper = scraper.api_base(&api_base);
+        }
+        if let Some(image_base) = self.settings.image_base() {
+            scraper = scraper.image_base(&image_base);
+        }
+        if let Some(fixtures) = fixtures() {
+            scraper = scraper.fixtures(fixtures);
+        }
+        scraper
+    }
+
+    /// The scraper for one provider, unless it needs an API key that isn't set. Replaying
+    /// fixtures doesn't need keys.
+    fn provider_scraper(
+        &self,
+        provider: library::Provider,
+        language: &str,
+    ) -> Option<Arc<dyn library::Scraper>> {
+        let fixtures = fixtures();
+        let key: &str = match provider {
+            library::Provider::Tmdb => &self.settings.tmdb_secret,
+            library::Provider::Tvmaze => "",
+            library::Provider::Omdb => &self.settings.omdb_key,
+            library::Provider::Tvdb => &self.settings.tvdb_key,
+            library::Provider::Local => return None,
+        };
+        let replaying = fixtures
+            .as_ref()
+            .is_some_and(library::Fixtures::is_replaying);
+        if provider.needs_key() && key.trim().is_empty() && !replaying {
+            return None;
+        }
+
+        let cache = library::ResponseCache::new(&self.storage_path);
+        let scraper: Arc<dyn library::Scraper> = match (provider, fixtures) {
+            (library::Provider::Tmdb, _) => Arc::new(self.scraper(language)),
+            (library::Provider::Tvmaze, fixtures) => {
+                let scraper = library::TvmazeScraper::new().cache(cache);
+                Arc::new(match fixtures {
+                    Some(fixtures) => scraper.fixtures(fixtures),
+                    None => scraper,
+                })
+            }
+            (library::Provider::Omdb, fixtures) => {
+                let scraper = library::OmdbScraper::new(key).cache(cache);
+                Arc::new(match fixtures {
+                    Some(fixtures) => scraper.fixtures(fixtures),
+                    None => scraper,
+                })
+            }
+            (library::Provider::Tvdb, fixtures) => {
+                let scraper = library::TvdbScraper::new(key).cache(cache);
+                Arc::new(match fixtures {
+                    Some(fixtures) => scraper.fixtures(fixtures),
+                    None => scraper,
+                })
+            }
+            (library::Provider::Local, _) => return None,
+        };
+        Some(scraper)
+    }
+
+    /// Every provider that is set up, in the user's order for movies and for series.
+    pub fn scraper_chain(&self, language: &str) -> library::ScraperChain {
+        let mut scrapers: FxHashMap<library::Provider, Option<Arc<dyn library::Scraper>>> =
+            FxHashMap::default();
+        let mut chain = |kind: library::MediaKind| {
+            self.settings
+                .providers(kind)
+                .iter()
+                .filter(|provider| provider.supports(kind))
+                .filter_map(|&provider| {
+                    scrapers
+                        .entry(provider)
+                        .or_insert_with(|| self.provider_scraper(provider, language))
+                        .clone()
+                })
+                .collect::<Vec<_>>()
+        };
+        let movies = chain(library::MediaKind::Movie);
+        let series = chain(library::MediaKind::Series);
+        library::ScraperChain::new(movies, series)
+    }
+
+    /// The providers, for whatever local sidecars don't describe. With none set up scraping
+    /// stays entirely offline.
+    pub fn fallback_scraper(&self, language: &str) -> Option<library::ScraperChain> {
+        Some(self.scraper_chain(language)).filter(|chain| !chain.is_empty())
+    }
+
+    /// The metadata language for `id`, going by the directory its video is in. Series and seasons
+    /// go by their first episode.
+    pub fn language_of(&self, id: library::MediaId) -> &str {
+        let path = match self.library.get(id) {
+            Some(library::Media::Series(_)) => library::find_all_episodes(id, &self.library)
+                .next()
+                .map(|(_, episode)| &episode.video.path),
+            Some(library::Media::Season(_)) => library::find_episodes(id, &self.library)
+                .next()
+                .map(|(_, episode)| &episode.video.path),
+            Some(media) => media.video().map(|video| &video.path),
+            None => None,
+        };
+
+        match path {
+            Some(path) => self.settings.language_for(path),
+            None => &self.settings.metadata_language,
+        }
+    }
+
+    /// Drops tabs whose media or collection no longer exists, e.g. after a series was emptied.
+    pub fn prune_tabs(&mut self) {
+        let library = &self.library;
+        self.tab_stack.retain(|tab| match tab {
+            Tab::Movie(id) => matches!(library.get(*id), Some(library::Media::Movie(_))),
+            Tab::TvShow(id) => matches!(library.get(*id), Some(library::Media::Series(_))),
+            Tab::Season(id) => matches!(library.get(*id), Some(library::Media::Season(_))),
+            Tab::Collection(id) => library.collection(*id).is_some(),
+            _ => true,
+        });
+        if self.tab_stack.is_empty() {
+            self.tab_stack.push_back(Tab::Home);
+        }
+    }
+
     pub fn save_library<'a, 'b>(&'a self) -> impl Future<Output = anyhow::Result<()>> + 'b {
         let library = self.library.clone();
         let storage_path = self.storage_path.clone();
@@ -71,6 +224,21 @@ impl AppState {
     }
 }
 
+/// Fixtures to record into or replay from, as set up by the environment.
+fn fixtures() -> Option<library::Fixtures> {
+    [
+        (settings::REPLAY_FIXTURES_VAR, library::FixtureMode::Replay),
+        (settings::RECORD_FIXTURES_VAR, library::FixtureMode::Record),
+    ]
+    .into_iter()
+    .find_map(|(var, mode)| {
+        Some(library::Fixtures::new(
+            Path::new(&std::env::var_os(var)?),
+            mode,
+        ))
+    })
+}
+
 pub const SANS_FONT: iced::Font = iced::Font {
     family: iced::font::Family::SansSerif,
     weight: iced::font::Weight::Normal,
@@ -102,8 +270,12 @@ pub const SUBTITLE_FONT: iced::Font = iced::Font {
 };
 
 pub fn truncate_text(text: &str, max_len: usize) -> String {
-    if text.len() > max_len {
-        format!("{}...", text[..max_len - 3].trim_end())
+    if text.chars().count() > max_len {
+        let end = text
+            .char_indices()
+            .nth(max_len - 3)
+            .map_or(text.len(), |(i, _)| i);
+        format!("{}...", text[..end].trim_end())
     } else {
         text.into()
     }
@@ -309,6 +481,23 @@ where
     .into()
 }
 
+/// Overlays `content` on a dimmed backdrop which emits `on_blur` when clicked.
+pub fn modal<'a, Message>(
+    content: impl Into<iced::Element<'a, Message>>,
+    on_blur: Message,
+) -> iced::Element<'a, Message>
+where
+    Message: 'a + Clone,
+{
+    opaque(
+        mouse_area(center(opaque(content)).style(|_| container::Style {
+            background: Some(iced::Background::Color(iced::Color::BLACK.scale_alpha(0.7))),
+            ..Default::default()
+        }))
+        .on_press(on_blur),
+    )
+}
+
 pub fn find_focused_maybe()
 -> impl iced::advanced::widget::Operation<Option<iced::advanced::widget::Id>> {
     use iced::advanced::widget::{
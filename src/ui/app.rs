@@ -5,12 +5,19 @@ use super::{
     screen::{self, Screen, cards},
 };
 use crate::{library, settings::UserSettings};
-use std::{collections::VecDeque, time::Instant};
+use itertools::Itertools;
+use std::{
+    collections::VecDeque,
+    time::{Duration, Instant},
+};
 
 pub struct App {
     now: Instant,
     screen: AppScreen,
     state: AppState,
+    /// Media to refresh once the running scan or refresh is done, as it was asked for meanwhile
+    /// or its failures are being retried along with the scan's.
+    refresh_after_scan: Vec<library::MediaId>,
 }
 
 impl App {
@@ -21,28 +28,51 @@ impl App {
             .to_path_buf();
         std::fs::create_dir_all(&storage_path).expect("mkdir");
 
-        let library = library::Library::load(&storage_path);
+        let mut library = library::Library::load(&storage_path);
+        if library::migrate_artwork(&mut library, &storage_path) {
+            library.save(&storage_path).unwrap();
+        }
         let settings = UserSettings::load(&storage_path);
 
         let (card_cache, cache_task) = cards::Cache::build(&library);
 
-        let (screen, screen_task) = screen::Home::new();
+        let state = AppState {
+            storage_path,
+            library,
+            settings,
+
+            card_cache,
+            library_status: LibraryStatus::Idle,
+            collecting_artwork: false,
+            tab_stack: VecDeque::from([Tab::Home]),
+            scale_factor: 1.0,
+            scrape_report: library::ScrapeReport::default(),
+            rejected_keys: Default::default(),
+        };
+
+        let (screen, screen_task) = if state.settings.onboarded {
+            let (screen, task) = screen::Home::new();
+            (AppScreen::Home(screen), task.map(Message::Home))
+        } else {
+            let (screen, task) = screen::Onboarding::new(&state);
+            (AppScreen::Onboarding(screen), task.map(Message::Onboarding))
+        };
 
         (
             App {
                 now: Instant::now(),
-                screen: AppScreen::Home(screen),
-                state: AppState {
-                    storage_path,
-                    library,
-                    settings,
-
-                    card_cache,
-                    library_status: LibraryStatus::Idle,
-                    tab_stack: VecDeque::from([Tab::Home]),
-                },
+                screen,
+                state,
+                refresh_after_scan: vec![],
             },
-            iced::Task::batch([cache_task, screen_task.map(Message::Home)]),
+            iced::Task::batch([
+                cache_task,
+                screen_task,
+                iced::Task::done(Message::CheckScheduledRefresh),
+                iced::window::latest()
+                    .and_then(iced::window::scale_factor)
+                    .map(Message::ScaleFactorChanged),
+            ]),
         )
     }
 
@@ -52,12 +82,24 @@ impl App {
                 AppScreen::Home(screen) => screen.subscription(self.now).map(Message::Home),
                 AppScreen::Player(screen) => screen.subscription(self.now).map(Message::Player),
                 AppScreen::Settings(screen) => screen.subscription(self.now).map(Message::Settings),
+                AppScreen::Onboarding(screen) => {
+                    screen.subscription(self.now).map(Message::Onboarding)
+                }
             },
             iced::event::listen_with(|event, _, _| match event {
                 iced::Event::Window(iced::window::Event::CloseRequested) => Some(Message::Exit),
+                iced::Event::Window(iced::window::Event::Rescaled(scale_factor)) => {
+                    Some(Message::ScaleFactorChanged(scale_factor))
+                }
                 _ => None,
             }),
             self.state.card_cache.subscription(self.now),
+            if self.state.settings.refresh_interval_days > 0 {
+                iced::time::every(Duration::from_secs(60 * 60))
+                    .map(|_| Message::CheckScheduledRefresh)
+            } else {
+                iced::Subscription::none()
+            },
         ])
     }
 
@@ -81,7 +123,39 @@ impl App {
                     iced::Task::done(Message::Purge { scan: true })
                 }
                 screen::HomeAction::ForceScan => iced::Task::done(Message::Scrape { force: true }),
+                screen::HomeAction::RefreshMetadata => iced::Task::done(Message::Refresh(None)),
             },
+            Message::Home(screen::HomeMessage::RefreshMetadata(id)) => {
+                iced::Task::done(Message::Refresh(Some(vec![id])))
+            }
+            Message::Home(screen::HomeMessage::RetryScrape) => {
+                if self.state.library_status == LibraryStatus::Scanning {
+                    return iced::Task::none();
+                }
+                let report = std::mem::take(&mut self.state.scrape_report);
+                // media that never got identified is picked up by a scan, the rest is refreshed
+                let (unidentified, identified): (Vec<_>, Vec<_>) =
+                    report.retry.into_iter().partition(|id| {
+                        matches!(
+                            self.state.library.get(*id),
+                            Some(library::Media::Uncategorised(_))
+                        )
+                    });
+                if unidentified.is_empty() {
+                    return iced::Task::done(Message::Refresh(Some(identified)));
+                }
+                self.refresh_after_scan.extend(identified);
+                iced::Task::done(Message::Scrape { force: false })
+            }
+            Message::Home(screen::HomeMessage::LibraryChanged) => {
+                self.state.prune_tabs();
+                let (card_cache, cache_task) = cards::Cache::build(&self.state.library);
+                self.state.card_cache = card_cache;
+                iced::Task::batch([
+                    cache_task,
+                    iced::Task::perform(self.state.save_library(), |_| ()).discard(),
+                ])
+            }
             Message::Player(screen::PlayerMessage::Back) => {
                 let (screen, task) = screen::Home::new();
                 self.screen = AppScreen::Home(screen);
@@ -99,6 +173,43 @@ impl App {
                     task.map(Message::Home),
                 ])
             }
+            Message::Settings(screen::SettingsMessage::ArtworkCollected(freed)) => {
+                // the screen may have been left while the artwork was being collected
+                self.state.collecting_artwork = false;
+                let idle = self.idle();
+                let AppScreen::Settings(settings) = &mut self.screen else {
+                    return idle;
+                };
+                let task = settings
+                    .update(
+                        screen::SettingsMessage::ArtworkCollected(freed),
+                        &mut self.state,
+                        self.now,
+                    )
+                    .map(Message::Settings);
+                iced::Task::batch([idle, task])
+            }
+            Message::Onboarding(screen::OnboardingMessage::StartScan) => iced::Task::batch([
+                iced::Task::perform(self.state.save_settings(), |_| ()).discard(),
+                iced::Task::done(Message::Purge { scan: true }),
+            ]),
+            Message::Onboarding(screen::OnboardingMessage::Finish) => {
+                self.state.settings.onboarded = true;
+                let (screen, task) = screen::Home::new();
+                self.screen = AppScreen::Home(screen);
+                iced::Task::batch([
+                    iced::Task::perform(self.state.save_settings(), |_| ()).discard(),
+                    task.map(Message::Home),
+                ])
+            }
+            Message::Onboarding(message) => {
+                let AppScreen::Onboarding(screen) = &mut self.screen else {
+                    return iced::Task::none();
+                };
+                screen
+                    .update(message, &mut self.state, self.now)
+                    .map(Message::Onboarding)
+            }
             Message::Home(message) => {
                 let AppScreen::Home(screen) = &mut self.screen else {
                     return iced::Task::none();
@@ -124,6 +235,9 @@ impl App {
                     .map(|message| Message::Player(message))
             }
             Message::Purge { scan } => {
+                if self.state.library_status == LibraryStatus::Scanning {
+                    return iced::Task::none();
+                }
                 self.state.library_status = LibraryStatus::Scanning;
 
                 let existing: Vec<_> = self
@@ -153,67 +267,158 @@ impl App {
                 )
             }
             Message::Scrape { force } => {
-                self.state.library_status = LibraryStatus::Scanning;
+                // a second scrape would race the running one over the same media
+                if self.state.library_status == LibraryStatus::Scanning {
+                    return iced::Task::none();
+                }
+                self.scrape(force)
+            }
+            Message::Refresh(ids) => {
+                if self.state.library_status == LibraryStatus::Scanning {
+                    // refreshes of particular media are asked for by the user, so they wait
+                    if let Some(ids) = ids {
+                        self.refresh_after_scan.extend(ids);
+                    }
+                    return iced::Task::none();
+                }
 
-                let storage = self.state.storage_path.clone();
-                let tmdb_secret = self.state.settings.tmdb_secret.clone();
-                let media: Vec<_> = self
-                    .state
-                    .library
-                    .iter_mut()
-                    .filter_map(|(id, media)| match media {
-                        library::Media::Uncategorised(uncategorised)
-                            if force || !uncategorised.dont_scrape =>
-                        {
-                            uncategorised.dont_scrape = true;
-                            Some((
-                                *id,
-                                uncategorised.video.path.file_name()?.to_str()?.to_string(),
-                            ))
-                        }
-                        _ => None,
+                let ids: Vec<_> = match ids {
+                    Some(ids) => ids,
+                    None => {
+                        self.state.library.set_last_refreshed(chrono::Local::now());
+                        self.state.library.iter().map(|(id, _)| *id).collect()
+                    }
+                };
+                let jobs: Vec<_> = ids
+                    .into_iter()
+                    .into_group_map_by(|id| self.state.language_of(*id).to_string())
+                    .into_iter()
+                    .map(|(language, ids)| {
+                        let videos = ids
+                            .iter()
+                            .flat_map(|id| library::video_paths(*id, &self.state.library))
+                            .collect_vec();
+                        (
+                            self.state.fallback_scraper(&language),
+                            library::RefreshPlan::new(&self.state.library, ids),
+                            videos,
+                        )
                     })
+                    .filter(|(_, plan, _)| !plan.is_empty())
                     .collect();
+                if jobs.is_empty() {
+                    return iced::Task::none();
+                }
+
+                self.state.library_status = LibraryStatus::Scanning;
+
+                let storage = self.state.storage_path.clone();
 
                 iced::Task::perform(
                     async move {
-                        let scraper = library::TmdbScraper::new(&tmdb_secret);
-                        library::scrape_all(&scraper, &storage, media.into_iter()).await
+                        let mut result = library::ScrapeResult::default();
+                        for (fallback, plan, videos) in jobs {
+                            let sidecars = library::Sidecars::load(videos).await;
+                            let scraper = library::SidecarScraper::new(sidecars, fallback);
+                            result.extend(library::refresh(&scraper, &storage, plan).await);
+                        }
+                        result
                     },
                     Message::ScrapeComplete,
                 )
             }
+            Message::CheckScheduledRefresh => {
+                let days = self.state.settings.refresh_interval_days;
+                let due = days > 0
+                    && self.state.library.last_refreshed().is_none_or(|last| {
+                        chrono::Local::now() - last >= chrono::TimeDelta::days(days as i64)
+                    });
+
+                if due {
+                    iced::Task::done(Message::Refresh(None))
+                } else {
+                    iced::Task::none()
+                }
+            }
             Message::PurgeComplete { scan, removed } => {
                 for id in removed {
                     self.state.library.remove(id);
                 }
-                self.state.library.purge_collections();
+                self.state.library.remove_empty();
+                self.state.prune_tabs();
                 self.state.library.save(&self.state.storage_path).unwrap();
                 let (card_cache, cache_task) = cards::Cache::build(&self.state.library);
                 self.state.card_cache = card_cache;
                 iced::Task::batch([
                     cache_task,
-                    if scan {
+                    iced::Task::done(Message::CollectArtwork { scan }),
+                ])
+            }
+            Message::CollectArtwork { scan } => {
+                // a purge already keeps scans and refreshes away, but what an open dialog
+                // downloaded isn't in the library yet, so would be taken for unused
+                if matches!(&self.screen, AppScreen::Home(home) if home.holds_artwork()) {
+                    log::info!("not removing unused artwork while a dialog is using some");
+                    return if scan {
                         iced::Task::done(Message::ScanDirectories)
                     } else {
-                        self.state.library_status = LibraryStatus::Idle;
-                        iced::Task::none()
+                        self.idle()
+                    };
+                }
+                self.state.collecting_artwork = true;
+
+                let storage = self.state.storage_path.clone();
+                let referenced = library::referenced_artwork(&self.state.library);
+
+                iced::Task::perform(
+                    async move {
+                        library::collect_artwork(&storage, &referenced)
+                            .await
+                            .map_err(|err| format!("{:#}", err))
                     },
-                ])
+                    move |freed| Message::ArtworkCollected { scan, freed },
+                )
+            }
+            Message::ArtworkCollected { scan, freed } => {
+                self.state.collecting_artwork = false;
+                match freed {
+                    Ok(bytes) => log::info!(
+                        "removed unused artwork, freeing {:.1} MB",
+                        bytes as f64 / 1_000_000.0
+                    ),
+                    Err(err) => log::warn!("failed to remove unused artwork: {}", err),
+                }
+                if scan {
+                    iced::Task::done(Message::ScanDirectories)
+                } else {
+                    self.idle()
+                }
             }
             Message::ScanDirectoriesComplete(added) => {
                 self.state.library.extend(added);
                 self.state.library.save(&self.state.storage_path).unwrap();
-                iced::Task::done(Message::Scrape { force: false })
+                self.scrape(false)
             }
             Message::ScrapeComplete(result) => {
-                self.state.library_status = LibraryStatus::Idle;
+                let report = result.report();
+                self.state.rejected_keys.extend(report.rejected_keys());
+                self.state.scrape_report = report;
+                result.insert(&mut self.state.library);
+                self.state.prune_tabs();
                 let (card_cache, cache_task) = cards::Cache::build(&self.state.library);
                 self.state.card_cache = card_cache;
-                result.insert(&mut self.state.library);
+                let idle = self.idle();
                 iced::Task::batch([
                     cache_task,
                     iced::Task::perform(self.state.save_library(), |_| ()).discard(),
+                    idle,
+                    if let AppScreen::Onboarding(_) = self.screen {
+                        iced::Task::done(Message::Onboarding(
+                            screen::OnboardingMessage::ScanComplete,
+                        ))
+                    } else {
+                        iced::Task::none()
+                    },
                 ])
             }
             Message::CardImageLoaded(id, image) => {
@@ -222,6 +427,10 @@ impl App {
                 }
                 iced::Task::none()
             }
+            Message::ScaleFactorChanged(scale_factor) => {
+                self.state.scale_factor = scale_factor;
+                iced::Task::none()
+            }
             Message::Animate => iced::Task::none(),
             Message::Exit => iced::Task::batch([
                 iced::Task::perform(self.state.save_library(), |_| ()),
@@ -232,6 +441,67 @@ impl App {
         }
     }
 
+    /// Marks the library as no longer scanning, and starts the refreshes that waited for it.
+    fn idle(&mut self) -> iced::Task<Message> {
+        self.state.library_status = LibraryStatus::Idle;
+        let refresh = std::mem::take(&mut self.refresh_after_scan);
+        if refresh.is_empty() {
+            iced::Task::none()
+        } else {
+            iced::Task::done(Message::Refresh(Some(refresh)))
+        }
+    }
+
+    /// Scrapes uncategorised media, or all of it with `force`, whatever else is running.
+    fn scrape(&mut self, force: bool) -> iced::Task<Message> {
+        self.state.library_status = LibraryStatus::Scanning;
+
+        let storage = self.state.storage_path.clone();
+        let settings = &self.state.settings;
+        let media = self
+            .state
+            .library
+            .iter_mut()
+            .filter_map(|(id, media)| match media {
+                library::Media::Uncategorised(uncategorised)
+                    if force || !uncategorised.dont_scrape =>
+                {
+                    uncategorised.dont_scrape = true;
+                    uncategorised.needs_review = false;
+                    uncategorised.scrape_failed = false;
+                    let path = &uncategorised.video.path;
+                    Some((
+                        settings.language_for(path).to_string(),
+                        (*id, path.file_name()?.to_str()?.to_string(), path.clone()),
+                    ))
+                }
+                _ => None,
+            })
+            .into_group_map();
+        let known = library::KnownSeasons::new(&self.state.library);
+        // one scraper per metadata language, as directories can override it
+        let jobs: Vec<_> = media
+            .into_iter()
+            .map(|(language, media)| (self.state.fallback_scraper(&language), media))
+            .collect();
+
+        iced::Task::perform(
+            async move {
+                let mut result = library::ScrapeResult::default();
+                for (fallback, media) in jobs {
+                    let sidecars =
+                        library::Sidecars::load(media.iter().map(|(_, _, path)| path.clone()))
+                            .await;
+                    let scraper = library::SidecarScraper::new(sidecars, fallback);
+                    let media = media.into_iter().map(|(id, filename, _)| (id, filename));
+                    result.extend(library::scrape_all(&scraper, &storage, &known, media).await);
+                }
+                result
+            },
+            Message::ScrapeComplete,
+        )
+    }
+
     pub fn view(&self) -> iced::Element<Message> {
         match &self.screen {
             AppScreen::Home(screen) => screen.view(&self.state, self.now).map(Message::Home),
@@ -239,6 +509,9 @@ impl App {
             AppScreen::Settings(screen) => {
                 screen.view(&self.state, self.now).map(Message::Settings)
             }
+            AppScreen::Onboarding(screen) => {
+                screen.view(&self.state, self.now).map(Message::Onboarding)
+            }
         }
     }
 }
@@ -248,6 +521,7 @@ pub enum Message {
     Home(screen::HomeMessage),
     Player(screen::PlayerMessage),
     Settings(screen::SettingsMessage),
+    Onboarding(screen::OnboardingMessage),
 
     Purge {
         scan: bool,
@@ -256,13 +530,25 @@ pub enum Message {
     Scrape {
         force: bool,
     },
+    /// Refreshes the metadata of some items, or of the whole library.
+    Refresh(Option<Vec<library::MediaId>>),
+    CheckScheduledRefresh,
     PurgeComplete {
         scan: bool,
         removed: Vec<library::MediaId>,
     },
+    /// Deletes artwork no longer referenced by the library, then scans if `scan` is set.
+    CollectArtwork {
+        scan: bool,
+    },
+    ArtworkCollected {
+        scan: bool,
+        freed: Result<u64, String>,
+    },
     ScanDirectoriesComplete(Vec<library::Media>),
     ScrapeComplete(library::ScrapeResult),
     CardImageLoaded(library::MediaId, Option<image::Allocation>),
+    ScaleFactorChanged(f32),
 
     Animate,
     Exit,
@@ -272,4 +558,5 @@ pub enum AppScreen {
     Home(screen::Home),
     Player(screen::Player),
     Settings(screen::Settings),
+    Onboarding(screen::Onboarding),
 }
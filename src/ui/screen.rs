@@ -1,10 +1,12 @@
 pub mod home;
+mod onboarding;
 mod player;
 mod settings;
 
 use std::time::Instant;
 
 pub use home::*;
+pub use onboarding::*;
 pub use player::*;
 pub use settings::*;
 
@@ -1,4 +1,11 @@
+mod calendar;
 pub mod cards;
+mod details;
+mod edit;
+mod episode_order;
+mod identify;
+mod move_to;
+mod scrape_report;
 mod seasons;
 mod sidebar;
 mod top_bar;
@@ -12,13 +19,17 @@ use crate::{
     },
 };
 use cards::Card;
+use edit::{EditMetadata, EditMetadataMessage};
+use episode_order::{EpisodeOrder, EpisodeOrderMessage};
 use iced::{
     color,
     widget::{
         button, center, column, container, image, opaque, row, rule, scrollable, space, stack, text,
     },
 };
+use identify::{Identify, IdentifyMessage};
 use itertools::Itertools;
+use move_to::{MoveTo, MoveToMessage};
 use std::{
     fmt,
     path::PathBuf,
@@ -78,6 +89,12 @@ pub struct Home {
 
     save_task: Option<iced::task::Handle>, // to debounce saves
     sidebar_action: sidebar::Action,
+    identify: Option<Identify>,
+    move_to: Option<MoveTo>,
+    edit: Option<EditMetadata>,
+    episode_order: Option<EpisodeOrder>,
+    /// Limits the calendar to series with recently watched episodes.
+    calendar_watching_only: bool,
 }
 
 impl Home {
@@ -95,6 +112,11 @@ impl Home {
 
                 save_task: None,
                 sidebar_action: sidebar::Action::None,
+                identify: None,
+                move_to: None,
+                edit: None,
+                episode_order: None,
+                calendar_watching_only: false,
             },
             iced::Task::none(),
         )
@@ -117,6 +139,12 @@ impl Home {
         self.save_task = Some(handle);
         task
     }
+
+    /// Whether an open dialog is downloading or holding artwork the library doesn't refer to yet.
+    pub fn holds_artwork(&self) -> bool {
+        self.identify.as_ref().is_some_and(Identify::applying)
+            || self.edit.as_ref().is_some_and(EditMetadata::holds_poster)
+    }
 }
 
 impl Screen for Home {
@@ -149,6 +177,10 @@ impl Screen for Home {
                 }
                 iced::Task::none()
             }
+            HomeMessage::PlayFromStart(id) => {
+                library::set_watched(id, library::Watched::No, &mut state.library);
+                iced::Task::done(HomeMessage::Play(id))
+            }
             HomeMessage::MarkUnwatched(id) => {
                 library::set_watched(id, library::Watched::No, &mut state.library);
                 self.save(state)
@@ -173,6 +205,14 @@ impl Screen for Home {
                 self.filter.not_watched = toggle;
                 iced::Task::none()
             }
+            HomeMessage::DismissScrapeReport => {
+                state.scrape_report = library::ScrapeReport::default();
+                iced::Task::none()
+            }
+            HomeMessage::ToggleCalendarWatching(toggle) => {
+                self.calendar_watching_only = toggle;
+                iced::Task::none()
+            }
             HomeMessage::SetSort(sort) => {
                 self.sort = sort;
                 iced::Task::none()
@@ -288,6 +328,78 @@ impl Screen for Home {
                 }
                 iced::Task::none()
             }
+            HomeMessage::BeginIdentify(id) => {
+                let (identify, task) = Identify::new(id, state);
+                self.identify = Some(identify);
+                task
+            }
+            // what's being applied is only in the library once it's done
+            HomeMessage::Identify(IdentifyMessage::Close)
+                if self.identify.as_ref().is_some_and(Identify::applying) =>
+            {
+                iced::Task::none()
+            }
+            HomeMessage::Identify(IdentifyMessage::Close) => {
+                self.identify = None;
+                iced::Task::none()
+            }
+            HomeMessage::Identify(IdentifyMessage::Applied(Ok(result))) => {
+                self.identify = None;
+                let report = result.report();
+                if !report.is_empty() {
+                    state.scrape_report = report;
+                }
+                result.insert(&mut state.library);
+                iced::Task::done(HomeMessage::LibraryChanged)
+            }
+            HomeMessage::Identify(message) => match &mut self.identify {
+                Some(identify) => identify.update(message, state),
+                None => iced::Task::none(),
+            },
+            HomeMessage::Unmatch(id) => {
+                if state.library.unmatch(id) {
+                    iced::Task::done(HomeMessage::LibraryChanged)
+                } else {
+                    iced::Task::none()
+                }
+            }
+            HomeMessage::BeginMoveTo(id) => {
+                self.move_to = Some(MoveTo::new(id, state));
+                iced::Task::none()
+            }
+            HomeMessage::MoveTo(MoveToMessage::Close) => {
+                self.move_to = None;
+                iced::Task::none()
+            }
+            HomeMessage::MoveTo(message) => match &mut self.move_to {
+                Some(move_to) => move_to.update(message, state),
+                None => iced::Task::none(),
+            },
+            HomeMessage::BeginEditMetadata(id) => {
+                self.edit = EditMetadata::new(id, state);
+                iced::Task::none()
+            }
+            HomeMessage::EditMetadata(EditMetadataMessage::Close) => {
+                self.edit = None;
+                iced::Task::none()
+            }
+            HomeMessage::EditMetadata(message) => match &mut self.edit {
+                Some(edit) => edit.update(message, state),
+                None => iced::Task::none(),
+            },
+            HomeMessage::BeginEpisodeOrder(id) => {
+                let (episode_order, task) = EpisodeOrder::new(id, state);
+                self.episode_order = Some(episode_order);
+                task
+            }
+            HomeMessage::EpisodeOrder(EpisodeOrderMessage::Close) => {
+                self.episode_order = None;
+                iced::Task::none()
+            }
+            HomeMessage::EpisodeOrder(message) => match &mut self.episode_order {
+                Some(episode_order) => episode_order.update(message, state),
+                None => iced::Task::none(),
+            },
             _ => iced::Task::none(),
         }
     }
@@ -298,8 +410,12 @@ impl Screen for Home {
     {
         let search = (!self.search.trim().is_empty()).then_some(self.search.as_str());
         let tab = state.tab_stack.back().cloned().unwrap();
+        let needs_review = state
+            .library
+            .iter()
+            .any(|(_, media)| is_needs_review(media));
 
-        row![]
+        let content = row![]
             .push(sidebar::sidebar(state.library_status, state.library.iter_collections(), self.sidebar_action.clone()))
             .push(rule::vertical(1.0).style(|theme| rule::Style {
                 color: iced::Color::from_rgb8(40, 40, 40),
@@ -377,6 +493,34 @@ impl Screen for Home {
                                             Some(20),
                                             now,
                                         ))
+                                        .push(needs_review.then(|| {
+                                            row![]
+                                                .spacing(30)
+                                                .align_y(iced::alignment::Vertical::Center)
+                                                .push(text("Needs Review").font(HEADER_FONT).size(24.0))
+                                                .push(rule::horizontal(1.0).style(|theme| rule::Style {
+                                                    color: iced::Color::from_rgb8(40, 40, 40),
+                                                    ..<iced::Theme as rule::Catalog>::default()(theme)
+                                                }))
+                                        }))
+                                        .push(needs_review.then(|| {
+                                            cards::card_grid(
+                                                &state.card_cache,
+                                                search,
+                                                state.library.iter().filter(|(_, media)| {
+                                                    is_needs_review(media)
+                                                }),
+                                                &state.library,
+                                                |&(_a_id, a), &(_b_id, b), _| {
+                                                    b.video()
+                                                        .unwrap()
+                                                        .added
+                                                        .cmp(&a.video().unwrap().added)
+                                                },
+                                                None,
+                                                now,
+                                            )
+                                        }))
                                         .into(),
                                     Tab::Movies | Tab::TvShows => cards::card_grid(
                                         &state.card_cache,
@@ -397,20 +541,52 @@ impl Screen for Home {
                                         None,
                                         now,
                                     ),
-                                    Tab::TvShow(id) => seasons::season_list(
-                                        &state.card_cache,
+                                    Tab::Calendar => calendar::calendar(
                                         search,
+                                        self.calendar_watching_only,
+                                        &state.library,
+                                    ),
+                                    Tab::Movie(id) => details::movie_page(
+                                        &state.card_cache,
                                         id,
                                         state
                                             .library
                                             .get(id)
                                             .and_then(|media| match media {
-                                                library::Media::Series(series) => Some(series),
+                                                library::Media::Movie(movie) => Some(movie),
                                                 _ => None,
                                             })
                                             .unwrap(),
                                         &state.library,
                                     ),
+                                    Tab::TvShow(id) => {
+                                        let series = state
+                                            .library
+                                            .get(id)
+                                            .and_then(|media| match media {
+                                                library::Media::Series(series) => Some(series),
+                                                _ => None,
+                                            })
+                                            .unwrap();
+                                        column![]
+                                            .width(iced::Length::Fill)
+                                            .push(details::series_header(
+                                                &state.card_cache,
+                                                id,
+                                                series,
+                                                &state.library,
+                                                state.settings.specials_in_airing_order,
+                                            ))
+                                            .push(seasons::season_list(
+                                                &state.card_cache,
+                                                search,
+                                                id,
+                                                series,
+                                                &state.library,
+                                                state.settings.specials_in_airing_order,
+                                            ))
+                                            .into()
+                                    }
                                     Tab::Season(id) => seasons::season_panel(
                                         &state.card_cache,
                                         search,
@@ -424,6 +600,7 @@ impl Screen for Home {
                                             })
                                             .unwrap(),
                                         &state.library,
+                                        state.settings.specials_in_airing_order,
                                     ),
                                     Tab::Collection(name) => {
                                         if let Some(iter) = state.library.collection_iter(&name) {
@@ -475,9 +652,21 @@ impl Screen for Home {
                                 color: iced::Color::from_rgb8(40, 40, 40),
                                 ..<iced::Theme as rule::Catalog>::default()(theme)
                             })),
-                    ),
-            )
-            .into()
+                    )
+                    .push(scrape_report::scrape_report(&state.scrape_report)),
+            );
+
+        if let Some(identify) = &self.identify {
+            stack![content, identify.view(&state.library)].into()
+        } else if let Some(move_to) = &self.move_to {
+            stack![content, move_to.view(&state.library)].into()
+        } else if let Some(edit) = &self.edit {
+            stack![content, edit.view(&state.library)].into()
+        } else if let Some(episode_order) = &self.episode_order {
+            stack![content, episode_order.view(&state.library)].into()
+        } else {
+            content.into()
+        }
     }
 
     fn subscription(&self, _now: Instant) -> iced::Subscription<HomeMessage> {
@@ -494,11 +683,13 @@ pub enum HomeAction {
     ScanDirectories,
     Purge,
     ForceScan,
+    RefreshMetadata,
 }
 
 #[derive(Debug, Clone)]
 pub enum HomeMessage {
     Play(library::MediaId),
+    PlayFromStart(library::MediaId),
     OpenSettings,
     Search(String),
     Action(HomeAction),
@@ -510,11 +701,27 @@ pub enum HomeMessage {
     ToggleFilterWatched(bool),
     ToggleFilterPartiallyWatched(bool),
     ToggleFilterNotWatched(bool),
+    ToggleCalendarWatching(bool),
     SetSort(Sort),
     ToggleSortDirection(SortDirection),
     ToggleMediaCollection(library::MediaId, library::CollectionId),
     CardMouseEnter(library::MediaId),
     CardMouseExit(library::MediaId),
+    BeginIdentify(library::MediaId),
+    Identify(IdentifyMessage),
+    Unmatch(library::MediaId),
+    BeginMoveTo(library::MediaId),
+    MoveTo(MoveToMessage),
+    BeginEditMetadata(library::MediaId),
+    RefreshMetadata(library::MediaId),
+    EditMetadata(EditMetadataMessage),
+    BeginEpisodeOrder(library::MediaId),
+    EpisodeOrder(EpisodeOrderMessage),
+    /// Media was re-categorised; the app rebuilds the card cache and saves.
+    LibraryChanged,
+    /// Scrapes what failed during the last scan or refresh again; handled by the app.
+    RetryScrape,
+    DismissScrapeReport,
 
     NewCollection,
     BeginRenameCollection(library::CollectionId),
@@ -604,6 +811,16 @@ fn watched_icon<'a>(
     row.into()
 }
 
+fn is_needs_review(media: &library::Media) -> bool {
+    matches!(
+        media,
+        library::Media::Uncategorised(library::Uncategorised {
+            needs_review: true,
+            ..
+        })
+    )
+}
+
 fn search_episode(
     search: &str,
     _id: library::MediaId,
@@ -701,6 +918,34 @@ fn media_menu<'a, 'b>(
                             menu_item(0xe8f4, "Mark watched").on_press(HomeMessage::MarkWatched(id))
                         }),
                     )
+                    .push(media.video().is_some().then(|| {
+                        menu_item(0xe8b6, "Identify...").on_press(HomeMessage::BeginIdentify(id))
+                    }))
+                    .push(
+                        (!matches!(media, library::Media::Uncategorised(_))).then(|| {
+                            menu_item(0xe3c9, "Edit metadata...")
+                                .on_press(HomeMessage::BeginEditMetadata(id))
+                        }),
+                    )
+                    .push(
+                        (!matches!(media, library::Media::Uncategorised(_))).then(|| {
+                            menu_item(0xe5d5, "Refresh metadata")
+                                .on_press(HomeMessage::RefreshMetadata(id))
+                        }),
+                    )
+                    .push(matches!(media, library::Media::Series(_)).then(|| {
+                        menu_item(0xe164, "Episode order...")
+                            .on_press(HomeMessage::BeginEpisodeOrder(id))
+                    }))
+                    .push(media.video().is_some().then(|| {
+                        menu_item(0xe89d, "Move to...").on_press(HomeMessage::BeginMoveTo(id))
+                    }))
+                    .push(
+                        matches!(media, library::Media::Movie(_) | library::Media::Episode(_))
+                            .then(|| {
+                                menu_item(0xe16f, "Unmatch").on_press(HomeMessage::Unmatch(id))
+                            }),
+                    )
                     .width(200.0)
                     .spacing(5.0),
             )
@@ -0,0 +1,146 @@
+use super::HomeMessage;
+use crate::{
+    library,
+    ui::{HEADER_FONT, Tab, greyscale, rich_checkbox, themed_button},
+};
+use chrono::{Datelike, NaiveDate};
+use iced::widget::{button, column, row, text};
+use itertools::Itertools;
+
+/// How far back recently aired episodes are shown.
+const RECENT_DAYS: i64 = 7;
+/// How far ahead upcoming episodes are shown.
+const UPCOMING_DAYS: i64 = 60;
+/// A series counts as being watched if any of its episodes was played this recently.
+const WATCHING_DAYS: i64 = 60;
+
+/// Whether the user has been watching `series` lately.
+fn is_watching(series: library::MediaId, library: &library::Library) -> bool {
+    library::series_last_watched(series, library)
+        .and_then(|(_, episode)| episode.video.last_watched)
+        .is_some_and(|last| (chrono::Local::now() - last).num_days() < WATCHING_DAYS)
+}
+
+fn day_title(day: NaiveDate, today: NaiveDate) -> String {
+    match (day - today).num_days() {
+        -1 => String::from("Yesterday"),
+        0 => String::from("Today"),
+        1 => String::from("Tomorrow"),
+        _ if day.year() == today.year() => day.format("%A, %e %B").to_string(),
+        _ => day.format("%A, %e %B %Y").to_string(),
+    }
+}
+
+pub fn calendar<'a>(
+    search: Option<&str>,
+    watching_only: bool,
+    library: &library::Library,
+) -> iced::Element<'a, HomeMessage> {
+    let today = chrono::Local::now().date_naive();
+    let search = search.map(str::to_lowercase);
+
+    let entries = library::calendar(
+        library,
+        today - chrono::Duration::days(RECENT_DAYS),
+        today + chrono::Duration::days(UPCOMING_DAYS),
+    )
+    .into_iter()
+    .filter(|entry| !watching_only || is_watching(entry.series, library))
+    .filter(|entry| match &search {
+        Some(search) => library
+            .get(entry.series)
+            .is_some_and(|series| series.title().to_lowercase().contains(search)),
+        None => true,
+    })
+    .collect_vec();
+
+    let days: Vec<iced::Element<'a, HomeMessage>> = entries
+        .into_iter()
+        .chunk_by(|entry| entry.episode.aired)
+        .into_iter()
+        .filter_map(|(day, entries)| Some((day?, entries.collect_vec())))
+        .map(|(day, entries)| {
+            column![]
+                .spacing(5.0)
+                .push(
+                    text(day_title(day, today))
+                        .font(HEADER_FONT)
+                        .size(20.0)
+                        .color(if day == today {
+                            iced::Color::WHITE
+                        } else {
+                            greyscale(190)
+                        }),
+                )
+                .extend(
+                    entries
+                        .into_iter()
+                        .map(|entry| calendar_entry(entry, library)),
+                )
+                .into()
+        })
+        .collect_vec();
+    let empty = days.is_empty();
+
+    column![]
+        .width(iced::Length::Fill)
+        .max_width(800.0)
+        .padding(iced::Padding::new(20.0).left(40.0).right(40.0))
+        .spacing(20.0)
+        .push(rich_checkbox(
+            text("Only shows I'm watching"),
+            watching_only,
+            HomeMessage::ToggleCalendarWatching,
+        ))
+        .extend(days)
+        .push(empty.then(|| {
+            text(
+                "Nothing aired recently or is coming up. Episode lists are updated whenever \
+                 metadata is refreshed.",
+            )
+            .color(greyscale(150))
+        }))
+        .into()
+}
+
+fn calendar_entry<'a>(
+    entry: library::CalendarEntry<'_>,
+    library: &library::Library,
+) -> iced::Element<'a, HomeMessage> {
+    let series = library
+        .get(entry.series)
+        .map(library::Media::title)
+        .unwrap_or_default();
+    let (status, status_color) = match entry.media {
+        Some(_) => ("In library", iced::Color::from_rgb8(68, 161, 50)),
+        None => ("Not yet available", greyscale(150)),
+    };
+
+    button(
+        row![]
+            .spacing(10.0)
+            .align_y(iced::Alignment::Center)
+            .push(
+                column![]
+                    .width(iced::Length::Fill)
+                    .spacing(2.0)
+                    .push(text(series))
+                    .push(
+                        text(format!(
+                            "S{:02}E{:02} - {}",
+                            entry.episode.season, entry.episode.episode, entry.episode.title
+                        ))
+                        .size(13.0)
+                        .color(greyscale(150)),
+                    ),
+            )
+            .push(text(status).size(13.0).color(status_color)),
+    )
+    .width(iced::Length::Fill)
+    .style(themed_button)
+    .on_press(match entry.media {
+        Some(id) => HomeMessage::Play(id),
+        None => HomeMessage::Goto(Tab::TvShow(entry.series)),
+    })
+    .into()
+}
@@ -25,6 +25,9 @@ pub struct Card {
     pub text_overflow: f32,
     pub text_animation: Option<Animation<f32>>,
     pub hover_animation: Animation<bool>,
+    /// Aired episodes of a series or season that aren't in the library, counted once per build
+    /// rather than on every view.
+    pub missing: usize,
 }
 
 impl Card {
@@ -56,6 +59,7 @@ impl Card {
             text_overflow,
             text_animation: None,
             hover_animation,
+            missing: library::missing_count(id, library),
         }
     }
 
@@ -175,6 +179,14 @@ fn media_card<'a>(
     let hover_alpha = card
         .map(|card| card.hover_animation.interpolate(0.0, 1.0, now))
         .unwrap_or(1.0);
+    let scrape_failed = matches!(
+        media,
+        library::Media::Uncategorised(library::Uncategorised {
+            scrape_failed: true,
+            ..
+        })
+    );
+    let missing = card.map_or(0, |card| card.missing);
 
     mouse_area(
         stack![]
@@ -240,18 +252,48 @@ fn media_card<'a>(
                             .height(iced::Length::Fill)
                             .align_x(iced::Alignment::Center)
                             .align_y(iced::Alignment::Center)
-                    })),
+                    }))
+                    .push(
+                        scrape_failed
+                            .then(|| badge("Scrape failed - will retry", color!(0xb33a3a))),
+                    )
+                    .push(
+                        (missing > 0)
+                            .then(|| badge(format!("{} missing", missing), color!(0x505050))),
+                    ),
             ),
     )
     .interaction(iced::mouse::Interaction::Pointer)
     .on_enter(HomeMessage::CardMouseEnter(id))
     .on_exit(HomeMessage::CardMouseExit(id))
     .on_press(match media {
-        library::Media::Uncategorised(_)
-        | library::Media::Movie(_)
-        | library::Media::Episode(_) => HomeMessage::Play(id),
+        library::Media::Uncategorised(_) | library::Media::Episode(_) => HomeMessage::Play(id),
+        library::Media::Movie(_) => HomeMessage::Goto(Tab::Movie(id)),
         library::Media::Series(_) => HomeMessage::Goto(Tab::TvShow(id)),
         library::Media::Season(_) => HomeMessage::Goto(Tab::Season(id)),
     })
     .into()
 }
+
+/// A label along the bottom edge of a card's poster.
+fn badge<'a>(
+    label: impl text::IntoFragment<'a>,
+    background: iced::Color,
+) -> iced::Element<'a, HomeMessage> {
+    container(
+        container(text(label).size(11.0))
+            .padding(iced::Padding::new(2.0).left(6.0).right(6.0))
+            .style(move |_| container::Style {
+                background: Some(iced::Background::Color(background.scale_alpha(0.9))),
+                text_color: Some(color!(0xf0f0f0)),
+                border: iced::Border::default().rounded(4.0),
+                ..Default::default()
+            }),
+    )
+    .width(iced::Length::Fill)
+    .height(iced::Length::Fill)
+    .padding(5.0)
+    .align_x(iced::Alignment::Center)
+    .align_y(iced::Alignment::End)
+    .into()
+}
@@ -0,0 +1,420 @@
+use super::{HomeMessage, cards::Cache, collection_menu, media_menu, poster_image, watched_icon};
+use crate::{
+    library,
+    ui::{HEADER_FONT, greyscale, icon, themed_button},
+};
+use iced::widget::{button, column, container, image, row, space, stack, text};
+use itertools::Itertools;
+use std::path::Path;
+
+const BACKDROP_HEIGHT: f32 = 360.0;
+const LOGO_HEIGHT: f32 = 80.0;
+
+pub fn movie_page<'a>(
+    cache: &'a Cache,
+    id: library::MediaId,
+    movie: &library::Movie,
+    library: &library::Library,
+) -> iced::Element<'a, HomeMessage> {
+    let metadata = &movie.metadata;
+    let details = &metadata.details;
+
+    let facts = [
+        (metadata.year > 0).then(|| metadata.year.to_string()),
+        details.certification.clone(),
+        details.runtime.map(format_runtime),
+        details.rating.map(|rating| format!("{:.1}/10", rating)),
+    ];
+
+    let header = column![]
+        .spacing(10.0)
+        .push(text(metadata.title.clone()).font(HEADER_FONT).size(32.0))
+        .push(
+            details
+                .original_title
+                .clone()
+                .filter(|original| *original != metadata.title)
+                .map(|original| text(original).color(greyscale(150))),
+        )
+        .push(text(facts.into_iter().flatten().join("  ·  ")).color(greyscale(190)))
+        .push(
+            (!details.genres.is_empty())
+                .then(|| text(details.genres.join(", ")).color(greyscale(150))),
+        )
+        .push(video_actions(id, &movie.video, library))
+        .push(
+            details
+                .tagline
+                .clone()
+                .map(|tagline| text(tagline).size(18.0).color(greyscale(210))),
+        )
+        .push(details.overview.clone().map(|overview| text(overview)));
+
+    page(
+        cache,
+        id,
+        metadata.backdrop.as_deref(),
+        header,
+        column![]
+            .spacing(30.0)
+            .push(credits_section("Cast", &details.cast))
+            .push(credits_section("Crew", &details.crew))
+            .push(section("File", file_info(&movie.video)))
+            .push(collections_section(id, library)),
+    )
+}
+
+pub fn series_header<'a>(
+    cache: &'a Cache,
+    id: library::MediaId,
+    series: &library::Series,
+    library: &library::Library,
+    specials: bool,
+) -> iced::Element<'a, HomeMessage> {
+    let metadata = &series.metadata;
+    let details = &metadata.details;
+
+    let episodes = library::find_all_episodes(id, library).count();
+    let facts = [
+        metadata.aired.map(|aired| aired.format("%Y").to_string()),
+        details.certification.clone(),
+        Some(format!(
+            "{} episode{}",
+            episodes,
+            if episodes == 1 { "" } else { "s" }
+        )),
+        details.runtime.map(format_runtime),
+        details.rating.map(|rating| format!("{:.1}/10", rating)),
+    ];
+
+    let up_next =
+        library::up_next(id, library, specials).and_then(|next| match library.get(next) {
+            Some(library::Media::Episode(episode)) => Some((next, episode)),
+            _ => None,
+        });
+
+    let title: iced::Element<'a, HomeMessage> = match &metadata.logo {
+        Some(logo) => image(image::Handle::from_path(logo))
+            .width(iced::Length::Shrink)
+            .height(LOGO_HEIGHT)
+            .into(),
+        None => text(metadata.title.clone())
+            .font(HEADER_FONT)
+            .size(32.0)
+            .into(),
+    };
+
+    let header = column![]
+        .spacing(10.0)
+        .push(title)
+        .push(text(facts.into_iter().flatten().join("  ·  ")).color(greyscale(190)))
+        .push(
+            (!details.genres.is_empty() || !details.studios.is_empty()).then(|| {
+                text(
+                    details
+                        .genres
+                        .iter()
+                        .chain(details.studios.first())
+                        .join(", "),
+                )
+                .color(greyscale(150))
+            }),
+        )
+        .push(
+            row![]
+                .spacing(10.0)
+                .align_y(iced::Alignment::Center)
+                .push(up_next.map(|(next, episode)| {
+                    let label = format!(
+                        "{} S{:02}E{:02}",
+                        match episode.video.watched {
+                            library::Watched::Partial { .. } => "Resume",
+                            _ => "Play",
+                        },
+                        episode.metadata.season,
+                        episode.metadata.episode
+                    );
+                    action_button(0xe037, label)
+                        .style(button::primary)
+                        .on_press(HomeMessage::Play(next))
+                }))
+                .push(watched_actions(id, library))
+                .push(container(watched_icon(
+                    library::calculate_watched(id, library).unwrap_or(library::Watched::No),
+                    true,
+                    1.0,
+                )))
+                .push(space::horizontal())
+                .push(collection_menu(id, library, 1.0))
+                .push(media_menu(id, library, 1.0)),
+        )
+        .push(
+            details
+                .tagline
+                .clone()
+                .map(|tagline| text(tagline).size(18.0).color(greyscale(210))),
+        )
+        .push(details.overview.clone().map(|overview| text(overview)));
+
+    page(
+        cache,
+        id,
+        metadata.backdrop.as_deref(),
+        header,
+        column![]
+            .spacing(30.0)
+            .push(credits_section("Cast", &details.cast))
+            .push(credits_section("Crew", &details.crew)),
+    )
+}
+
+/// Backdrop, poster and header side by side, with further sections underneath.
+fn page<'a>(
+    cache: &'a Cache,
+    id: library::MediaId,
+    backdrop_path: Option<&Path>,
+    header: impl Into<iced::Element<'a, HomeMessage>>,
+    body: impl Into<iced::Element<'a, HomeMessage>>,
+) -> iced::Element<'a, HomeMessage> {
+    let card = cache.cache.get(&id);
+
+    stack![]
+        .width(iced::Length::Fill)
+        .push(backdrop(
+            // without a backdrop, a washed out poster still gives the page some colour
+            backdrop_path.map(image::Handle::from_path).or_else(|| {
+                card.and_then(|card| card.image.as_ref())
+                    .map(|allocation| allocation.handle().clone())
+            }),
+        ))
+        .push(
+            container(
+                column![]
+                    .width(iced::Length::Fill)
+                    .max_width(1000.0)
+                    .spacing(30.0)
+                    .padding(iced::Padding::new(40.0).top(BACKDROP_HEIGHT / 3.0))
+                    .push(
+                        row![]
+                            .spacing(30.0)
+                            .push(poster_image(card))
+                            .push(container(header).width(iced::Length::Fill)),
+                    )
+                    .push(body),
+            )
+            .center_x(iced::Length::Fill),
+        )
+        .into()
+}
+
+fn backdrop<'a>(handle: Option<image::Handle>) -> iced::Element<'a, HomeMessage> {
+    let fade = container(space())
+        .width(iced::Length::Fill)
+        .height(BACKDROP_HEIGHT)
+        .style(|theme: &iced::Theme| container::Style {
+            background: Some(iced::Background::Gradient(iced::Gradient::Linear(
+                iced::gradient::Linear::new(0.0)
+                    .add_stop(0.0, theme.palette().background)
+                    .add_stop(1.0, theme.palette().background.scale_alpha(0.4)),
+            ))),
+            ..Default::default()
+        });
+
+    stack![]
+        .width(iced::Length::Fill)
+        .height(BACKDROP_HEIGHT)
+        .clip(true)
+        .push(handle.map(|handle| {
+            image(handle)
+                .content_fit(iced::ContentFit::Cover)
+                .width(iced::Length::Fill)
+                .height(iced::Length::Fill)
+                .opacity(0.35)
+        }))
+        .push(fade)
+        .into()
+}
+
+fn video_actions<'a>(
+    id: library::MediaId,
+    video: &library::Video,
+    library: &library::Library,
+) -> iced::Element<'a, HomeMessage> {
+    let play: iced::Element<'a, HomeMessage> = match video.watched {
+        library::Watched::Partial { seconds, .. } => row![]
+            .spacing(10.0)
+            .push(
+                action_button(0xe037, format!("Resume from {}", format_position(seconds)))
+                    .style(button::primary)
+                    .on_press(HomeMessage::Play(id)),
+            )
+            .push(action_button(0xe042, "Play from start").on_press(HomeMessage::PlayFromStart(id)))
+            .into(),
+        _ => action_button(0xe037, "Play")
+            .style(button::primary)
+            .on_press(HomeMessage::Play(id))
+            .into(),
+    };
+
+    row![]
+        .spacing(10.0)
+        .align_y(iced::Alignment::Center)
+        .push(play)
+        .push(watched_actions(id, library))
+        .push(action_button(0xe8b6, "Identify").on_press(HomeMessage::BeginIdentify(id)))
+        .push(container(watched_icon(video.watched, true, 1.0)))
+        .push(space::horizontal())
+        .push(collection_menu(id, library, 1.0))
+        .push(media_menu(id, library, 1.0))
+        .into()
+}
+
+fn watched_actions<'a>(
+    id: library::MediaId,
+    library: &library::Library,
+) -> iced::Element<'a, HomeMessage> {
+    match library::calculate_watched(id, library) {
+        Some(library::Watched::Yes) => action_button(0xe8f5, "Mark unwatched")
+            .on_press(HomeMessage::MarkUnwatched(id))
+            .into(),
+        _ => action_button(0xe8f4, "Mark watched")
+            .on_press(HomeMessage::MarkWatched(id))
+            .into(),
+    }
+}
+
+fn action_button<'a>(
+    codepoint: u32,
+    label: impl Into<String>,
+) -> iced::widget::Button<'a, HomeMessage> {
+    button(
+        row![]
+            .spacing(5.0)
+            .align_y(iced::Alignment::Center)
+            .push(icon(codepoint).size(18.0))
+            .push(text(label.into())),
+    )
+    .padding(iced::Padding::new(8.0).left(12.0).right(14.0))
+    .style(themed_button)
+}
+
+fn section<'a>(
+    title: &'a str,
+    content: impl Into<iced::Element<'a, HomeMessage>>,
+) -> iced::Element<'a, HomeMessage> {
+    column![]
+        .spacing(10.0)
+        .push(text(title).font(HEADER_FONT).size(22.0))
+        .push(content)
+        .into()
+}
+
+fn credits_section<'a>(
+    title: &'a str,
+    credits: &[library::Credit],
+) -> Option<iced::Element<'a, HomeMessage>> {
+    if credits.is_empty() {
+        return None;
+    }
+
+    Some(section(
+        title,
+        row![]
+            .spacing(20.0)
+            .extend(credits.iter().map(|credit| {
+                column![]
+                    .width(150.0)
+                    .spacing(2.0)
+                    .push(text(credit.name.clone()))
+                    .push(text(credit.role.clone()).size(13.0).color(greyscale(150)))
+                    .into()
+            }))
+            .wrap()
+            .vertical_spacing(15.0),
+    ))
+}
+
+fn file_info<'a>(video: &library::Video) -> iced::Element<'a, HomeMessage> {
+    let rows = [
+        (
+            "File",
+            video
+                .path
+                .file_name()
+                .and_then(|name| name.to_str())
+                .map(String::from),
+        ),
+        (
+            "Directory",
+            video
+                .path
+                .parent()
+                .and_then(|parent| parent.to_str())
+                .map(String::from),
+        ),
+        (
+            "Format",
+            video
+                .path
+                .extension()
+                .and_then(|ext| ext.to_str())
+                .map(|ext| ext.to_uppercase()),
+        ),
+        ("Added", Some(video.added.format("%e %B %Y").to_string())),
+        (
+            "Last watched",
+            video
+                .last_watched
+                .map(|last| last.format("%e %B %Y").to_string()),
+        ),
+    ];
+
+    column![]
+        .spacing(5.0)
+        .extend(rows.into_iter().filter_map(|(label, value)| {
+            Some(
+                row![]
+                    .spacing(10.0)
+                    .push(text(label).width(120.0).color(greyscale(150)))
+                    .push(text(value?))
+                    .into(),
+            )
+        }))
+        .into()
+}
+
+fn collections_section<'a>(
+    id: library::MediaId,
+    library: &library::Library,
+) -> Option<iced::Element<'a, HomeMessage>> {
+    let collections = library
+        .iter_collections()
+        .filter(|(_, collection)| collection.contains(id))
+        .map(|(_, collection)| collection.name())
+        .sorted()
+        .join(", ");
+
+    (!collections.is_empty()).then(|| section("Collections", text(collections)))
+}
+
+fn format_runtime(minutes: u32) -> String {
+    match (minutes / 60, minutes % 60) {
+        (0, minutes) => format!("{}m", minutes),
+        (hours, 0) => format!("{}h", hours),
+        (hours, minutes) => format!("{}h {}m", hours, minutes),
+    }
+}
+
+fn format_position(seconds: f32) -> String {
+    let seconds = seconds as u64;
+    if seconds >= 3600 {
+        format!(
+            "{}:{:02}:{:02}",
+            seconds / 3600,
+            seconds % 3600 / 60,
+            seconds % 60
+        )
+    } else {
+        format!("{}:{:02}", seconds / 60, seconds % 60)
+    }
+}
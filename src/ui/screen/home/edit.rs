@@ -0,0 +1,435 @@
+use super::HomeMessage;
+use crate::{
+    library,
+    ui::{
+        AppState, HEADER_FONT, greyscale, icon, modal, themed_button, themed_menu,
+        themed_text_input, truncate_text,
+    },
+};
+use chrono::NaiveDate;
+use iced::widget::{button, column, container, row, space, text, text_input, tooltip};
+use rfd::AsyncFileDialog;
+use rustc_hash::FxHashSet;
+use std::path::PathBuf;
+
+pub struct EditMetadata {
+    id: library::MediaId,
+    metadata: library::Metadata,
+
+    title: String,
+    year: String,
+    released: String,
+    season: String,
+    episode: String,
+    poster: Option<PathBuf>,
+    locked: FxHashSet<library::Field>,
+
+    dialog_open: bool,
+    /// Whether a poster was imported, which the library only refers to once saved.
+    imported: bool,
+    error: Option<String>,
+    storage: PathBuf,
+}
+
+impl EditMetadata {
+    pub fn new(id: library::MediaId, state: &AppState) -> Option<Self> {
+        let metadata = state.library.get(id)?.metadata()?;
+
+        let (title, year, released, season, episode, poster, locked) = match &metadata {
+            library::Metadata::Movie(movie) => (
+                movie.title.clone(),
+                Some(movie.year),
+                movie.released,
+                None,
+                None,
+                movie.poster.clone(),
+                movie.locked.clone(),
+            ),
+            library::Metadata::Series(series) => (
+                series.title.clone(),
+                None,
+                series.aired,
+                None,
+                None,
+                series.poster.clone(),
+                series.locked.clone(),
+            ),
+            library::Metadata::Season(season) => (
+                season.title.clone(),
+                None,
+                season.aired,
+                Some(season.season),
+                None,
+                season.poster.clone(),
+                season.locked.clone(),
+            ),
+            library::Metadata::Episode(episode) => (
+                episode.title.clone(),
+                None,
+                episode.aired,
+                Some(episode.season),
+                Some(episode.episode),
+                None,
+                episode.locked.clone(),
+            ),
+        };
+
+        let to_string = |value: Option<u16>| value.map(|x| x.to_string()).unwrap_or_default();
+
+        Some(EditMetadata {
+            id,
+            metadata,
+
+            title,
+            year: to_string(year),
+            released: released
+                .map(|date| date.format("%Y-%m-%d").to_string())
+                .unwrap_or_default(),
+            season: to_string(season),
+            episode: to_string(episode),
+            poster,
+            locked,
+
+            dialog_open: false,
+            imported: false,
+            error: None,
+            storage: state.storage_path.clone(),
+        })
+    }
+
+    fn fields(&self) -> &'static [library::Field] {
+        use library::Field::*;
+        match self.metadata {
+            library::Metadata::Movie(_) => &[Title, Year, Released, Poster],
+            library::Metadata::Series(_) => &[Title, Released, Poster],
+            library::Metadata::Season(_) => &[Title, Season, Released, Poster],
+            library::Metadata::Episode(_) => &[Title, Season, Episode, Released],
+        }
+    }
+
+    /// Builds the edited metadata, or an error describing the first invalid field.
+    fn build(&self) -> Result<library::Metadata, String> {
+        let title = self.title.trim().to_string();
+        if title.is_empty() {
+            return Err("Title cannot be empty".into());
+        }
+        let released = match self.released.trim() {
+            "" => None,
+            date => Some(
+                NaiveDate::parse_from_str(date, "%Y-%m-%d")
+                    .map_err(|_| "Date must be formatted as YYYY-MM-DD".to_string())?,
+            ),
+        };
+        let number = |value: &str, name: &str| {
+            value
+                .trim()
+                .parse::<u16>()
+                .map_err(|_| format!("{} must be a number", name))
+        };
+        let locked = self.locked.clone();
+
+        Ok(match &self.metadata {
+            library::Metadata::Movie(movie) => library::Metadata::Movie(library::MovieMetadata {
+                title,
+                year: number(&self.year, "Year")?,
+                released,
+                poster: self.poster.clone(),
+                locked,
+                ..movie.clone()
+            }),
+            library::Metadata::Series(series) => {
+                library::Metadata::Series(library::SeriesMetadata {
+                    title,
+                    aired: released,
+                    poster: self.poster.clone(),
+                    locked,
+                    ..series.clone()
+                })
+            }
+            library::Metadata::Season(season) => {
+                library::Metadata::Season(library::SeasonMetadata {
+                    title,
+                    season: number(&self.season, "Season")?,
+                    aired: released,
+                    poster: self.poster.clone(),
+                    locked,
+                    ..season.clone()
+                })
+            }
+            library::Metadata::Episode(episode) => {
+                library::Metadata::Episode(library::EpisodeMetadata {
+                    title,
+                    season: number(&self.season, "Season")?,
+                    episode: number(&self.episode, "Episode")?,
+                    aired: released,
+                    locked,
+                    ..episode.clone()
+                })
+            }
+        })
+    }
+
+    pub fn holds_poster(&self) -> bool {
+        self.dialog_open || self.imported
+    }
+
+    pub fn update(
+        &mut self,
+        message: EditMetadataMessage,
+        state: &mut AppState,
+    ) -> iced::Task<HomeMessage> {
+        match message {
+            EditMetadataMessage::Input(field, value) => {
+                if matches!(
+                    field,
+                    library::Field::Year | library::Field::Season | library::Field::Episode
+                ) && !value.chars().all(|c| c.is_ascii_digit())
+                {
+                    return iced::Task::none();
+                }
+
+                match field {
+                    library::Field::Title => self.title = value,
+                    library::Field::Year => self.year = value,
+                    library::Field::Released => self.released = value,
+                    library::Field::Season => self.season = value,
+                    library::Field::Episode => self.episode = value,
+                    library::Field::Poster => {}
+                }
+                // anything edited by hand shouldn't be undone by the next scrape
+                self.locked.insert(field);
+                iced::Task::none()
+            }
+            EditMetadataMessage::ToggleLock(field) => {
+                if !self.locked.remove(&field) {
+                    self.locked.insert(field);
+                }
+                iced::Task::none()
+            }
+            EditMetadataMessage::ChoosePoster => {
+                if self.dialog_open {
+                    return iced::Task::none();
+                }
+                if state.collecting_artwork {
+                    self.error = Some(String::from(
+                        "Unused artwork is being removed, try again in a moment",
+                    ));
+                    return iced::Task::none();
+                }
+
+                self.dialog_open = true;
+                let storage = self.storage.clone();
+                iced::Task::perform(
+                    async move {
+                        let Some(handle) = AsyncFileDialog::new()
+                            .add_filter("Images", &["jpg", "jpeg", "png", "webp"])
+                            .pick_file()
+                            .await
+                        else {
+                            return Ok(None);
+                        };
+                        library::import_poster(&storage, handle.path())
+                            .await
+                            .map(Some)
+                            .map_err(|err| err.to_string())
+                    },
+                    |result| HomeMessage::EditMetadata(EditMetadataMessage::PosterChosen(result)),
+                )
+            }
+            EditMetadataMessage::PosterChosen(result) => {
+                self.dialog_open = false;
+                match result {
+                    Ok(Some(poster)) => {
+                        self.poster = Some(poster);
+                        self.imported = true;
+                        self.locked.insert(library::Field::Poster);
+                    }
+                    Ok(None) => {}
+                    Err(err) => self.error = Some(err),
+                }
+                iced::Task::none()
+            }
+            EditMetadataMessage::ClearPoster => {
+                self.poster = None;
+                self.locked.insert(library::Field::Poster);
+                iced::Task::none()
+            }
+            EditMetadataMessage::Save => {
+                let result = self.build().and_then(|metadata| {
+                    state
+                        .library
+                        .set_metadata(self.id, metadata)
+                        .map_err(|err| err.to_string())
+                });
+
+                match result {
+                    Ok(()) => iced::Task::batch([
+                        iced::Task::done(HomeMessage::EditMetadata(EditMetadataMessage::Close)),
+                        iced::Task::done(HomeMessage::LibraryChanged),
+                    ]),
+                    Err(err) => {
+                        self.error = Some(err);
+                        iced::Task::none()
+                    }
+                }
+            }
+            EditMetadataMessage::Close => iced::Task::none(),
+        }
+    }
+
+    pub fn view<'a>(&'a self, library: &library::Library) -> iced::Element<'a, HomeMessage> {
+        let field_row = |field: library::Field| -> iced::Element<'a, HomeMessage> {
+            let locked = self.locked.contains(&field);
+            let label = match field {
+                library::Field::Title => "Title",
+                library::Field::Year => "Year",
+                library::Field::Released => match self.metadata {
+                    library::Metadata::Movie(_) => "Release date",
+                    _ => "Air date",
+                },
+                library::Field::Season => "Season",
+                library::Field::Episode => "Episode",
+                library::Field::Poster => "Poster",
+            };
+
+            let input: iced::Element<'a, HomeMessage> =
+                match field {
+                    library::Field::Poster => row![]
+                        .spacing(5.0)
+                        .align_y(iced::Alignment::Center)
+                        .push(
+                            text(
+                                self.poster
+                                    .as_ref()
+                                    .and_then(|path| path.file_name())
+                                    .and_then(|name| name.to_str())
+                                    .map(|name| truncate_text(name, 30))
+                                    .unwrap_or_else(|| "None".into()),
+                            )
+                            .color(greyscale(170)),
+                        )
+                        .push(space::horizontal())
+                        .push(button("Choose...").style(themed_button).on_press_maybe(
+                            (!self.dialog_open).then_some(HomeMessage::EditMetadata(
+                                EditMetadataMessage::ChoosePoster,
+                            )),
+                        ))
+                        .push(button("Clear").style(themed_button).on_press_maybe(
+                            self.poster.is_some().then_some(HomeMessage::EditMetadata(
+                                EditMetadataMessage::ClearPoster,
+                            )),
+                        ))
+                        .into(),
+                    _ => text_input(
+                        if field == library::Field::Released {
+                            "YYYY-MM-DD"
+                        } else {
+                            ""
+                        },
+                        match field {
+                            library::Field::Year => &self.year,
+                            library::Field::Released => &self.released,
+                            library::Field::Season => &self.season,
+                            library::Field::Episode => &self.episode,
+                            _ => &self.title,
+                        },
+                    )
+                    .on_input(move |value| {
+                        HomeMessage::EditMetadata(EditMetadataMessage::Input(field, value))
+                    })
+                    .on_submit(HomeMessage::EditMetadata(EditMetadataMessage::Save))
+                    .style(themed_text_input)
+                    .into(),
+                };
+
+            row![]
+                .spacing(10.0)
+                .align_y(iced::Alignment::Center)
+                .push(text(label).width(110.0))
+                .push(container(input).width(iced::Length::Fill))
+                .push(tooltip(
+                    button(icon(if locked { 0xe897 } else { 0xe898 }).size(18.0).color(
+                        if locked {
+                            greyscale(230)
+                        } else {
+                            greyscale(110)
+                        },
+                    ))
+                    .style(themed_button)
+                    .on_press(HomeMessage::EditMetadata(
+                        EditMetadataMessage::ToggleLock(field),
+                    )),
+                    container(text(if locked {
+                        "Locked: kept when metadata is scraped again"
+                    } else {
+                        "Unlocked: may be replaced when metadata is scraped again"
+                    }))
+                    .padding(5.0)
+                    .style(themed_menu),
+                    tooltip::Position::Left,
+                ))
+                .into()
+        };
+
+        let close = HomeMessage::EditMetadata(EditMetadataMessage::Close);
+
+        modal(
+            container(
+                column![]
+                    .spacing(10.0)
+                    .push(
+                        row![]
+                            .align_y(iced::Alignment::Center)
+                            .push(
+                                text(format!(
+                                    "Edit \"{}\"",
+                                    truncate_text(&library::full_title(self.id, library), 40)
+                                ))
+                                .font(HEADER_FONT)
+                                .size(20.0),
+                            )
+                            .push(space::horizontal())
+                            .push(
+                                button(icon(0xe5cd).size(20.0))
+                                    .style(themed_button)
+                                    .on_press(close.clone()),
+                            ),
+                    )
+                    .extend(self.fields().iter().copied().map(field_row))
+                    .push(self.error.as_ref().map(|error| {
+                        text(error.clone()).color(iced::Color::from_rgb8(237, 71, 71))
+                    }))
+                    .push(
+                        row![]
+                            .spacing(10.0)
+                            .push(space::horizontal())
+                            .push(
+                                button("Cancel")
+                                    .style(themed_button)
+                                    .on_press(close.clone()),
+                            )
+                            .push(
+                                button("Save")
+                                    .style(button::primary)
+                                    .on_press(HomeMessage::EditMetadata(EditMetadataMessage::Save)),
+                            ),
+                    ),
+            )
+            .width(520.0)
+            .padding(15.0)
+            .style(themed_menu),
+            close,
+        )
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum EditMetadataMessage {
+    Input(library::Field, String),
+    ToggleLock(library::Field),
+    ChoosePoster,
+    PosterChosen(Result<Option<PathBuf>, String>),
+    ClearPoster,
+    Save,
+    Close,
+}
@@ -0,0 +1,169 @@
+use super::HomeMessage;
+use crate::{
+    library,
+    ui::{AppState, HEADER_FONT, icon, modal, themed_button, themed_menu, truncate_text},
+};
+use iced::widget::{button, column, container, pick_list, row, space, text};
+
+pub struct EpisodeOrder {
+    id: library::MediaId,
+    orders: Vec<library::EpisodeOrder>,
+    selected: library::EpisodeOrder,
+    loading: bool,
+    error: Option<String>,
+}
+
+impl EpisodeOrder {
+    pub fn new(id: library::MediaId, state: &AppState) -> (Self, iced::Task<HomeMessage>) {
+        let Some(library::Media::Series(series)) = state.library.get(id) else {
+            return (
+                EpisodeOrder {
+                    id,
+                    orders: vec![],
+                    selected: library::EpisodeOrder::Aired,
+                    loading: false,
+                    error: None,
+                },
+                iced::Task::none(),
+            );
+        };
+
+        let source = series.metadata.source();
+        let scraper = state.scraper_chain(state.language_of(id));
+        let task = iced::Task::perform(
+            async move {
+                library::Scraper::episode_orders(&scraper, source)
+                    .await
+                    .map_err(|err| format!("{:#}", err))
+            },
+            |result| HomeMessage::EpisodeOrder(EpisodeOrderMessage::Loaded(result)),
+        );
+
+        let dialog = EpisodeOrder {
+            id,
+            orders: vec![library::EpisodeOrder::Aired],
+            selected: series.episode_order.clone(),
+            loading: true,
+            error: None,
+        };
+        (dialog, task)
+    }
+
+    pub fn update(
+        &mut self,
+        message: EpisodeOrderMessage,
+        state: &mut AppState,
+    ) -> iced::Task<HomeMessage> {
+        match message {
+            EpisodeOrderMessage::Loaded(result) => {
+                self.loading = false;
+                match result {
+                    Ok(orders) => {
+                        self.orders = std::iter::once(library::EpisodeOrder::Aired)
+                            .chain(orders)
+                            .collect();
+                    }
+                    Err(err) => self.error = Some(err),
+                }
+                iced::Task::none()
+            }
+            EpisodeOrderMessage::Select(order) => {
+                self.selected = order;
+                iced::Task::none()
+            }
+            EpisodeOrderMessage::Apply => {
+                let close = iced::Task::done(HomeMessage::EpisodeOrder(EpisodeOrderMessage::Close));
+                if !state
+                    .library
+                    .set_episode_order(self.id, self.selected.clone())
+                {
+                    return close;
+                }
+                iced::Task::batch([
+                    close,
+                    iced::Task::done(HomeMessage::LibraryChanged),
+                    iced::Task::done(HomeMessage::RefreshMetadata(self.id)),
+                ])
+            }
+            EpisodeOrderMessage::Close => iced::Task::none(),
+        }
+    }
+
+    pub fn view<'a>(&'a self, library: &library::Library) -> iced::Element<'a, HomeMessage> {
+        let note = if self.loading {
+            Some(String::from("Loading orderings..."))
+        } else if self.orders.len() == 1 && self.error.is_none() {
+            Some(String::from("No other orderings are known for this show."))
+        } else {
+            None
+        };
+
+        modal(
+            container(
+                column![]
+                    .spacing(10.0)
+                    .push(
+                        row![]
+                            .align_y(iced::Alignment::Center)
+                            .push(
+                                text(format!(
+                                    "Episode order of \"{}\"",
+                                    truncate_text(&library::full_title(self.id, library), 30)
+                                ))
+                                .font(HEADER_FONT)
+                                .size(20.0),
+                            )
+                            .push(space::horizontal())
+                            .push(
+                                button(icon(0xe5cd).size(20.0))
+                                    .style(themed_button)
+                                    .on_press(HomeMessage::EpisodeOrder(
+                                        EpisodeOrderMessage::Close,
+                                    )),
+                            ),
+                    )
+                    .push(text(
+                        "Match the order your files are numbered in. Episodes keep their \
+                         watch progress when the order changes.",
+                    ))
+                    .push(
+                        pick_list(self.orders.clone(), Some(self.selected.clone()), |order| {
+                            HomeMessage::EpisodeOrder(EpisodeOrderMessage::Select(order))
+                        })
+                        .width(iced::Length::Fill),
+                    )
+                    .push(note.map(|note| text(note).size(14.0)))
+                    .push(self.error.as_ref().map(|error| {
+                        text(error.clone()).color(iced::Color::from_rgb8(237, 71, 71))
+                    }))
+                    .push(
+                        row![]
+                            .spacing(10.0)
+                            .push(space::horizontal())
+                            .push(
+                                button("Cancel").style(themed_button).on_press(
+                                    HomeMessage::EpisodeOrder(EpisodeOrderMessage::Close),
+                                ),
+                            )
+                            .push(
+                                button("Apply").style(button::primary).on_press(
+                                    HomeMessage::EpisodeOrder(EpisodeOrderMessage::Apply),
+                                ),
+                            ),
+                    ),
+            )
+            .width(420.0)
+            .padding(15.0)
+            .style(themed_menu),
+            HomeMessage::EpisodeOrder(EpisodeOrderMessage::Close),
+        )
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum EpisodeOrderMessage {
+    Loaded(Result<Vec<library::EpisodeOrder>, String>),
+    Select(library::EpisodeOrder),
+    Apply,
+    Close,
+}
@@ -0,0 +1,542 @@
+use super::HomeMessage;
+use crate::{
+    library,
+    ui::{
+        AppState, HEADER_FONT, greyscale, icon, modal, themed_button, themed_menu,
+        themed_scrollable, themed_text_input, truncate_text,
+    },
+};
+use iced::widget::{
+    button, center, column, container, image, row, scrollable, space, text, text_input,
+};
+use rustc_hash::FxHashMap;
+use std::{path::PathBuf, time::Duration};
+
+pub struct Identify {
+    id: library::MediaId,
+    kind: library::MediaKind,
+    title: String,
+    year: String,
+    season: String,
+    episode: String,
+
+    results: Vec<library::SearchResult>,
+    thumbnails: FxHashMap<(library::MediaKind, library::ProviderId), image::Handle>,
+    selected: Option<library::ProviderId>,
+
+    searching: bool,
+    applying: bool,
+    error: Option<String>,
+    search_task: Option<iced::task::Handle>, // to debounce searches
+
+    scraper: library::ScraperChain,
+    /// Fetches result thumbnails, whichever provider they're from.
+    thumbnailer: library::TmdbScraper,
+    storage: PathBuf,
+}
+
+impl Identify {
+    pub fn new(id: library::MediaId, state: &AppState) -> (Self, iced::Task<HomeMessage>) {
+        let (kind, title, year, season, episode) = prefill(id, &state.library);
+
+        let mut identify = Identify {
+            id,
+            kind,
+            title,
+            year: year.map(|year| year.to_string()).unwrap_or_default(),
+            season: season.to_string(),
+            episode: episode.to_string(),
+
+            results: vec![],
+            thumbnails: FxHashMap::default(),
+            selected: None,
+
+            searching: false,
+            applying: false,
+            error: None,
+            search_task: None,
+
+            scraper: state.scraper_chain(state.language_of(id)),
+            thumbnailer: state.scraper(state.language_of(id)),
+            storage: state.storage_path.clone(),
+        };
+        let task = identify.search(Duration::ZERO);
+
+        (identify, task)
+    }
+
+    fn search(&mut self, delay: Duration) -> iced::Task<HomeMessage> {
+        if let Some(task) = self.search_task.take() {
+            task.abort();
+        }
+
+        let title = self.title.trim().to_string();
+        if title.is_empty() {
+            self.searching = false;
+            return iced::Task::none();
+        }
+
+        self.searching = true;
+
+        let kind = self.kind;
+        let year = self.year.trim().parse().ok();
+        let scraper = self.scraper.clone();
+        let (task, handle) = iced::Task::perform(
+            async move {
+                async_std::task::sleep(delay).await;
+                let (results, errors) = scraper.search_all(kind, &title, year).await;
+                let errors = errors.iter().map(|err| format!("{:#}", err)).collect();
+                (results, errors)
+            },
+            move |(results, errors)| {
+                HomeMessage::Identify(IdentifyMessage::SearchComplete(kind, results, errors))
+            },
+        )
+        .abortable();
+        self.search_task = Some(handle);
+        task
+    }
+
+    fn identification(&self) -> Option<library::Identification> {
+        let id = self.selected?;
+        Some(match self.kind {
+            library::MediaKind::Movie => library::Identification::Movie { id },
+            library::MediaKind::Series => library::Identification::Episode {
+                series: id,
+                season: self.season.trim().parse().ok()?,
+                episode: self.episode.trim().parse().ok()?,
+            },
+        })
+    }
+
+    pub fn applying(&self) -> bool {
+        self.applying
+    }
+
+    pub fn update(
+        &mut self,
+        message: IdentifyMessage,
+        state: &AppState,
+    ) -> iced::Task<HomeMessage> {
+        match message {
+            IdentifyMessage::SetKind(kind) => {
+                if self.kind == kind {
+                    return iced::Task::none();
+                }
+                self.kind = kind;
+                self.results.clear();
+                self.selected = None;
+                self.search(Duration::ZERO)
+            }
+            IdentifyMessage::TitleInput(title) => {
+                self.title = title;
+                self.search(Duration::from_millis(400))
+            }
+            IdentifyMessage::YearInput(year) => {
+                if !year.chars().all(|c| c.is_ascii_digit()) {
+                    return iced::Task::none();
+                }
+                self.year = year;
+                self.search(Duration::from_millis(400))
+            }
+            IdentifyMessage::SeasonInput(season) => {
+                if season.chars().all(|c| c.is_ascii_digit()) {
+                    self.season = season;
+                }
+                iced::Task::none()
+            }
+            IdentifyMessage::EpisodeInput(episode) => {
+                if episode.chars().all(|c| c.is_ascii_digit()) {
+                    self.episode = episode;
+                }
+                iced::Task::none()
+            }
+            IdentifyMessage::Search => self.search(Duration::ZERO),
+            IdentifyMessage::SearchComplete(kind, results, errors) => {
+                self.searching = false;
+                self.search_task = None;
+
+                if kind != self.kind {
+                    return iced::Task::none();
+                }
+
+                // results from the providers that did answer are still shown
+                self.error = (!errors.is_empty()).then(|| errors.join("\n"));
+                self.selected = None;
+                self.results = results;
+
+                iced::Task::batch(self.results.iter().filter_map(|result| {
+                    if self.thumbnails.contains_key(&(kind, result.id)) {
+                        return None;
+                    }
+                    let poster = result.poster.clone()?;
+                    let id = result.id;
+                    let thumbnailer = self.thumbnailer.clone();
+                    Some(iced::Task::perform(
+                        async move { thumbnailer.fetch_thumbnail(&poster).await },
+                        move |bytes| match bytes {
+                            Ok(bytes) => HomeMessage::Identify(IdentifyMessage::ThumbnailLoaded(
+                                kind,
+                                id,
+                                image::Handle::from_bytes(bytes),
+                            )),
+                            Err(_) => HomeMessage::None,
+                        },
+                    ))
+                }))
+            }
+            IdentifyMessage::ThumbnailLoaded(kind, id, handle) => {
+                self.thumbnails.insert((kind, id), handle);
+                iced::Task::none()
+            }
+            IdentifyMessage::Select(id) => {
+                self.selected = Some(id);
+                iced::Task::none()
+            }
+            IdentifyMessage::Apply => {
+                let Some(identification) = self.identification() else {
+                    return iced::Task::none();
+                };
+                if state.collecting_artwork {
+                    self.error = Some(String::from(
+                        "Unused artwork is being removed, try again in a moment",
+                    ));
+                    return iced::Task::none();
+                }
+
+                self.applying = true;
+                self.error = None;
+
+                let id = self.id;
+                let scraper = self.scraper.clone();
+                let storage = self.storage.clone();
+                // a series already in the library keeps its ordering
+                let order = match &identification {
+                    library::Identification::Episode { series, .. } => {
+                        state.library.episode_order(*series)
+                    }
+                    library::Identification::Movie { .. } => library::EpisodeOrder::Aired,
+                };
+                iced::Task::perform(
+                    async move {
+                        library::identify(&scraper, &storage, id, identification, &order)
+                            .await
+                            .map_err(|err| format!("{:#}", err))
+                    },
+                    |result| HomeMessage::Identify(IdentifyMessage::Applied(result)),
+                )
+            }
+            IdentifyMessage::Applied(result) => {
+                // success is handled by the home screen, which owns the dialog
+                self.applying = false;
+                if let Err(err) = result {
+                    self.error = Some(err);
+                }
+                iced::Task::none()
+            }
+            IdentifyMessage::Close => iced::Task::none(),
+        }
+    }
+
+    pub fn view<'a>(&'a self, library: &library::Library) -> iced::Element<'a, HomeMessage> {
+        let kind_button = |kind: library::MediaKind, label: &'a str| {
+            let selected = self.kind == kind;
+            button(text(label))
+                .style(move |theme, status| {
+                    let mut style = themed_button(theme, status);
+                    if selected {
+                        style.background = Some(iced::Background::Color(
+                            theme.palette().primary.scale_alpha(0.5),
+                        ));
+                    }
+                    style
+                })
+                .on_press(HomeMessage::Identify(IdentifyMessage::SetKind(kind)))
+        };
+
+        let error_text =
+            |error: &String| text(error.clone()).color(iced::Color::from_rgb8(237, 71, 71));
+        let status: Option<iced::Element<'a, HomeMessage>> =
+            if let Some(error) = self.error.as_ref().filter(|_| self.results.is_empty()) {
+                Some(error_text(error).into())
+            } else if self.searching {
+                Some(text("Searching...").color(greyscale(130)).into())
+            } else if self.results.is_empty() {
+                Some(text("No results").color(greyscale(130)).into())
+            } else {
+                None
+            };
+
+        let footer = row![]
+            .spacing(10.0)
+            .align_y(iced::Alignment::Center)
+            .push((self.kind == library::MediaKind::Series).then(|| {
+                row![]
+                    .spacing(5.0)
+                    .align_y(iced::Alignment::Center)
+                    .push("Season")
+                    .push(
+                        text_input("", &self.season)
+                            .width(50.0)
+                            .on_input(|season| {
+                                HomeMessage::Identify(IdentifyMessage::SeasonInput(season))
+                            })
+                            .style(themed_text_input),
+                    )
+                    .push("Episode")
+                    .push(
+                        text_input("", &self.episode)
+                            .width(50.0)
+                            .on_input(|episode| {
+                                HomeMessage::Identify(IdentifyMessage::EpisodeInput(episode))
+                            })
+                            .style(themed_text_input),
+                    )
+            }))
+            .push(space::horizontal())
+            .push(button("Cancel").style(themed_button).on_press_maybe(
+                (!self.applying).then_some(HomeMessage::Identify(IdentifyMessage::Close)),
+            ))
+            .push(
+                button(if self.applying {
+                    "Identifying..."
+                } else {
+                    "Identify"
+                })
+                .style(button::primary)
+                .on_press_maybe(
+                    (!self.applying && self.identification().is_some())
+                        .then_some(HomeMessage::Identify(IdentifyMessage::Apply)),
+                ),
+            );
+
+        modal(
+            container(
+                column![]
+                    .spacing(10.0)
+                    .push(
+                        row![]
+                            .align_y(iced::Alignment::Center)
+                            .push(
+                                text(format!(
+                                    "Identify \"{}\"",
+                                    truncate_text(&library::full_title(self.id, library), 40)
+                                ))
+                                .font(HEADER_FONT)
+                                .size(20.0),
+                            )
+                            .push(space::horizontal())
+                            .push(
+                                button(icon(0xe5cd).size(20.0))
+                                    .style(themed_button)
+                                    .on_press_maybe(
+                                        (!self.applying).then_some(HomeMessage::Identify(
+                                            IdentifyMessage::Close,
+                                        )),
+                                    ),
+                            ),
+                    )
+                    .push(
+                        row![]
+                            .spacing(5.0)
+                            .align_y(iced::Alignment::Center)
+                            .push(kind_button(library::MediaKind::Movie, "Movie"))
+                            .push(kind_button(library::MediaKind::Series, "TV Show"))
+                            .push(
+                                text_input("Title", &self.title)
+                                    .on_input(|title| {
+                                        HomeMessage::Identify(IdentifyMessage::TitleInput(title))
+                                    })
+                                    .on_submit(HomeMessage::Identify(IdentifyMessage::Search))
+                                    .style(themed_text_input),
+                            )
+                            .push(
+                                text_input("Year", &self.year)
+                                    .width(70.0)
+                                    .on_input(|year| {
+                                        HomeMessage::Identify(IdentifyMessage::YearInput(year))
+                                    })
+                                    .on_submit(HomeMessage::Identify(IdentifyMessage::Search))
+                                    .style(themed_text_input),
+                            ),
+                    )
+                    .push(
+                        container(match status {
+                            Some(status) => center(status).into(),
+                            None => iced::Element::from(
+                                scrollable(
+                                    column![]
+                                        .spacing(5.0)
+                                        .padding(iced::Padding::new(5.0).right(15.0))
+                                        .extend(
+                                            self.results.iter().map(|result| self.result(result)),
+                                        ),
+                                )
+                                .style(themed_scrollable)
+                                .height(iced::Length::Fill),
+                            ),
+                        })
+                        .height(iced::Length::Fill),
+                    )
+                    // some providers failed, but others had results
+                    .push(
+                        self.error
+                            .as_ref()
+                            .filter(|_| !self.results.is_empty())
+                            .map(|error| error_text(error).size(14.0)),
+                    )
+                    .push(footer),
+            )
+            .width(640.0)
+            .height(560.0)
+            .padding(15.0)
+            .style(themed_menu),
+            HomeMessage::Identify(IdentifyMessage::Close),
+        )
+    }
+
+    fn result<'a>(&'a self, result: &'a library::SearchResult) -> iced::Element<'a, HomeMessage> {
+        let selected = self.selected == Some(result.id);
+        let thumbnail = self.thumbnails.get(&(self.kind, result.id));
+
+        button(
+            row![]
+                .spacing(10.0)
+                .push(
+                    container(thumbnail.map(|handle| {
+                        image(handle.clone())
+                            .content_fit(iced::ContentFit::Cover)
+                            .width(iced::Length::Fill)
+                            .height(iced::Length::Fill)
+                    }))
+                    .width(46.0)
+                    .height(69.0)
+                    .clip(true)
+                    .style(|_| container::Style {
+                        background: Some(iced::Background::Color(greyscale(40))),
+                        border: iced::Border::default().rounded(3.0),
+                        ..Default::default()
+                    }),
+                )
+                .push(
+                    column![]
+                        .spacing(3.0)
+                        .push(
+                            text(match result.year {
+                                Some(year) => format!("{} ({})", result.title, year),
+                                None => result.title.clone(),
+                            })
+                            .font(HEADER_FONT),
+                        )
+                        .push(
+                            text(result.id.provider.name())
+                                .size(12.0)
+                                .color(greyscale(130)),
+                        )
+                        .push(
+                            text(truncate_text(&result.overview, 200))
+                                .size(12.0)
+                                .color(greyscale(170)),
+                        ),
+                ),
+        )
+        .width(iced::Length::Fill)
+        .padding(5.0)
+        .style(move |theme, status| {
+            let mut style = themed_button(theme, status);
+            if selected {
+                style.background = Some(iced::Background::Color(
+                    theme.palette().primary.scale_alpha(0.3),
+                ));
+            }
+            style
+        })
+        .on_press(HomeMessage::Identify(IdentifyMessage::Select(result.id)))
+        .into()
+    }
+}
+
+/// Best initial guess for the search fields, from existing metadata or the file name.
+fn prefill(
+    id: library::MediaId,
+    library: &library::Library,
+) -> (library::MediaKind, String, Option<u16>, u16, u16) {
+    match library.get(id) {
+        Some(library::Media::Movie(movie)) => (
+            library::MediaKind::Movie,
+            movie.metadata.title.clone(),
+            Some(movie.metadata.year),
+            1,
+            1,
+        ),
+        Some(library::Media::Episode(episode)) => {
+            let series = library.get(episode.series);
+            (
+                library::MediaKind::Series,
+                series.map(|series| series.title()).unwrap_or_default(),
+                series.and_then(|series| series.year()),
+                episode.metadata.season,
+                episode.metadata.episode,
+            )
+        }
+        Some(media) => {
+            let file_name = media
+                .video()
+                .and_then(|video| video.path.file_name())
+                .and_then(|name| name.to_str())
+                .unwrap_or_default();
+            match library::detect_media_type(file_name) {
+                library::MediaType::Movie { title, year } => {
+                    (library::MediaKind::Movie, title, Some(year), 1, 1)
+                }
+                library::MediaType::Episode {
+                    series_title,
+                    year,
+                    season,
+                    episode,
+                } => (
+                    library::MediaKind::Series,
+                    series_title,
+                    year,
+                    season,
+                    episode,
+                ),
+                library::MediaType::AbsoluteEpisode {
+                    series_title,
+                    year,
+                    episode,
+                } => (library::MediaKind::Series, series_title, year, 1, episode),
+                library::MediaType::Unknown => (
+                    library::MediaKind::Movie,
+                    media
+                        .video()
+                        .and_then(|video| video.path.file_stem())
+                        .and_then(|stem| stem.to_str())
+                        .unwrap_or_default()
+                        .to_string(),
+                    None,
+                    1,
+                    1,
+                ),
+            }
+        }
+        None => (library::MediaKind::Movie, String::new(), None, 1, 1),
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum IdentifyMessage {
+    SetKind(library::MediaKind),
+    TitleInput(String),
+    YearInput(String),
+    SeasonInput(String),
+    EpisodeInput(String),
+    Search,
+    /// The results of every provider, and the errors of those that couldn't be searched.
+    SearchComplete(library::MediaKind, Vec<library::SearchResult>, Vec<String>),
+    ThumbnailLoaded(library::MediaKind, library::ProviderId, image::Handle),
+    Select(library::ProviderId),
+    Apply,
+    Applied(Result<library::ScrapeResult, String>),
+    Close,
+}
@@ -0,0 +1,206 @@
+use super::HomeMessage;
+use crate::{
+    library,
+    ui::{
+        AppState, HEADER_FONT, icon, modal, themed_button, themed_menu, themed_text_input,
+        truncate_text,
+    },
+};
+use iced::widget::{button, column, container, pick_list, row, space, text, text_input};
+use itertools::Itertools;
+use std::fmt;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SeriesOption {
+    id: library::MediaId,
+    title: String,
+}
+
+impl fmt::Display for SeriesOption {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.title)
+    }
+}
+
+pub struct MoveTo {
+    id: library::MediaId,
+    series: Option<SeriesOption>,
+    season: String,
+    episode: String,
+    error: Option<String>,
+}
+
+impl MoveTo {
+    pub fn new(id: library::MediaId, state: &AppState) -> Self {
+        let (series, season, episode) = match state.library.get(id) {
+            Some(library::Media::Episode(episode)) => (
+                Some(SeriesOption {
+                    id: episode.series,
+                    title: library::full_title(episode.series, &state.library),
+                }),
+                episode.metadata.season,
+                episode.metadata.episode,
+            ),
+            _ => (None, 1, 1),
+        };
+
+        MoveTo {
+            id,
+            series,
+            season: season.to_string(),
+            episode: episode.to_string(),
+            error: None,
+        }
+    }
+
+    pub fn update(
+        &mut self,
+        message: MoveToMessage,
+        state: &mut AppState,
+    ) -> iced::Task<HomeMessage> {
+        match message {
+            MoveToMessage::SelectSeries(series) => {
+                self.series = Some(series);
+                iced::Task::none()
+            }
+            MoveToMessage::SeasonInput(season) => {
+                if season.chars().all(|c| c.is_ascii_digit()) {
+                    self.season = season;
+                }
+                iced::Task::none()
+            }
+            MoveToMessage::EpisodeInput(episode) => {
+                if episode.chars().all(|c| c.is_ascii_digit()) {
+                    self.episode = episode;
+                }
+                iced::Task::none()
+            }
+            MoveToMessage::Apply => {
+                let (Some(series), Ok(season), Ok(episode)) = (
+                    &self.series,
+                    self.season.trim().parse(),
+                    self.episode.trim().parse(),
+                ) else {
+                    return iced::Task::none();
+                };
+
+                match state
+                    .library
+                    .move_episode(self.id, series.id, season, episode)
+                {
+                    Ok(()) => iced::Task::batch([
+                        iced::Task::done(HomeMessage::MoveTo(MoveToMessage::Close)),
+                        iced::Task::done(HomeMessage::LibraryChanged),
+                    ]),
+                    Err(err) => {
+                        self.error = Some(err.to_string());
+                        iced::Task::none()
+                    }
+                }
+            }
+            MoveToMessage::Close => iced::Task::none(),
+        }
+    }
+
+    pub fn view<'a>(&'a self, library: &library::Library) -> iced::Element<'a, HomeMessage> {
+        let options: Vec<_> = library
+            .iter()
+            .filter_map(|(id, media)| match media {
+                library::Media::Series(_) => Some(SeriesOption {
+                    id: *id,
+                    title: library::full_title(*id, library),
+                }),
+                _ => None,
+            })
+            .sorted_by(|a, b| a.title.cmp(&b.title))
+            .collect();
+
+        let valid = self.series.is_some()
+            && self.season.trim().parse::<u16>().is_ok()
+            && self.episode.trim().parse::<u16>().is_ok();
+
+        modal(
+            container(
+                column![]
+                    .spacing(10.0)
+                    .push(
+                        row![]
+                            .align_y(iced::Alignment::Center)
+                            .push(
+                                text(format!(
+                                    "Move \"{}\"",
+                                    truncate_text(&library::full_title(self.id, library), 40)
+                                ))
+                                .font(HEADER_FONT)
+                                .size(20.0),
+                            )
+                            .push(space::horizontal())
+                            .push(
+                                button(icon(0xe5cd).size(20.0))
+                                    .style(themed_button)
+                                    .on_press(HomeMessage::MoveTo(MoveToMessage::Close)),
+                            ),
+                    )
+                    .push(
+                        pick_list(options, self.series.clone(), |series| {
+                            HomeMessage::MoveTo(MoveToMessage::SelectSeries(series))
+                        })
+                        .placeholder("TV Show")
+                        .width(iced::Length::Fill),
+                    )
+                    .push(
+                        row![]
+                            .spacing(5.0)
+                            .align_y(iced::Alignment::Center)
+                            .push("Season")
+                            .push(
+                                text_input("", &self.season)
+                                    .width(50.0)
+                                    .on_input(|season| {
+                                        HomeMessage::MoveTo(MoveToMessage::SeasonInput(season))
+                                    })
+                                    .style(themed_text_input),
+                            )
+                            .push("Episode")
+                            .push(
+                                text_input("", &self.episode)
+                                    .width(50.0)
+                                    .on_input(|episode| {
+                                        HomeMessage::MoveTo(MoveToMessage::EpisodeInput(episode))
+                                    })
+                                    .style(themed_text_input),
+                            ),
+                    )
+                    .push(self.error.as_ref().map(|error| {
+                        text(error.clone()).color(iced::Color::from_rgb8(237, 71, 71))
+                    }))
+                    .push(
+                        row![]
+                            .spacing(10.0)
+                            .push(space::horizontal())
+                            .push(
+                                button("Cancel")
+                                    .style(themed_button)
+                                    .on_press(HomeMessage::MoveTo(MoveToMessage::Close)),
+                            )
+                            .push(button("Move").style(button::primary).on_press_maybe(
+                                valid.then_some(HomeMessage::MoveTo(MoveToMessage::Apply)),
+                            )),
+                    ),
+            )
+            .width(420.0)
+            .padding(15.0)
+            .style(themed_menu),
+            HomeMessage::MoveTo(MoveToMessage::Close),
+        )
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum MoveToMessage {
+    SelectSeries(SeriesOption),
+    SeasonInput(String),
+    EpisodeInput(String),
+    Apply,
+    Close,
+}
@@ -0,0 +1,109 @@
+use super::HomeMessage;
+use crate::{
+    library,
+    ui::{HEADER_FONT, greyscale, icon, themed_button, themed_menu},
+};
+use iced::widget::{button, column, container, row, space, text};
+use itertools::Itertools;
+
+/// Titles listed under each kind of failure before the rest are only counted.
+const MAX_TITLES: usize = 3;
+
+fn titles(failures: &[&library::ScrapeFailure]) -> String {
+    let titles = failures
+        .iter()
+        .map(|failure| failure.title.as_str())
+        .unique()
+        .collect_vec();
+    let shown = titles.iter().take(MAX_TITLES).join(", ");
+    match titles.len().saturating_sub(MAX_TITLES) {
+        0 => shown,
+        more => format!("{} and {} more", shown, more),
+    }
+}
+
+/// A summary of what went wrong during the last scan or refresh, in the corner of the home screen.
+pub fn scrape_report<'a>(report: &library::ScrapeReport) -> Option<iced::Element<'a, HomeMessage>> {
+    if report.is_empty() {
+        return None;
+    }
+
+    let kinds: Vec<iced::Element<'a, HomeMessage>> = report
+        .failures
+        .iter()
+        .map(|failure| failure.kind)
+        .unique()
+        .map(|kind| {
+            let failures = report
+                .failures
+                .iter()
+                .filter(|failure| failure.kind == kind)
+                .collect_vec();
+            // every file fails the same way with a rejected key, and artwork has no title
+            let details = match kind {
+                library::FailureKind::Auth(_) => {
+                    Some(String::from("Check the key in Settings, then retry."))
+                }
+                library::FailureKind::Artwork => None,
+                _ => Some(titles(&failures)),
+            };
+
+            column![]
+                .spacing(2.0)
+                .push(text(format!("{} ({})", kind, failures.len())))
+                .push(details.map(|details| text(details).size(13.0).color(greyscale(150))))
+                .into()
+        })
+        .collect_vec();
+    let auth = report
+        .failures
+        .iter()
+        .any(|failure| matches!(failure.kind, library::FailureKind::Auth(_)));
+
+    Some(
+        container(
+            container(
+                column![]
+                    .spacing(10.0)
+                    .push(
+                        row![]
+                            .align_y(iced::Alignment::Center)
+                            .push(
+                                text("Some metadata couldn't be scraped")
+                                    .font(HEADER_FONT)
+                                    .size(18.0),
+                            )
+                            .push(space::horizontal())
+                            .push(
+                                button(icon(0xe5cd).size(20.0))
+                                    .style(themed_button)
+                                    .on_press(HomeMessage::DismissScrapeReport),
+                            ),
+                    )
+                    .extend(kinds)
+                    .push(
+                        row![]
+                            .spacing(10.0)
+                            .push(space::horizontal())
+                            .push(auth.then(|| {
+                                button("Settings")
+                                    .style(themed_button)
+                                    .on_press(HomeMessage::OpenSettings)
+                            }))
+                            .push((!report.retry.is_empty()).then(|| {
+                                button("Retry")
+                                    .style(button::primary)
+                                    .on_press(HomeMessage::RetryScrape)
+                            })),
+                    ),
+            )
+            .width(380.0)
+            .padding(15.0)
+            .style(themed_menu),
+        )
+        .padding(20.0)
+        .align_right(iced::Length::Fill)
+        .align_bottom(iced::Length::Fill)
+        .into(),
+    )
+}
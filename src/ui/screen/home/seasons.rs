@@ -4,17 +4,21 @@ use super::{
 };
 use crate::{
     library,
-    ui::{HEADER_FONT, icon, themed_button},
+    ui::{HEADER_FONT, greyscale, icon, themed_button, truncate_text},
 };
-use iced::widget::{button, column, container, hover, row, space, text};
+use iced::widget::{button, column, container, hover, image, row, rule, space, text};
 use itertools::Itertools;
 
+/// Matches the width episode stills are downloaded at.
+const STILL_WIDTH: f32 = 160.0;
+
 pub fn season_list<'a>(
     cache: &'a Cache,
     search: Option<&str>,
     id: library::MediaId,
     _series: &library::Series,
     library: &library::Library,
+    specials: bool,
 ) -> iced::Element<'a, HomeMessage> {
     column![]
         .width(iced::Length::Fill)
@@ -29,9 +33,26 @@ pub fn season_list<'a>(
                         search_season(search, **id, season, library)
                     }
                 }),
-                |(_, a), (_, b)| a.metadata.season.cmp(&b.metadata.season),
+                // specials go last, in a section of their own
+                |(_, a), (_, b)| {
+                    (a.metadata.season == 0, a.metadata.season)
+                        .cmp(&(b.metadata.season == 0, b.metadata.season))
+                },
             )
-            .map(|(id, season)| season_panel(cache, search, *id, season, library)),
+            .map(|(id, season)| {
+                let panel = season_panel(cache, search, *id, season, library, specials);
+                match season.metadata.season {
+                    0 => column![]
+                        .width(iced::Length::Fill)
+                        .max_width(800.0)
+                        .spacing(20.0)
+                        .push(rule::horizontal(1.0))
+                        .push(text("Specials").size(28.0).font(HEADER_FONT))
+                        .push(panel)
+                        .into(),
+                    _ => panel,
+                }
+            }),
         )
         .into()
 }
@@ -42,6 +63,7 @@ pub fn season_panel<'a>(
     id: library::MediaId,
     season: &library::Season,
     library: &library::Library,
+    specials: bool,
 ) -> iced::Element<'a, HomeMessage> {
     let card = cache.cache.get(&id);
 
@@ -59,9 +81,13 @@ pub fn season_panel<'a>(
                             .push(
                                 column![]
                                     .push(
-                                        text(format!("S{:02}", season.metadata.season))
-                                            .size(14.0)
-                                            .style(|theme: &iced::Theme| text::Style {
+                                        text(match season.metadata.season {
+                                            0 => String::from("Specials"),
+                                            season => format!("S{:02}", season),
+                                        })
+                                        .size(14.0)
+                                        .style(
+                                            |theme: &iced::Theme| text::Style {
                                                 color: Some(
                                                     theme
                                                         .extended_palette()
@@ -70,7 +96,8 @@ pub fn season_panel<'a>(
                                                         .color,
                                                 ),
                                                 ..Default::default()
-                                            }),
+                                            },
+                                        ),
                                     )
                                     .push(
                                         text(season.metadata.title.clone())
@@ -92,7 +119,7 @@ pub fn season_panel<'a>(
                     ),
             ),
         )
-        .push(episode_list(search, id, season, library))
+        .push(episode_list(search, id, season, library, specials))
         .into()
 }
 
@@ -101,6 +128,7 @@ fn episode_list<'a>(
     id: library::MediaId,
     _season: &library::Season,
     library: &library::Library,
+    specials: bool,
 ) -> iced::Element<'a, HomeMessage> {
     let mut episodes = search_maybe(
         library::find_episodes(id, library),
@@ -117,13 +145,73 @@ fn episode_list<'a>(
         episodes = library::find_episodes(id, library).collect_vec();
     }
 
+    let mut entries = episodes
+        .into_iter()
+        .map(|(id, episode)| {
+            (
+                episode.metadata.episode,
+                episode_entry(*id, episode, library, specials),
+            )
+        })
+        .collect_vec();
+    // gaps only make sense in the full list, not among search results
+    if search.is_none() {
+        let today = chrono::Local::now().date_naive();
+        entries.extend(
+            library::missing_episodes(id, library)
+                .into_iter()
+                .map(|episode| (episode.episode, missing_entry(episode, today))),
+        );
+        entries.sort_by_key(|(number, _)| *number);
+    }
+
     column![]
         .width(iced::Length::Fill)
         .spacing(5.0)
-        .extend(
-            episodes
-                .into_iter()
-                .map(|(id, episode)| episode_entry(*id, episode, library)),
+        .extend(entries.into_iter().map(|(_, entry)| entry))
+        .into()
+}
+
+/// An episode the provider lists that isn't in the library, either missing or yet to air.
+fn missing_entry<'a>(
+    episode: &library::EpisodeMetadata,
+    today: chrono::NaiveDate,
+) -> iced::Element<'a, HomeMessage> {
+    let status = match episode.aired {
+        Some(aired) if aired > today => {
+            format!("Airs {}", aired.format("%e %B %Y").to_string().trim())
+        }
+        Some(aired) => format!(
+            "Missing · aired {}",
+            aired.format("%e %B %Y").to_string().trim()
+        ),
+        None => String::from("Missing"),
+    };
+
+    row![]
+        .spacing(10.0)
+        .align_y(iced::Alignment::Center)
+        .padding(iced::Padding::new(5.0).left(40.0).right(10.0))
+        .push(
+            text(format!("E{:02}", episode.episode))
+                .size(14.0)
+                .width(30.0)
+                .color(greyscale(90)),
+        )
+        .push(episode.still.as_ref().map(|still| {
+            image(image::Handle::from_path(still))
+                .content_fit(iced::ContentFit::Cover)
+                .width(STILL_WIDTH)
+                .height(STILL_WIDTH * 9.0 / 16.0)
+                .border_radius(4.0)
+                .opacity(0.35)
+        }))
+        .push(
+            column![]
+                .width(iced::Length::Fill)
+                .spacing(2.0)
+                .push(text(episode.title.clone()).color(greyscale(120)))
+                .push(text(status).size(13.0).color(greyscale(100))),
         )
         .into()
 }
@@ -132,7 +220,17 @@ fn episode_entry<'a>(
     id: library::MediaId,
     episode: &library::Episode,
     library: &library::Library,
+    specials: bool,
 ) -> iced::Element<'a, HomeMessage> {
+    // where a special falls among the regular episodes, when they're watched in airing order
+    let placement = (specials && episode.metadata.season == 0)
+        .then(|| library::special_placement(episode, library))
+        .flatten()
+        .map(|placement| match placement {
+            (0, 0) => String::from("Airs before the first episode"),
+            (season, number) => format!("Airs after S{:02}E{:02}", season, number),
+        });
+
     hover(
         button(
             row![]
@@ -148,8 +246,28 @@ fn episode_entry<'a>(
                             ..Default::default()
                         }),
                 )
-                .push(text(episode.metadata.title.clone()))
-                .push(space::horizontal())
+                .push(episode.metadata.still.as_ref().map(|still| {
+                    image(image::Handle::from_path(still))
+                        .content_fit(iced::ContentFit::Cover)
+                        .width(STILL_WIDTH)
+                        .height(STILL_WIDTH * 9.0 / 16.0)
+                        .border_radius(4.0)
+                }))
+                .push(
+                    column![]
+                        .width(iced::Length::Fill)
+                        .spacing(2.0)
+                        .push(text(episode.metadata.title.clone()))
+                        .push(
+                            placement
+                                .map(|placement| text(placement).size(13.0).color(greyscale(190))),
+                        )
+                        .push(episode.metadata.overview.as_ref().map(|overview| {
+                            text(truncate_text(overview, 160))
+                                .size(13.0)
+                                .color(greyscale(150))
+                        })),
+                )
                 .push(
                     container(watched_icon(episode.video.watched, false, 1.0)).style(
                         |theme: &iced::Theme| container::Style {
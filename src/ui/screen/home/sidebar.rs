@@ -32,6 +32,7 @@ pub fn sidebar<'a>(
             .push(sidebar_button(0xe88a, "Home").on_press(HomeMessage::Goto(Tab::Home)))
             .push(sidebar_button(0xe02c, "Movies").on_press(HomeMessage::Goto(Tab::Movies)))
             .push(sidebar_button(0xe639, "TV Shows").on_press(HomeMessage::Goto(Tab::TvShows)))
+            .push(sidebar_button(0xebcc, "Calendar").on_press(HomeMessage::Goto(Tab::Calendar)))
             .extend(collections.map(|(id, collection)| {
                 let id = *id;
 
@@ -165,7 +166,15 @@ pub fn sidebar<'a>(
                                             (!scanning).then_some(HomeMessage::Action(
                                                 HomeAction::ForceScan,
                                             )),
-                                        )),
+                                        ))
+                                        .push(
+                                            sidebar_button(0xe5d5, "Refresh Metadata")
+                                                .on_press_maybe((!scanning).then_some(
+                                                    HomeMessage::Action(
+                                                        HomeAction::RefreshMetadata,
+                                                    ),
+                                                )),
+                                        ),
                                 )
                                 .padding(5.0)
                                 .style(themed_menu),
@@ -47,7 +47,8 @@ pub fn top_bar<'a>(
                             Tab::Home => "Home".into(),
                             Tab::Movies => "Movies".into(),
                             Tab::TvShows => "TV Shows".into(),
-                            Tab::TvShow(id) => library.get(id).unwrap().title(),
+                            Tab::Calendar => "Calendar".into(),
+                            Tab::Movie(id) | Tab::TvShow(id) => library.get(id).unwrap().title(),
                             Tab::Season(id) => {
                                 let library::Media::Season(season) = library.get(id).unwrap()
                                 else {
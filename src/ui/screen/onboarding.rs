@@ -0,0 +1,532 @@
+use super::Screen;
+use crate::{
+    library,
+    ui::{
+        AppState, HEADER_FONT, LibraryStatus, MONO_FONT, greyscale, icon, themed_button,
+        themed_text_input,
+    },
+};
+use iced::widget::{button, center, column, row, space, text, text_input};
+use normpath::PathExt;
+use rfd::AsyncFileDialog;
+use rustc_hash::FxHashMap;
+use std::{path::PathBuf, time::Instant};
+
+const ERROR_COLOR: iced::Color = iced::Color::from_rgb8(237, 71, 71);
+const SUCCESS_COLOR: iced::Color = iced::Color::from_rgb8(68, 161, 50);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Step {
+    Welcome,
+    Key,
+    Directories,
+    Scan,
+}
+
+impl Step {
+    const COUNT: usize = 4;
+
+    fn next(self) -> Self {
+        match self {
+            Step::Welcome => Step::Key,
+            Step::Key => Step::Directories,
+            Step::Directories | Step::Scan => Step::Scan,
+        }
+    }
+
+    fn previous(self) -> Self {
+        match self {
+            Step::Welcome | Step::Key => Step::Welcome,
+            Step::Directories => Step::Key,
+            Step::Scan => Step::Directories,
+        }
+    }
+}
+
+/// Walks a new user through connecting to TMDB, choosing directories and the first scan. Settings
+/// covers everything here for later changes.
+pub struct Onboarding {
+    step: Step,
+    dialog_open: bool,
+    testing: bool,
+    /// The outcome of testing the secret as it is, cleared when it changes.
+    tested: Option<Result<(), String>>,
+    /// The videos found in each directory, once counted.
+    counts: FxHashMap<PathBuf, Result<library::VideoCounts, String>>,
+    scanning: bool,
+    scanned: bool,
+}
+
+fn count_videos(path: PathBuf) -> iced::Task<OnboardingMessage> {
+    iced::Task::perform(
+        async move {
+            let counts = library::count_videos(&path)
+                .await
+                .map_err(|err| format!("{:#}", err));
+            (path, counts)
+        },
+        |(path, counts)| OnboardingMessage::Counted(path, counts),
+    )
+}
+
+impl Onboarding {
+    pub fn new(state: &AppState) -> (Self, iced::Task<OnboardingMessage>) {
+        (
+            Onboarding {
+                step: Step::Welcome,
+                dialog_open: false,
+                testing: false,
+                tested: None,
+                counts: FxHashMap::default(),
+                scanning: false,
+                scanned: false,
+            },
+            iced::Task::batch(state.settings.directories.iter().cloned().map(count_videos)),
+        )
+    }
+}
+
+impl Screen for Onboarding {
+    type Message = OnboardingMessage;
+
+    fn update(
+        &mut self,
+        message: OnboardingMessage,
+        state: &mut AppState,
+        _now: Instant,
+    ) -> iced::Task<OnboardingMessage> {
+        match message {
+            OnboardingMessage::Next => {
+                self.step = self.step.next();
+                if self.step == Step::Scan && !self.scanning && !self.scanned {
+                    self.scanning = true;
+                    return iced::Task::done(OnboardingMessage::StartScan);
+                }
+                iced::Task::none()
+            }
+            OnboardingMessage::Back => {
+                self.step = self.step.previous();
+                iced::Task::none()
+            }
+            OnboardingMessage::SecretInput(secret) => {
+                state.settings.tmdb_secret = secret;
+                state.rejected_keys.remove(&library::Provider::Tmdb);
+                self.tested = None;
+                iced::Task::none()
+            }
+            OnboardingMessage::TestConnection => {
+                let secret = state.settings.tmdb_secret.trim().to_string();
+                if secret.is_empty() || self.testing {
+                    return iced::Task::none();
+                }
+
+                self.testing = true;
+                self.tested = None;
+                // not cached, as a cached response would pass any secret
+                let mut scraper = library::TmdbScraper::new(&secret);
+                if let Some(api_base) = state.settings.api_base() {
+                    scraper = scraper.api_base(&api_base);
+                }
+                iced::Task::perform(
+                    async move {
+                        let result = scraper.check_secret().await.map_err(|err| {
+                            let kind = library::FailureKind::of(&err);
+                            let message = match kind {
+                                library::FailureKind::Other => format!("{:#}", err),
+                                kind => kind.to_string(),
+                            };
+                            (kind, message)
+                        });
+                        (secret, result)
+                    },
+                    |(secret, result)| OnboardingMessage::ConnectionTested(secret, result),
+                )
+            }
+            OnboardingMessage::ConnectionTested(secret, result) => {
+                self.testing = false;
+                // the secret changed while it was being tested
+                if secret != state.settings.tmdb_secret.trim() {
+                    return iced::Task::none();
+                }
+
+                if let Err((library::FailureKind::Auth(provider), _)) = &result {
+                    state.rejected_keys.insert(*provider);
+                }
+                self.tested = Some(result.map_err(|(_, message)| message));
+                iced::Task::none()
+            }
+            OnboardingMessage::AddDirectory => {
+                if self.dialog_open {
+                    return iced::Task::none();
+                }
+
+                self.dialog_open = true;
+                iced::Task::perform(
+                    async move {
+                        AsyncFileDialog::new()
+                            .pick_folder()
+                            .await
+                            .map(|handle| handle.path().to_path_buf())
+                    },
+                    OnboardingMessage::AddDirectoryResponse,
+                )
+            }
+            OnboardingMessage::AddDirectoryResponse(path) => {
+                self.dialog_open = false;
+
+                let Some(path) = path else {
+                    return iced::Task::none();
+                };
+                if state
+                    .settings
+                    .directories
+                    .iter()
+                    .any(|other| other.normalize().unwrap() == path.normalize().unwrap())
+                {
+                    return iced::Task::none();
+                }
+
+                state.settings.directories.push(path.clone());
+                count_videos(path)
+            }
+            OnboardingMessage::RemoveDirectory(index) => {
+                let path = state.settings.directories.remove(index);
+                state.settings.directory_languages.remove(&path);
+                self.counts.remove(&path);
+                iced::Task::none()
+            }
+            OnboardingMessage::Counted(path, counts) => {
+                if state.settings.directories.contains(&path) {
+                    self.counts.insert(path, counts);
+                }
+                iced::Task::none()
+            }
+            OnboardingMessage::ScanComplete => {
+                self.scanning = false;
+                self.scanned = true;
+                iced::Task::none()
+            }
+            OnboardingMessage::StartScan | OnboardingMessage::Finish => iced::Task::none(),
+        }
+    }
+
+    fn view<'a, 'b>(
+        &'a self,
+        state: &'a AppState,
+        _now: Instant,
+    ) -> iced::Element<'b, OnboardingMessage>
+    where
+        'a: 'b,
+    {
+        let content = match self.step {
+            Step::Welcome => self.welcome(),
+            Step::Key => self.key(state),
+            Step::Directories => self.directories(state),
+            Step::Scan => self.scan(state),
+        };
+
+        center(
+            column![]
+                .width(iced::Length::Fill)
+                .max_width(600.0)
+                .padding(30.0)
+                .spacing(20.0)
+                .push(
+                    text(format!(
+                        "Step {} of {}",
+                        self.step as usize + 1,
+                        Step::COUNT
+                    ))
+                    .size(14.0)
+                    .color(greyscale(150)),
+                )
+                .push(content),
+        )
+        .into()
+    }
+}
+
+impl Onboarding {
+    fn welcome<'a>(&self) -> iced::Element<'a, OnboardingMessage> {
+        column![]
+            .spacing(15.0)
+            .push(text("Welcome to Jangal").font(HEADER_FONT).size(28.0))
+            .push(text(
+                "Let's get your library set up: connect to a metadata provider, choose where your \
+                 videos are, and run a first scan. Everything can be changed later in Settings.",
+            ))
+            .push(footer(
+                Some(("Skip setup", OnboardingMessage::Finish)),
+                ("Get started", Some(OnboardingMessage::Next)),
+            ))
+            .into()
+    }
+
+    fn key<'a>(&self, state: &'a AppState) -> iced::Element<'a, OnboardingMessage> {
+        let secret = state.settings.tmdb_secret.trim();
+        let status: Option<iced::Element<'a, OnboardingMessage>> = if self.testing {
+            Some(text("Testing...").color(greyscale(150)).into())
+        } else {
+            match &self.tested {
+                Some(Ok(())) => Some(
+                    row![]
+                        .spacing(5.0)
+                        .align_y(iced::Alignment::Center)
+                        .push(icon(0xe5ca).size(18.0).color(SUCCESS_COLOR))
+                        .push(text("Connected").color(SUCCESS_COLOR))
+                        .into(),
+                ),
+                Some(Err(err)) => Some(text(err.clone()).color(ERROR_COLOR).into()),
+                None => None,
+            }
+        };
+
+        column![]
+            .spacing(15.0)
+            .push(text("Connect to TMDB").font(HEADER_FONT).size(28.0))
+            .push(text(
+                "Posters, descriptions and episode lists come from The Movie Database. Create a \
+                 free account on themoviedb.org, then copy the API key or the API read access \
+                 token from the API section of your account settings.",
+            ))
+            .push(
+                text_input("API key or read access token", &state.settings.tmdb_secret)
+                    .on_input(OnboardingMessage::SecretInput)
+                    .on_submit(OnboardingMessage::TestConnection)
+                    .style(themed_text_input)
+                    .font(MONO_FONT),
+            )
+            .push(
+                row![]
+                    .spacing(15.0)
+                    .align_y(iced::Alignment::Center)
+                    .push(
+                        button("Test connection")
+                            .style(themed_button)
+                            .on_press_maybe(
+                                (!secret.is_empty() && !self.testing)
+                                    .then_some(OnboardingMessage::TestConnection),
+                            ),
+                    )
+                    .push(status),
+            )
+            .push(
+                text(
+                    "Without a key, nothing can be identified until another provider, such as \
+                     TVmaze for TV shows, is enabled in Settings.",
+                )
+                .size(14.0)
+                .color(greyscale(150)),
+            )
+            .push(footer(
+                Some(("Back", OnboardingMessage::Back)),
+                (
+                    if secret.is_empty() { "Skip" } else { "Next" },
+                    Some(OnboardingMessage::Next),
+                ),
+            ))
+            .into()
+    }
+
+    fn directories<'a>(&'a self, state: &'a AppState) -> iced::Element<'a, OnboardingMessage> {
+        let directories = state
+            .settings
+            .directories
+            .iter()
+            .enumerate()
+            .map(|(i, path)| {
+                let counts = match self.counts.get(path) {
+                    Some(Ok(counts)) => text(describe_counts(counts)).color(greyscale(150)),
+                    Some(Err(err)) => text(err.clone()).color(ERROR_COLOR),
+                    None => text("Counting...").color(greyscale(150)),
+                };
+                row![]
+                    .spacing(10.0)
+                    .align_y(iced::Alignment::Center)
+                    .push(
+                        button(icon(0xe15b).size(20.0))
+                            .style(themed_button)
+                            .on_press(OnboardingMessage::RemoveDirectory(i)),
+                    )
+                    .push(
+                        column![]
+                            .spacing(2.0)
+                            .push(text(path.to_string_lossy().to_string()).font(MONO_FONT))
+                            .push(counts.size(14.0)),
+                    )
+                    .into()
+            })
+            .collect::<Vec<iced::Element<'a, OnboardingMessage>>>();
+
+        column![]
+            .spacing(15.0)
+            .push(text("Choose your media").font(HEADER_FONT).size(28.0))
+            .push(text(
+                "Add the directories your movies and TV shows are in, including any \
+                 subdirectories. Files named like \"Title (2010)\" or \"Show S01E02\" are \
+                 recognised best.",
+            ))
+            .extend(directories)
+            .push(
+                button(
+                    row![]
+                        .align_y(iced::Alignment::Center)
+                        .spacing(10.0)
+                        .push(icon(0xe145).size(20.0))
+                        .push("Add directory"),
+                )
+                .width(iced::Length::Fill)
+                .padding(iced::Padding::new(5.0).left(10.0))
+                .style(themed_button)
+                .on_press_maybe((!self.dialog_open).then_some(OnboardingMessage::AddDirectory)),
+            )
+            .push(footer(
+                Some(("Back", OnboardingMessage::Back)),
+                (
+                    "Scan",
+                    (!state.settings.directories.is_empty()).then_some(OnboardingMessage::Next),
+                ),
+            ))
+            .into()
+    }
+
+    fn scan<'a>(&self, state: &'a AppState) -> iced::Element<'a, OnboardingMessage> {
+        let videos = state
+            .library
+            .iter()
+            .filter(|(_, media)| media.video().is_some())
+            .count();
+        let scanning = self.scanning || state.library_status == LibraryStatus::Scanning;
+        let found = self.scanned || videos > 0;
+
+        let mut content = column![]
+            .spacing(15.0)
+            .push(text("First scan").font(HEADER_FONT).size(28.0))
+            .push(phase(
+                if found {
+                    format!("Found {} videos", videos)
+                } else {
+                    String::from("Looking for videos...")
+                },
+                found,
+            ))
+            .push(found.then(|| {
+                phase(
+                    if self.scanned {
+                        String::from("Fetched metadata")
+                    } else {
+                        String::from("Fetching metadata...")
+                    },
+                    self.scanned,
+                )
+            }));
+
+        if self.scanned && !scanning {
+            let count = |matches: fn(&library::Media) -> bool| {
+                state
+                    .library
+                    .iter()
+                    .filter(|(_, media)| matches(media))
+                    .count()
+            };
+            let movies = count(|media| matches!(media, library::Media::Movie(_)));
+            let shows = count(|media| matches!(media, library::Media::Series(_)));
+            let unidentified = count(|media| matches!(media, library::Media::Uncategorised(_)));
+
+            content = content
+                .push(text(format!(
+                    "Your library has {} movies and {} TV shows.",
+                    movies, shows
+                )))
+                .push((unidentified > 0).then(|| {
+                    text(format!(
+                        "{} videos couldn't be identified. They are listed under Needs Review or \
+                         can be identified from their menu.",
+                        unidentified
+                    ))
+                    .color(greyscale(150))
+                }))
+                .push((!state.scrape_report.is_empty()).then(|| {
+                    text("Some metadata couldn't be scraped; the details are on the home screen.")
+                        .color(greyscale(150))
+                }));
+        }
+
+        content
+            .push(footer(
+                None,
+                (
+                    "Finish",
+                    (self.scanned && !scanning).then_some(OnboardingMessage::Finish),
+                ),
+            ))
+            .into()
+    }
+}
+
+fn describe_counts(counts: &library::VideoCounts) -> String {
+    if counts.movies + counts.episodes + counts.unknown == 0 {
+        return String::from("No videos found");
+    }
+
+    let mut parts = vec![
+        format!("{} movies", counts.movies),
+        format!("{} episodes", counts.episodes),
+    ];
+    if counts.unknown > 0 {
+        parts.push(format!("{} unrecognised", counts.unknown));
+    }
+    parts.join(", ")
+}
+
+fn phase<'a>(label: String, done: bool) -> iced::Element<'a, OnboardingMessage> {
+    row![]
+        .spacing(10.0)
+        .align_y(iced::Alignment::Center)
+        .push(
+            icon(if done { 0xe5ca } else { 0xe8b5 })
+                .size(20.0)
+                .color(if done { SUCCESS_COLOR } else { greyscale(150) }),
+        )
+        .push(text(label))
+        .into()
+}
+
+/// A secondary button on the left and the button leading on to the right, disabled without a
+/// message.
+fn footer<'a>(
+    secondary: Option<(&'a str, OnboardingMessage)>,
+    primary: (&'a str, Option<OnboardingMessage>),
+) -> iced::Element<'a, OnboardingMessage> {
+    row![]
+        .spacing(10.0)
+        .push(
+            secondary.map(|(label, message)| button(label).style(themed_button).on_press(message)),
+        )
+        .push(space::horizontal())
+        .push(
+            button(primary.0)
+                .style(button::primary)
+                .on_press_maybe(primary.1),
+        )
+        .into()
+}
+
+#[derive(Debug, Clone)]
+pub enum OnboardingMessage {
+    Next,
+    Back,
+    SecretInput(String),
+    TestConnection,
+    ConnectionTested(String, Result<(), (library::FailureKind, String)>),
+    AddDirectory,
+    AddDirectoryResponse(Option<PathBuf>),
+    RemoveDirectory(usize),
+    Counted(PathBuf, Result<library::VideoCounts, String>),
+    /// Scans the chosen directories; handled by the app.
+    StartScan,
+    /// Sent by the app once the scan and its scrape are done.
+    ScanComplete,
+    /// Finishes or skips the setup for the home screen; handled by the app.
+    Finish,
+}
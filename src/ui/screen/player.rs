@@ -447,7 +447,11 @@ impl Screen for Player {
                 iced::Task::perform(state.save_library(), |_| ()).discard()
             }
             PlayerMessage::Previous => {
-                if let Some(previous) = library::previous_in_list(self.id, &state.library) {
+                if let Some(previous) = library::previous_in_list(
+                    self.id,
+                    &state.library,
+                    state.settings.specials_in_airing_order,
+                ) {
                     let Player { is_fullscreen, .. } = *self;
                     let (screen, task) = Player::new(previous, state);
                     *self = screen;
@@ -458,7 +462,11 @@ impl Screen for Player {
                 }
             }
             PlayerMessage::Next => {
-                if let Some(next) = library::next_in_list(self.id, &state.library) {
+                if let Some(next) = library::next_in_list(
+                    self.id,
+                    &state.library,
+                    state.settings.specials_in_airing_order,
+                ) {
                     let Player { is_fullscreen, .. } = *self;
                     let (screen, task) = Player::new(next, state);
                     *self = screen;
@@ -1,6 +1,11 @@
 use super::Screen;
-use crate::ui::{
-    AppState, HEADER_FONT, MONO_FONT, SUBTITLE_FONT, icon, themed_button, themed_text_input,
+use crate::{
+    library,
+    settings::{API_BASE_VAR, IMAGE_BASE_VAR},
+    ui::{
+        AppState, HEADER_FONT, LibraryStatus, MONO_FONT, SUBTITLE_FONT, greyscale, icon,
+        rich_checkbox, themed_button, themed_text_input,
+    },
 };
 use iced::widget::{
     button, column, container, row, rule, scrollable, slider, space, text, text_input,
@@ -11,11 +16,24 @@ use std::{path::PathBuf, time::Instant};
 
 pub struct Settings {
     dialog_open: bool,
+    /// Outcome of clearing the metadata cache, shown next to the button.
+    cache_status: Option<String>,
+    /// Outcome of deleting unused artwork, shown next to the button.
+    artwork_status: Option<String>,
+    show_advanced: bool,
 }
 
 impl Settings {
     pub fn new() -> (Self, iced::Task<SettingsMessage>) {
-        (Settings { dialog_open: false }, iced::Task::none())
+        (
+            Settings {
+                dialog_open: false,
+                cache_status: None,
+                artwork_status: None,
+                show_advanced: false,
+            },
+            iced::Task::none(),
+        )
     }
 }
 
@@ -62,11 +80,47 @@ impl Screen for Settings {
                 iced::Task::none()
             }
             SettingsMessage::RemoveDirectory(index) => {
-                state.settings.directories.remove(index);
+                let path = state.settings.directories.remove(index);
+                state.settings.directory_languages.remove(&path);
+                iced::Task::none()
+            }
+            SettingsMessage::DirectoryLanguage(path, language) => {
+                if language.trim().is_empty() {
+                    state.settings.directory_languages.remove(&path);
+                } else {
+                    state.settings.directory_languages.insert(path, language);
+                }
                 iced::Task::none()
             }
             SettingsMessage::ApiSecretInput(secret) => {
                 state.settings.tmdb_secret = secret;
+                state.rejected_keys.remove(&library::Provider::Tmdb);
+                iced::Task::none()
+            }
+            SettingsMessage::OmdbKeyInput(key) => {
+                state.settings.omdb_key = key;
+                state.rejected_keys.remove(&library::Provider::Omdb);
+                iced::Task::none()
+            }
+            SettingsMessage::TvdbKeyInput(key) => {
+                state.settings.tvdb_key = key;
+                state.rejected_keys.remove(&library::Provider::Tvdb);
+                iced::Task::none()
+            }
+            SettingsMessage::ToggleProvider(kind, provider, enabled) => {
+                let providers = state.settings.providers_mut(kind);
+                if !enabled {
+                    providers.retain(|other| *other != provider);
+                } else if !providers.contains(&provider) {
+                    providers.push(provider);
+                }
+                iced::Task::none()
+            }
+            SettingsMessage::RaiseProvider(kind, index) => {
+                let providers = state.settings.providers_mut(kind);
+                if index > 0 && index < providers.len() {
+                    providers.swap(index - 1, index);
+                }
                 iced::Task::none()
             }
             SettingsMessage::WatchThresholdMovies(minutes) => {
@@ -81,6 +135,10 @@ impl Screen for Settings {
                 }
                 iced::Task::none()
             }
+            SettingsMessage::SpecialsInAiringOrder(enabled) => {
+                state.settings.specials_in_airing_order = enabled;
+                iced::Task::none()
+            }
             SettingsMessage::SubtitleOpacity(opacity) => {
                 state.settings.subtitle_opacity = opacity;
                 iced::Task::none()
@@ -93,6 +151,80 @@ impl Screen for Settings {
                 state.settings.thumbnail_interval = interval;
                 iced::Task::none()
             }
+            SettingsMessage::RefreshInterval(days) => {
+                if let Ok(days) = days.parse() {
+                    state.settings.refresh_interval_days = days;
+                }
+                iced::Task::none()
+            }
+            SettingsMessage::MetadataLanguage(language) => {
+                state.settings.metadata_language = language;
+                iced::Task::none()
+            }
+            SettingsMessage::FallbackLanguage(language) => {
+                state.settings.fallback_language = language;
+                iced::Task::none()
+            }
+            SettingsMessage::MetadataRegion(region) => {
+                state.settings.metadata_region = region;
+                iced::Task::none()
+            }
+            SettingsMessage::ToggleAdvanced => {
+                self.show_advanced = !self.show_advanced;
+                iced::Task::none()
+            }
+            SettingsMessage::ApiBase(base) => {
+                state.settings.tmdb_api_base = base;
+                iced::Task::none()
+            }
+            SettingsMessage::ImageBase(base) => {
+                state.settings.tmdb_image_base = base;
+                iced::Task::none()
+            }
+            SettingsMessage::ClearMetadataCache => {
+                let storage = state.storage_path.clone();
+                iced::Task::perform(
+                    async move {
+                        library::clear_metadata_cache(&storage)
+                            .await
+                            .map_err(|err| err.to_string())
+                    },
+                    SettingsMessage::MetadataCacheCleared,
+                )
+            }
+            SettingsMessage::MetadataCacheCleared(result) => {
+                self.cache_status = Some(match result {
+                    Ok(bytes) => format!("Cleared {:.1} MB", bytes as f64 / 1_000_000.0),
+                    Err(err) => format!("Failed to clear cache: {}", err),
+                });
+                iced::Task::none()
+            }
+            SettingsMessage::CollectArtwork => {
+                // a scan could write new artwork while this is deciding what to delete
+                if state.library_status == LibraryStatus::Scanning {
+                    return iced::Task::none();
+                }
+                state.library_status = LibraryStatus::Scanning;
+                state.collecting_artwork = true;
+
+                let storage = state.storage_path.clone();
+                let referenced = library::referenced_artwork(&state.library);
+                iced::Task::perform(
+                    async move {
+                        library::collect_artwork(&storage, &referenced)
+                            .await
+                            .map_err(|err| format!("{:#}", err))
+                    },
+                    SettingsMessage::ArtworkCollected,
+                )
+            }
+            SettingsMessage::ArtworkCollected(result) => {
+                self.artwork_status = Some(match result {
+                    Ok(bytes) => format!("Freed {:.1} MB", bytes as f64 / 1_000_000.0),
+                    Err(err) => format!("Failed to remove artwork: {}", err),
+                });
+                iced::Task::none()
+            }
             _ => iced::Task::none(),
         }
     }
@@ -141,18 +273,55 @@ impl Screen for Settings {
                     column![]
                         .spacing(10.0)
                         .padding(iced::Padding::new(20.0).left(25.0).right(25.0))
-                        .push(
-                            row![]
-                                .align_y(iced::Alignment::Center)
-                                .push(text("TMDB API Secret").width(iced::Length::FillPortion(1)))
-                                .push(
-                                    text_input("API Secret", &state.settings.tmdb_secret)
-                                        .on_input(SettingsMessage::ApiSecretInput)
-                                        .style(themed_text_input)
-                                        .font(MONO_FONT)
-                                        .width(iced::Length::FillPortion(2)),
-                                ),
-                        )
+                        .push(key_row(
+                            "TMDB API Secret",
+                            "API Secret",
+                            &state.settings.tmdb_secret,
+                            state.rejected_keys.contains(&library::Provider::Tmdb),
+                            SettingsMessage::ApiSecretInput,
+                        ))
+                        .push(key_row(
+                            "OMDb API Key",
+                            "API Key",
+                            &state.settings.omdb_key,
+                            state.rejected_keys.contains(&library::Provider::Omdb),
+                            SettingsMessage::OmdbKeyInput,
+                        ))
+                        .push(key_row(
+                            "TheTVDB API Key",
+                            "API Key",
+                            &state.settings.tvdb_key,
+                            state.rejected_keys.contains(&library::Provider::Tvdb),
+                            SettingsMessage::TvdbKeyInput,
+                        ))
+                        .push(provider_row(
+                            "Movie Providers",
+                            library::MediaKind::Movie,
+                            state,
+                        ))
+                        .push(provider_row(
+                            "TV Show Providers",
+                            library::MediaKind::Series,
+                            state,
+                        ))
+                        .push(language_row(
+                            "Metadata Language",
+                            "en-US",
+                            &state.settings.metadata_language,
+                            SettingsMessage::MetadataLanguage,
+                        ))
+                        .push(language_row(
+                            "Fallback Language",
+                            "None",
+                            &state.settings.fallback_language,
+                            SettingsMessage::FallbackLanguage,
+                        ))
+                        .push(language_row(
+                            "Release Region",
+                            "US",
+                            &state.settings.metadata_region,
+                            SettingsMessage::MetadataRegion,
+                        ))
                         .push(
                             row![]
                                 .push(text("Media Directories").width(iced::Length::FillPortion(1)))
@@ -183,6 +352,24 @@ impl Screen for Settings {
                                                             .font(MONO_FONT),
                                                     )
                                                     .push(space::horizontal())
+                                                    .push(
+                                                        text_input(
+                                                            "Language",
+                                                            state
+                                                                .settings
+                                                                .directory_languages
+                                                                .get(&path)
+                                                                .map_or("", String::as_str),
+                                                        )
+                                                        .width(110)
+                                                        .on_input(move |language| {
+                                                            SettingsMessage::DirectoryLanguage(
+                                                                path.clone(),
+                                                                language,
+                                                            )
+                                                        })
+                                                        .style(themed_text_input),
+                                                    )
                                                     .into()
                                                 }),
                                         )
@@ -259,6 +446,18 @@ impl Screen for Settings {
                                         .push(space::horizontal()),
                                 ),
                         )
+                        .push(
+                            row![]
+                                .align_y(iced::Alignment::Center)
+                                .push(text("TV specials").width(iced::Length::FillPortion(1)))
+                                .push(row![].width(iced::Length::FillPortion(2)).push(
+                                    rich_checkbox(
+                                        text("Play in airing order, between episodes"),
+                                        state.settings.specials_in_airing_order,
+                                        SettingsMessage::SpecialsInAiringOrder,
+                                    ),
+                                )),
+                        )
                         .push(
                             row![]
                                 .align_y(iced::Alignment::Center)
@@ -358,23 +557,294 @@ impl Screen for Settings {
                                         )))
                                         .push(space::horizontal()),
                                 ),
-                        ),
+                        )
+                        .push(
+                            row![]
+                                .align_y(iced::Alignment::Center)
+                                .push(
+                                    text("Refresh metadata every")
+                                        .width(iced::Length::FillPortion(1)),
+                                )
+                                .push(
+                                    row![]
+                                        .align_y(iced::Alignment::Center)
+                                        .width(iced::Length::FillPortion(2))
+                                        .spacing(5.0)
+                                        .push(
+                                            text_input(
+                                                "",
+                                                &state.settings.refresh_interval_days.to_string(),
+                                            )
+                                            .align_x(iced::Alignment::End)
+                                            .width(100)
+                                            .on_input(SettingsMessage::RefreshInterval)
+                                            .style(themed_text_input),
+                                        )
+                                        .push("day(s), or 0 to never refresh automatically")
+                                        .push(space::horizontal()),
+                                ),
+                        )
+                        .push(
+                            row![]
+                                .align_y(iced::Alignment::Center)
+                                .push(text("Metadata Cache").width(iced::Length::FillPortion(1)))
+                                .push(
+                                    row![]
+                                        .align_y(iced::Alignment::Center)
+                                        .width(iced::Length::FillPortion(2))
+                                        .spacing(10.0)
+                                        .push(
+                                            button("Clear metadata cache")
+                                                .style(themed_button)
+                                                .on_press(SettingsMessage::ClearMetadataCache),
+                                        )
+                                        .push(self.cache_status.as_ref().map(|status| {
+                                            text(status.clone()).color(greyscale(150))
+                                        }))
+                                        .push(space::horizontal()),
+                                ),
+                        )
+                        .push(
+                            row![]
+                                .align_y(iced::Alignment::Center)
+                                .push(text("Artwork").width(iced::Length::FillPortion(1)))
+                                .push(
+                                    row![]
+                                        .align_y(iced::Alignment::Center)
+                                        .width(iced::Length::FillPortion(2))
+                                        .spacing(10.0)
+                                        .push(
+                                            button("Remove unused artwork")
+                                                .style(themed_button)
+                                                .on_press_maybe(
+                                                    (state.library_status == LibraryStatus::Idle)
+                                                        .then_some(SettingsMessage::CollectArtwork),
+                                                ),
+                                        )
+                                        .push(self.artwork_status.as_ref().map(|status| {
+                                            text(status.clone()).color(greyscale(150))
+                                        }))
+                                        .push(space::horizontal()),
+                                ),
+                        )
+                        .push(
+                            button(if self.show_advanced {
+                                "Hide advanced settings"
+                            } else {
+                                "Show advanced settings"
+                            })
+                            .style(themed_button)
+                            .on_press(SettingsMessage::ToggleAdvanced),
+                        )
+                        .push(self.show_advanced.then(|| {
+                            column![]
+                                .spacing(10.0)
+                                .push(endpoint_row(
+                                    "TMDB API Base",
+                                    library::DEFAULT_API_BASE,
+                                    &state.settings.tmdb_api_base,
+                                    API_BASE_VAR,
+                                    SettingsMessage::ApiBase,
+                                ))
+                                .push(endpoint_row(
+                                    "TMDB Image Base",
+                                    library::DEFAULT_IMAGE_BASE,
+                                    &state.settings.tmdb_image_base,
+                                    IMAGE_BASE_VAR,
+                                    SettingsMessage::ImageBase,
+                                ))
+                        })),
                 )),
         )
         .into()
     }
 }
 
+/// `rejected` if the provider turned the key down during the last scan or refresh.
+fn key_row<'a>(
+    label: &'a str,
+    placeholder: &'a str,
+    value: &'a str,
+    rejected: bool,
+    on_input: fn(String) -> SettingsMessage,
+) -> iced::Element<'a, SettingsMessage> {
+    row![]
+        .align_y(iced::Alignment::Center)
+        .push(text(label).width(iced::Length::FillPortion(1)))
+        .push(
+            column![]
+                .width(iced::Length::FillPortion(2))
+                .spacing(5.0)
+                .push(
+                    text_input(placeholder, value)
+                        .on_input(on_input)
+                        .style(themed_text_input)
+                        .font(MONO_FONT),
+                )
+                .push(rejected.then(|| {
+                    text("Invalid API key. It was rejected the last time metadata was scraped.")
+                        .size(14.0)
+                        .color(iced::Color::from_rgb8(237, 71, 71))
+                })),
+        )
+        .into()
+}
+
+/// The providers asked about `kind`, in order, followed by the ones that are turned off.
+fn provider_row<'a>(
+    label: &'a str,
+    kind: library::MediaKind,
+    state: &'a AppState,
+) -> iced::Element<'a, SettingsMessage> {
+    let enabled = state.settings.providers(kind);
+    let disabled = library::Provider::ALL
+        .into_iter()
+        .filter(|provider| provider.supports(kind) && !enabled.contains(provider));
+    let has_key = |provider: library::Provider| match provider {
+        library::Provider::Tmdb => !state.settings.tmdb_secret.trim().is_empty(),
+        library::Provider::Tvmaze => true,
+        library::Provider::Omdb => !state.settings.omdb_key.trim().is_empty(),
+        library::Provider::Tvdb => !state.settings.tvdb_key.trim().is_empty(),
+        library::Provider::Local => true,
+    };
+
+    let entry = move |provider: library::Provider,
+                      index: Option<usize>|
+          -> iced::Element<'a, SettingsMessage> {
+        row![]
+            .align_y(iced::Alignment::Center)
+            .spacing(5.0)
+            .push(rich_checkbox(
+                text(provider.name()),
+                index.is_some(),
+                move |enabled| SettingsMessage::ToggleProvider(kind, provider, enabled),
+            ))
+            .push(index.map(|index| {
+                row![]
+                    .push(
+                        button(icon(0xe316).size(20.0))
+                            .style(themed_button)
+                            .on_press_maybe(
+                                (index > 0).then_some(SettingsMessage::RaiseProvider(kind, index)),
+                            ),
+                    )
+                    .push(
+                        button(icon(0xe313).size(20.0))
+                            .style(themed_button)
+                            .on_press_maybe(
+                                (index + 1 < enabled.len())
+                                    .then_some(SettingsMessage::RaiseProvider(kind, index + 1)),
+                            ),
+                    )
+            }))
+            .push(
+                (!has_key(provider))
+                    .then(|| text("Needs an API key").size(13.0).color(greyscale(150))),
+            )
+            .into()
+    };
+
+    row![]
+        .push(text(label).width(iced::Length::FillPortion(1)))
+        .push(
+            column![]
+                .width(iced::Length::FillPortion(2))
+                .spacing(5.0)
+                .extend(
+                    enabled
+                        .iter()
+                        .enumerate()
+                        .map(|(index, provider)| entry(*provider, Some(index))),
+                )
+                .extend(disabled.map(|provider| entry(provider, None))),
+        )
+        .into()
+}
+
+fn language_row<'a>(
+    label: &'a str,
+    placeholder: &'a str,
+    value: &'a str,
+    on_input: fn(String) -> SettingsMessage,
+) -> iced::Element<'a, SettingsMessage> {
+    row![]
+        .align_y(iced::Alignment::Center)
+        .push(text(label).width(iced::Length::FillPortion(1)))
+        .push(
+            row![]
+                .align_y(iced::Alignment::Center)
+                .width(iced::Length::FillPortion(2))
+                .spacing(5.0)
+                .push(
+                    text_input(placeholder, value)
+                        .width(100)
+                        .on_input(on_input)
+                        .style(themed_text_input),
+                )
+                .push(space::horizontal()),
+        )
+        .into()
+}
+
+fn endpoint_row<'a>(
+    label: &'a str,
+    placeholder: &'a str,
+    value: &'a str,
+    var: &'static str,
+    on_input: fn(String) -> SettingsMessage,
+) -> iced::Element<'a, SettingsMessage> {
+    let overridden = std::env::var(var).is_ok_and(|base| !base.trim().is_empty());
+
+    row![]
+        .align_y(iced::Alignment::Center)
+        .push(text(label).width(iced::Length::FillPortion(1)))
+        .push(
+            column![]
+                .width(iced::Length::FillPortion(2))
+                .spacing(5.0)
+                .push(
+                    text_input(placeholder, value)
+                        .on_input(on_input)
+                        .style(themed_text_input)
+                        .font(MONO_FONT),
+                )
+                .push(overridden.then(|| {
+                    text(format!("Overridden by the {} environment variable", var))
+                        .size(13.0)
+                        .color(greyscale(150))
+                })),
+        )
+        .into()
+}
+
 #[derive(Debug, Clone)]
 pub enum SettingsMessage {
     Back,
     AddDirectory,
     AddDirectoryResponse(Option<PathBuf>),
     RemoveDirectory(usize),
+    DirectoryLanguage(PathBuf, String),
     ApiSecretInput(String),
+    OmdbKeyInput(String),
+    TvdbKeyInput(String),
+    ToggleProvider(library::MediaKind, library::Provider, bool),
+    /// Moves the provider at the index up by one.
+    RaiseProvider(library::MediaKind, usize),
     WatchThresholdMovies(String),
     WatchThresholdEpisodes(String),
+    SpecialsInAiringOrder(bool),
     SubtitleOpacity(f32),
     SubtitleSize(f32),
     ThumbnailInterval(u32),
+    RefreshInterval(String),
+    MetadataLanguage(String),
+    FallbackLanguage(String),
+    MetadataRegion(String),
+    ToggleAdvanced,
+    ApiBase(String),
+    ImageBase(String),
+    ClearMetadataCache,
+    MetadataCacheCleared(Result<u64, String>),
+    CollectArtwork,
+    ArtworkCollected(Result<u64, String>),
 }